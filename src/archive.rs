@@ -0,0 +1,208 @@
+//! Exports a set of icons into a zip archive along with a manifest describing its contents
+
+use std::{
+    collections::HashSet,
+    io::{Seek, Write},
+};
+
+use skrifa::{instance::LocationRef, FontRef};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    batch::BatchFormat, document::IconDocument, error::ArchiveError, icon2svg::DrawOptions,
+    iconid::IconIdentifier, json, pathstyle::PathStyle,
+};
+
+/// Name of the manifest written at the root of every archive
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+pub struct ArchiveOptions<'a> {
+    icons: Vec<IconIdentifier>,
+    formats: Vec<BatchFormat>,
+    sizes: Vec<f32>,
+    location: LocationRef<'a>,
+    style: PathStyle,
+}
+
+impl<'a> ArchiveOptions<'a> {
+    pub fn new(location: LocationRef<'a>, style: PathStyle) -> ArchiveOptions<'a> {
+        ArchiveOptions {
+            icons: Vec::new(),
+            formats: Vec::new(),
+            sizes: Vec::new(),
+            location,
+            style,
+        }
+    }
+
+    pub fn with_icon(mut self, icon: IconIdentifier) -> Self {
+        self.icons.push(icon);
+        self
+    }
+
+    pub fn with_format(mut self, format: BatchFormat) -> Self {
+        self.formats.push(format);
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.sizes.push(size);
+        self
+    }
+}
+
+/// Writes every combination of icon, format and size to `writer` as a zip archive.
+///
+/// Files are named `<format>/<icon>_<size>px.<ext>`, e.g. `svg/mail_24px.svg`, with icon names
+/// escaped as for [crate::batch::BatchExporter], and are listed in [MANIFEST_NAME].
+/// Combinations that would produce a file already written, e.g. an icon or size given twice,
+/// are skipped. Pass a `Cursor<Vec<u8>>` to build the archive in memory.
+pub fn write_zip<W: Write + Seek>(
+    font: &FontRef,
+    options: &ArchiveOptions,
+    writer: W,
+) -> Result<W, ArchiveError> {
+    let file_options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(writer);
+    let mut manifest = Vec::new();
+    let mut written = HashSet::new();
+
+    for icon in options.icons.iter() {
+        let stem = icon.path_stem();
+        for format in options.formats.iter() {
+            for size in options.sizes.iter() {
+                let path = format!("{0}/{stem}_{size}px.{0}", format.extension());
+                if !written.insert(path.clone()) {
+                    continue;
+                }
+                let draw_options =
+                    DrawOptions::new(icon.clone(), *size, options.location, options.style);
+                let document = IconDocument::draw(font, &draw_options)?;
+                let content = format.write(&document, &draw_options);
+                zip.start_file(path.as_str(), file_options)?;
+                zip.write_all(&content)
+                    .map_err(|e| ArchiveError::IoError(path.clone(), e))?;
+                manifest.push(format!(
+                    "    {{\"icon\": {}, \"format\": \"{}\", \"size\": {size}, \"path\": {}}}",
+                    json::string(&stem),
+                    format.extension(),
+                    json::string(&path)
+                ));
+            }
+        }
+    }
+
+    zip.start_file(MANIFEST_NAME, file_options)?;
+    let manifest = format!("{{\n  \"files\": [\n{}\n  ]\n}}\n", manifest.join(",\n"));
+    zip.write_all(manifest.as_bytes())
+        .map_err(|e| ArchiveError::IoError(MANIFEST_NAME.to_string(), e))?;
+    Ok(zip.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use skrifa::{instance::Location, FontRef, MetadataProvider};
+    use zip::ZipArchive;
+
+    use crate::{
+        archive::{write_zip, ArchiveOptions, MANIFEST_NAME},
+        batch::{tests::font_with_icon_named, BatchFormat},
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut content = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    #[test]
+    fn zip_icons_at_sizes() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+        let options = ArchiveOptions::new((&loc).into(), PathStyle::Unchanged)
+            .with_icon(iconid::MAIL.clone())
+            .with_icon(IconIdentifier::Name("mail".into()))
+            .with_icon(IconIdentifier::Name("mail".into()))
+            .with_format(BatchFormat::Svg)
+            .with_format(BatchFormat::Pdf)
+            .with_size(24.0)
+            .with_size(48.0)
+            .with_size(24.0);
+
+        let zip = write_zip(&font, &options, Cursor::new(Vec::new())).unwrap();
+        let mut archive = ZipArchive::new(zip).unwrap();
+
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                "manifest.json",
+                "pdf/mail_24px.pdf",
+                "pdf/mail_48px.pdf",
+                "pdf/ue158_24px.pdf",
+                "pdf/ue158_48px.pdf",
+                "svg/mail_24px.svg",
+                "svg/mail_48px.svg",
+                "svg/ue158_24px.svg",
+                "svg/ue158_48px.svg",
+            ],
+            names
+        );
+        assert_eq!(
+            testdata::MAIL_SVG.trim(),
+            read_entry(&mut archive, "svg/mail_24px.svg")
+        );
+        let manifest = read_entry(&mut archive, MANIFEST_NAME);
+        assert!(manifest.contains(
+            "{\"icon\": \"mail\", \"format\": \"svg\", \"size\": 48, \"path\": \"svg/mail_48px.svg\"}"
+        ));
+        assert_eq!(8, manifest.matches("\"path\"").count());
+    }
+
+    #[test]
+    fn zip_missing_icon_fails() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = Location::default();
+        let options = ArchiveOptions::new((&loc).into(), PathStyle::Unchanged)
+            .with_icon(IconIdentifier::Name("no_such_icon".into()))
+            .with_format(BatchFormat::Svg)
+            .with_size(24.0);
+
+        assert!(write_zip(&font, &options, Cursor::new(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn zip_entries_stay_in_archive() {
+        let font_data = font_with_icon_named("../lan");
+        let font = FontRef::new(&font_data).unwrap();
+        let loc = Location::default();
+        let options = ArchiveOptions::new((&loc).into(), PathStyle::Compact)
+            .with_icon(IconIdentifier::Name("../lan".into()))
+            .with_format(BatchFormat::Svg)
+            .with_size(24.0);
+
+        let zip = write_zip(&font, &options, Cursor::new(Vec::new())).unwrap();
+        let archive = ZipArchive::new(zip).unwrap();
+
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            vec!["manifest.json", "svg/-u002e-u002e-u002flan_24px.svg"],
+            names
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Describes the variation axes and named instances of a font, e.g. to build a location picker
+
+use skrifa::{setting::VariationSetting, string::StringId, FontRef, MetadataProvider, Tag};
+
+use crate::error::LocationError;
+
+/// A variation axis in user coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisInfo {
+    pub tag: Tag,
+    /// From the name table, e.g. `Weight`
+    pub name: Option<String>,
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+    /// Whether the font asks for the axis not to be shown to users
+    pub hidden: bool,
+}
+
+impl AxisInfo {
+    pub fn contains(&self, value: f32) -> bool {
+        let (min, max) = self.range();
+        (min..=max).contains(&value)
+    }
+
+    pub fn clamp(&self, value: f32) -> f32 {
+        let (min, max) = self.range();
+        value.clamp(min, max)
+    }
+
+    /// Min and max in order, a malformed fvar may list them the other way round
+    fn range(&self) -> (f32, f32) {
+        (self.min.min(self.max), self.max.max(self.min))
+    }
+}
+
+/// A named instance, e.g. `Bold`, as user coordinates for every axis
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedInstanceInfo {
+    pub name: Option<String>,
+    pub postscript_name: Option<String>,
+    /// In axis order
+    pub coords: Vec<VariationSetting>,
+}
+
+/// The axes of `font` in fvar order, empty if it doesn't vary
+pub fn axes(font: &FontRef) -> Vec<AxisInfo> {
+    font.axes()
+        .iter()
+        .map(|axis| AxisInfo {
+            tag: axis.tag(),
+            name: name(font, axis.name_id()),
+            min: axis.min_value(),
+            default: axis.default_value(),
+            max: axis.max_value(),
+            hidden: axis.is_hidden(),
+        })
+        .collect()
+}
+
+/// The named instances of `font` in fvar order
+pub fn named_instances(font: &FontRef) -> Vec<NamedInstanceInfo> {
+    let tags: Vec<_> = font.axes().iter().map(|axis| axis.tag()).collect();
+    font.named_instances()
+        .iter()
+        .map(|instance| NamedInstanceInfo {
+            name: name(font, instance.subfamily_name_id()),
+            postscript_name: instance
+                .postscript_name_id()
+                .and_then(|name_id| name(font, name_id)),
+            coords: tags
+                .iter()
+                .zip(instance.user_coords())
+                .map(|(tag, value)| (*tag, value).into())
+                .collect(),
+        })
+        .collect()
+}
+
+/// Clamps each of `coords` to its axis range, failing on an axis the font doesn't have
+pub fn clamp_location<I>(font: &FontRef, coords: I) -> Result<Vec<VariationSetting>, LocationError>
+where
+    I: IntoIterator,
+    I::Item: Into<VariationSetting>,
+{
+    let axes = axes(font);
+    coords
+        .into_iter()
+        .map(|setting| {
+            let setting = setting.into();
+            let axis = find_axis(&axes, setting.selector)?;
+            Ok((setting.selector, axis.clamp(setting.value)).into())
+        })
+        .collect()
+}
+
+/// Checks that each of `coords` names an axis of the font and is within its range
+pub fn validate_location<I>(font: &FontRef, coords: I) -> Result<(), LocationError>
+where
+    I: IntoIterator,
+    I::Item: Into<VariationSetting>,
+{
+    let axes = axes(font);
+    for setting in coords {
+        let setting = setting.into();
+        let axis = find_axis(&axes, setting.selector)?;
+        if !axis.contains(setting.value) {
+            return Err(LocationError::OutOfRange(
+                setting.selector,
+                setting.value,
+                axis.min,
+                axis.max,
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn find_axis(axes: &[AxisInfo], tag: Tag) -> Result<&AxisInfo, LocationError> {
+    axes.iter()
+        .find(|axis| axis.tag == tag)
+        .ok_or(LocationError::NoAxis(tag))
+}
+
+fn name(font: &FontRef, name_id: StringId) -> Option<String> {
+    font.localized_strings(name_id)
+        .english_or_first()
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, Tag};
+
+    use crate::{
+        axes::{axes, clamp_location, named_instances, validate_location, AxisInfo},
+        error::LocationError,
+        testdata,
+    };
+
+    #[test]
+    fn icon_font_axes() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let axes = axes(&font);
+
+        assert_eq!(
+            vec![
+                (Tag::new(b"FILL"), 0.0, 0.0, 1.0),
+                (Tag::new(b"GRAD"), -50.0, 0.0, 200.0),
+                (Tag::new(b"opsz"), 20.0, 24.0, 48.0),
+                (Tag::new(b"wght"), 100.0, 400.0, 700.0),
+            ],
+            axes.iter()
+                .map(|a| (a.tag, a.min, a.default, a.max))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("Weight"), axes[3].name.as_deref());
+    }
+
+    #[test]
+    fn icon_font_named_instances() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let instances = named_instances(&font);
+
+        assert_eq!(
+            vec![
+                "Thin",
+                "ExtraLight",
+                "Light",
+                "Regular",
+                "Medium",
+                "SemiBold",
+                "Bold"
+            ],
+            instances
+                .iter()
+                .filter_map(|i| i.name.as_deref())
+                .collect::<Vec<_>>()
+        );
+        let bold = &instances[6];
+        assert_eq!(
+            vec![
+                (Tag::new(b"FILL"), 0.0),
+                (Tag::new(b"GRAD"), 0.0),
+                (Tag::new(b"opsz"), 24.0),
+                (Tag::new(b"wght"), 700.0),
+            ],
+            bold.coords
+                .iter()
+                .map(|s| (s.selector, s.value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clamp_inverted_range() {
+        let axis = AxisInfo {
+            tag: Tag::new(b"wght"),
+            name: None,
+            min: 700.0,
+            default: 400.0,
+            max: 100.0,
+            hidden: false,
+        };
+
+        assert_eq!(700.0, axis.clamp(900.0));
+        assert_eq!(100.0, axis.clamp(0.0));
+        assert_eq!(400.0, axis.clamp(400.0));
+        assert!(axis.contains(400.0));
+        assert!(!axis.contains(900.0));
+    }
+
+    #[test]
+    fn clamp_to_range() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let clamped = clamp_location(&font, [("wght", 900.0), ("FILL", 0.5)]).unwrap();
+
+        assert_eq!(
+            vec![(Tag::new(b"wght"), 700.0), (Tag::new(b"FILL"), 0.5)],
+            clamped
+                .iter()
+                .map(|s| (s.selector, s.value))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        assert!(validate_location(&font, [("wght", 700.0), ("opsz", 20.0)]).is_ok());
+        assert!(matches!(
+            validate_location(&font, [("wght", 900.0)]),
+            Err(LocationError::OutOfRange(tag, 900.0, 100.0, 700.0)) if tag == Tag::new(b"wght")
+        ));
+        assert!(matches!(
+            clamp_location(&font, [("wdth", 100.0)]),
+            Err(LocationError::NoAxis(tag)) if tag == Tag::new(b"wdth")
+        ));
+    }
+}
@@ -0,0 +1,717 @@
+//! Renders many icons in many formats, sizes and locations in parallel
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use rayon::prelude::*;
+use skrifa::{instance::LocationRef, FontRef, GlyphId, MetadataProvider};
+
+use crate::{
+    document::IconDocument,
+    error::{BatchError, DrawSvgError, IconResolutionError},
+    icon2eps::write_eps,
+    icon2pdf::write_pdf,
+    icon2svg::{write_svg, DrawOptions, Fallback},
+    iconid::{is_pua, IconIdentifier, Icons},
+    pathstyle::PathStyle,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchFormat {
+    Svg,
+    Eps,
+    Pdf,
+}
+
+impl BatchFormat {
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            BatchFormat::Svg => "svg",
+            BatchFormat::Eps => "eps",
+            BatchFormat::Pdf => "pdf",
+        }
+    }
+
+    /// Serializes `document`, drawn with `options`, in this format
+    pub(crate) fn write(&self, document: &IconDocument, options: &DrawOptions<'_>) -> Vec<u8> {
+        match self {
+            BatchFormat::Svg => write_svg(document, options).into_bytes(),
+            BatchFormat::Eps => write_eps(document, &options.identifier).into_bytes(),
+            BatchFormat::Pdf => write_pdf(document, &options.identifier),
+        }
+    }
+}
+
+/// One file to be produced by a [BatchExporter]
+#[derive(Clone, Debug)]
+pub struct BatchItem {
+    pub identifier: IconIdentifier,
+    pub format: BatchFormat,
+    pub size: f32,
+    /// Index into the exporter's locations
+    pub location: usize,
+    /// Where the sink should put the output, e.g. `svg/mail_24px.svg`
+    pub path: String,
+}
+
+/// Reported as each item finishes, in whatever order that happens
+pub struct BatchProgress<'a> {
+    pub item: &'a BatchItem,
+    pub completed: usize,
+    pub total: usize,
+    pub error: Option<&'a BatchError>,
+}
+
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Includes items where a fallback was drawn
+    pub succeeded: usize,
+    pub failures: Vec<(BatchItem, BatchError)>,
+    /// Items where a fallback was drawn, with the error it replaced
+    pub warnings: Vec<(BatchItem, DrawSvgError)>,
+}
+
+/// Receives output from a [BatchExporter], called from many threads at once
+pub trait BatchSink: Sync {
+    fn write(&self, path: &str, content: &[u8]) -> io::Result<()>;
+}
+
+/// Writes output to files under a directory, creating subdirectories as needed.
+///
+/// Paths must be relative and stay within the directory, others fail with
+/// [io::ErrorKind::InvalidInput].
+pub struct DirSink {
+    root: PathBuf,
+}
+
+impl DirSink {
+    pub fn new(root: impl Into<PathBuf>) -> DirSink {
+        DirSink { root: root.into() }
+    }
+}
+
+impl BatchSink for DirSink {
+    fn write(&self, path: &str, content: &[u8]) -> io::Result<()> {
+        if !Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(..)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{path} is not a path within the output directory"),
+            ));
+        }
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+}
+
+/// Keeps output in memory, keyed by path
+#[derive(Default)]
+pub struct MemorySink {
+    files: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> MemorySink {
+        Default::default()
+    }
+
+    pub fn into_files(self) -> BTreeMap<String, Vec<u8>> {
+        self.files.into_inner().unwrap()
+    }
+}
+
+impl BatchSink for MemorySink {
+    fn write(&self, path: &str, content: &[u8]) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), content.to_vec());
+        Ok(())
+    }
+}
+
+/// Renders every combination of icon, format, size and location.
+///
+/// Output is named `<format>/<icon>_<size>px.<ext>`; with more than one location a
+/// `_loc<index>` suffix is added before the extension. Characters of icon names other than
+/// ASCII letters, digits, `_` and `-` are escaped, e.g. `a/b` becomes `a-u002fb`.
+pub struct BatchExporter<'a> {
+    font: FontRef<'a>,
+    icons: Vec<IconIdentifier>,
+    formats: Vec<BatchFormat>,
+    sizes: Vec<f32>,
+    locations: Vec<LocationRef<'a>>,
+    style: PathStyle,
+    fallback: Fallback,
+    canonical: bool,
+    optical_size: bool,
+    threads: Option<usize>,
+}
+
+impl<'a> BatchExporter<'a> {
+    pub fn new(font: FontRef<'a>, style: PathStyle) -> BatchExporter<'a> {
+        BatchExporter {
+            font,
+            icons: Vec::new(),
+            formats: Vec::new(),
+            sizes: Vec::new(),
+            locations: Vec::new(),
+            style,
+            fallback: Fallback::Error,
+            canonical: false,
+            optical_size: false,
+            threads: None,
+        }
+    }
+
+    pub fn with_icons(mut self, icons: impl IntoIterator<Item = IconIdentifier>) -> Self {
+        self.icons.extend(icons);
+        self
+    }
+
+    pub fn with_format(mut self, format: BatchFormat) -> Self {
+        self.formats.push(format);
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.sizes.push(size);
+        self
+    }
+
+    /// Adds a location to draw at, if none are given icons are drawn at the default location
+    pub fn with_location(mut self, location: LocationRef<'a>) -> Self {
+        self.locations.push(location);
+        self
+    }
+
+    /// Draws `fallback` for missing icons, they are reported as warnings rather than failures
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// See [DrawOptions::with_canonical_output]
+    pub fn with_canonical_output(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// See [DrawOptions::with_automatic_optical_size], opsz follows each size
+    pub fn with_automatic_optical_size(mut self, optical_size: bool) -> Self {
+        self.optical_size = optical_size;
+        self
+    }
+
+    /// Renders on a dedicated pool of `threads` threads rather than rayon's global pool
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    fn locations(&self) -> Vec<LocationRef<'a>> {
+        if self.locations.is_empty() {
+            vec![LocationRef::default()]
+        } else {
+            self.locations.clone()
+        }
+    }
+
+    pub fn items(&self) -> Vec<BatchItem> {
+        let num_locations = self.locations().len();
+        let mut items = Vec::new();
+        for identifier in self.icons.iter() {
+            let stem = identifier.path_stem();
+            for format in self.formats.iter() {
+                for size in self.sizes.iter() {
+                    for location in 0..num_locations {
+                        let suffix = if num_locations > 1 {
+                            format!("_loc{location}")
+                        } else {
+                            String::new()
+                        };
+                        items.push(BatchItem {
+                            identifier: identifier.clone(),
+                            format: *format,
+                            size: *size,
+                            location,
+                            path: format!("{0}/{stem}_{size}px{suffix}.{0}", format.extension()),
+                        });
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    pub fn run(&self, sink: &impl BatchSink) -> Result<BatchReport, BatchError> {
+        self.run_with_progress(sink, |_| {})
+    }
+
+    /// Renders items in parallel, a failed item is reported and the rest continue.
+    ///
+    /// Only fails if the pool for [BatchExporter::with_threads] can't be built.
+    pub fn run_with_progress(
+        &self,
+        sink: &impl BatchSink,
+        progress: impl Fn(BatchProgress) + Sync,
+    ) -> Result<BatchReport, BatchError> {
+        let progress = &progress;
+        let run = || self.run_items(sink, progress);
+        let Some(threads) = self.threads else {
+            return Ok(run());
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(BatchError::ThreadPoolError)?;
+        Ok(pool.install(run))
+    }
+
+    fn run_items(
+        &self,
+        sink: &impl BatchSink,
+        progress: impl Fn(BatchProgress) + Sync,
+    ) -> BatchReport {
+        let locations = self.locations();
+        let items = self.items();
+        let total = items.len();
+        let completed = AtomicUsize::new(0);
+
+        let results: Vec<_> = items
+            .into_par_iter()
+            .map(|item| {
+                let result = self.export(&item, locations[item.location], sink);
+                progress(BatchProgress {
+                    item: &item,
+                    completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                    total,
+                    error: result.as_ref().err(),
+                });
+                (item, result)
+            })
+            .collect();
+
+        let mut report = BatchReport::default();
+        for (item, result) in results {
+            match result {
+                Ok(None) => report.succeeded += 1,
+                Ok(Some(warning)) => {
+                    report.succeeded += 1;
+                    report.warnings.push((item, warning));
+                }
+                Err(e) => report.failures.push((item, e)),
+            }
+        }
+        report
+    }
+
+    fn export(
+        &self,
+        item: &BatchItem,
+        location: LocationRef,
+        sink: &impl BatchSink,
+    ) -> Result<Option<DrawSvgError>, BatchError> {
+        let options = DrawOptions::new(item.identifier.clone(), item.size, location, self.style)
+            .with_fallback(self.fallback.clone())
+            .with_canonical_output(self.canonical)
+            .with_automatic_optical_size(self.optical_size);
+        let mut document = IconDocument::draw(&self.font, &options)?;
+        let warning = document.fallback_for.take();
+        let content = item.format.write(&document, &options);
+        sink.write(&item.path, &content)
+            .map_err(|e| BatchError::SinkError(item.path.clone(), e))?;
+        Ok(warning)
+    }
+}
+
+/// Every icon in `font` with a name matching `pattern`, sorted by name, for use with
+/// [BatchExporter::with_icons] to export a whole font or part of one.
+///
+/// In `pattern` `*` matches any run of characters and `?` any one character, so `*` selects
+/// every icon. Icons are identified by their first matching name. Icons the font gives no name,
+/// only a private use codepoint, are matched and sorted on the name their output is given,
+/// e.g. `ue158`, and identified by that codepoint.
+pub fn icons_matching(
+    font: &FontRef,
+    pattern: &str,
+) -> Result<Vec<IconIdentifier>, IconResolutionError> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let matches = |name: &str| glob_match(&pattern, &name.chars().collect::<Vec<_>>());
+    let icons = font.icons()?;
+    let named: HashSet<GlyphId> = icons.iter().map(|icon| icon.gid).collect();
+    let mut unnamed: BTreeMap<GlyphId, u32> = BTreeMap::new();
+    for (codepoint, gid) in font.charmap().mappings() {
+        if is_pua(codepoint) && gid != GlyphId::NOTDEF && !named.contains(&gid) {
+            unnamed.entry(gid).or_insert(codepoint);
+        }
+    }
+
+    let mut found: Vec<_> = icons
+        .into_iter()
+        .filter_map(|icon| icon.names.into_iter().find(|name| matches(name)))
+        .map(|name| (name.clone(), IconIdentifier::Name(name.into())))
+        .chain(unnamed.into_values().filter_map(|codepoint| {
+            let identifier = IconIdentifier::Codepoint(codepoint);
+            let stem = identifier.file_stem();
+            matches(&stem).then_some((stem, identifier))
+        }))
+        .collect();
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(found
+        .into_iter()
+        .map(|(_, identifier)| identifier)
+        .collect())
+}
+
+/// Whether `text` matches `pattern` in full, backtracking only to the most recent `*`
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The pattern index after the last '*' and the text index it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                star = Some((p, t));
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => {
+                // Let the last '*' swallow one more character and retry
+                let Some((star_p, star_t)) = star else {
+                    return false;
+                };
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, t));
+            }
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{io, sync::Mutex};
+
+    use skrifa::{FontRef, GlyphId, MetadataProvider};
+    use write_fonts::{
+        tables::{
+            cmap::Cmap,
+            gsub::{
+                Gsub, Ligature, LigatureSet, LigatureSubstFormat1, SubstitutionLookup,
+                SubstitutionLookupList,
+            },
+            layout::{CoverageTable, Feature, FeatureList, FeatureRecord, Lookup, LookupFlag},
+        },
+        types::Tag,
+        FontBuilder,
+    };
+
+    use crate::{
+        batch::{
+            glob_match, icons_matching, BatchExporter, BatchFormat, BatchSink, DirSink, MemorySink,
+        },
+        icon2svg::Fallback,
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    /// ICON_FONT where the only ligature names the lan icon, gid 3, `name`. Characters of
+    /// `name` ICON_FONT doesn't map are given glyphs no other character maps to.
+    pub(crate) fn font_with_icon_named(name: &str) -> Vec<u8> {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let mut mappings: Vec<(char, GlyphId)> = font
+            .charmap()
+            .mappings()
+            .map(|(codepoint, gid)| (char::from_u32(codepoint).unwrap(), gid))
+            .collect();
+        // Glyphs only reached by substitution
+        let mut spare = [GlyphId::new(2), GlyphId::new(4)].into_iter();
+        let gids: Vec<GlyphId> = name
+            .chars()
+            .map(|c| match mappings.iter().find(|(mapped, _)| *mapped == c) {
+                Some((_, gid)) => *gid,
+                None => {
+                    let gid = spare.next().expect("too many unmapped characters");
+                    mappings.push((c, gid));
+                    gid
+                }
+            })
+            .collect();
+        let cmap = Cmap::from_mappings(mappings).unwrap();
+        let lookups = SubstitutionLookupList::new(vec![SubstitutionLookup::Ligature(Lookup::new(
+            LookupFlag::empty(),
+            vec![LigatureSubstFormat1::new(
+                CoverageTable::format_1(vec![gids[0]]),
+                vec![LigatureSet::new(vec![Ligature::new(
+                    GlyphId::new(3),
+                    gids[1..].to_vec(),
+                )])],
+            )],
+            0,
+        ))]);
+        let features = FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"liga"),
+            Feature::new(None, vec![0]),
+        )]);
+        let gsub = Gsub::new(Default::default(), features, lookups);
+        FontBuilder::new()
+            .add_table(&cmap)
+            .unwrap()
+            .add_table(&gsub)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    #[test]
+    fn export_every_combination() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+        let exporter = BatchExporter::new(font, PathStyle::Unchanged)
+            .with_icons([iconid::MAIL.clone(), iconid::LAN.clone()])
+            .with_format(BatchFormat::Svg)
+            .with_format(BatchFormat::Eps)
+            .with_format(BatchFormat::Pdf)
+            .with_size(24.0)
+            .with_location((&loc).into());
+        let sink = MemorySink::new();
+
+        let report = exporter.run(&sink).unwrap();
+
+        assert_eq!(6, report.succeeded);
+        assert!(report.failures.is_empty());
+        let files = sink.into_files();
+        assert_eq!(
+            vec![
+                "eps/lan_24px.eps",
+                "eps/ue158_24px.eps",
+                "pdf/lan_24px.pdf",
+                "pdf/ue158_24px.pdf",
+                "svg/lan_24px.svg",
+                "svg/ue158_24px.svg"
+            ],
+            files.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            testdata::LAN_SVG.trim().as_bytes(),
+            files["svg/lan_24px.svg"]
+        );
+    }
+
+    #[test]
+    fn failures_are_reported_per_item() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let filled = font.axes().location(&[("FILL", 1.0)]);
+        let unfilled = font.axes().location(&[("FILL", 0.0)]);
+        let exporter = BatchExporter::new(font, PathStyle::Compact)
+            .with_icons([
+                IconIdentifier::Name("no_such_icon".into()),
+                iconid::MAIL.clone(),
+            ])
+            .with_format(BatchFormat::Svg)
+            .with_size(24.0)
+            .with_location((&unfilled).into())
+            .with_location((&filled).into());
+        let sink = MemorySink::new();
+        let progress = Mutex::new(Vec::new());
+
+        let report = exporter
+            .run_with_progress(&sink, |p| {
+                progress
+                    .lock()
+                    .unwrap()
+                    .push((p.completed, p.total, p.error.is_some()))
+            })
+            .unwrap();
+
+        assert_eq!(2, report.succeeded);
+        assert_eq!(2, report.failures.len());
+        let mut progress = progress.into_inner().unwrap();
+        progress.sort();
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            progress.iter().map(|p| p.0).collect::<Vec<_>>()
+        );
+        assert_eq!(2, progress.iter().filter(|p| p.2).count());
+        assert_eq!(
+            vec!["svg/ue158_24px_loc0.svg", "svg/ue158_24px_loc1.svg"],
+            sink.into_files().keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fallbacks_are_warnings() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let exporter = BatchExporter::new(font, PathStyle::Compact)
+            .with_icons([
+                IconIdentifier::Name("no_such_icon".into()),
+                iconid::LAN.clone(),
+            ])
+            .with_format(BatchFormat::Svg)
+            .with_format(BatchFormat::Eps)
+            .with_size(24.0)
+            .with_fallback(Fallback::Notdef);
+        let sink = MemorySink::new();
+
+        let report = exporter.run(&sink).unwrap();
+
+        assert_eq!(4, report.succeeded);
+        assert!(report.failures.is_empty());
+        let mut warned: Vec<_> = report.warnings.iter().map(|(i, _)| &i.path).collect();
+        warned.sort();
+        assert_eq!(
+            vec!["eps/no_such_icon_24px.eps", "svg/no_such_icon_24px.svg"],
+            warned
+        );
+        assert_eq!(4, sink.into_files().len());
+    }
+
+    #[test]
+    fn export_matching_icons() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let icons = icons_matching(&font, "arrow_*").unwrap();
+        assert!(!icons.is_empty());
+        let exporter = BatchExporter::new(font, PathStyle::Compact)
+            .with_icons(icons.clone())
+            .with_format(BatchFormat::Svg)
+            .with_size(24.0)
+            .with_threads(2);
+        let sink = MemorySink::new();
+
+        let report = exporter.run(&sink).unwrap();
+
+        assert_eq!(icons.len(), report.succeeded);
+        assert!(report.failures.is_empty());
+        assert!(sink
+            .into_files()
+            .keys()
+            .all(|path| path.starts_with("svg/arrow_")));
+    }
+
+    #[test]
+    fn all_icons() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        assert_eq!(
+            vec![
+                IconIdentifier::Name("lan".into()),
+                IconIdentifier::Name("mail".into()),
+                IconIdentifier::Name("man".into()),
+            ],
+            icons_matching(&font, "*").unwrap()
+        );
+    }
+
+    #[test]
+    fn globs() {
+        let matches = |pattern: &str, text: &str| {
+            glob_match(
+                &pattern.chars().collect::<Vec<_>>(),
+                &text.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(matches("*", ""));
+        assert!(matches("arrow_*", "arrow_back"));
+        assert!(matches("*_back", "arrow_back"));
+        assert!(matches("m?n", "man"));
+        assert!(!matches("m?n", "mail"));
+        assert!(!matches("arrow_*", "narrow_thing"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(matches("*a", "banana"));
+        assert!(matches("**", "x"));
+        assert!(!matches("a*b", "a"));
+        assert!(!matches("*?", ""));
+        // Would take exponential time without bounded backtracking
+        assert!(!matches("*a*a*a*a*a*a*a*a*b", &"a".repeat(64)));
+    }
+
+    #[test]
+    fn unnamed_icons_match_on_file_stem() {
+        // gid 4, the lan drawn at FILL 0.99, gets a codepoint but no ligature names it
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let cmap = Cmap::from_mappings(
+            font.charmap()
+                .mappings()
+                .map(|(codepoint, gid)| (char::from_u32(codepoint).unwrap(), gid))
+                .chain([('\u{f000}', GlyphId::new(4))]),
+        )
+        .unwrap();
+        let font_data = FontBuilder::new()
+            .add_table(&cmap)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build();
+        let font = FontRef::new(&font_data).unwrap();
+
+        let all = icons_matching(&font, "*").unwrap();
+        let unnamed = icons_matching(&font, "uf0??").unwrap();
+
+        assert_eq!(vec![IconIdentifier::Codepoint(0xf000)], unnamed);
+        assert!(all.contains(&IconIdentifier::Codepoint(0xf000)));
+        assert!(all.contains(&IconIdentifier::Name("lan".into())));
+    }
+
+    #[test]
+    fn font_names_stay_in_output_directory() {
+        let font_data = font_with_icon_named("../lan");
+        let font = FontRef::new(&font_data).unwrap();
+        let icons = icons_matching(&font, "*").unwrap();
+        let exporter = BatchExporter::new(font, PathStyle::Compact)
+            .with_icons(icons)
+            .with_format(BatchFormat::Svg)
+            .with_size(24.0);
+        let sink = MemorySink::new();
+
+        let report = exporter.run(&sink).unwrap();
+
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(
+            vec![
+                "svg/-u002e-u002e-u002flan_24px.svg",
+                "svg/ue158_24px.svg",
+                "svg/ue4eb_24px.svg"
+            ],
+            sink.into_files().keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn dir_sink_rejects_paths_outside_root() {
+        let sink = DirSink::new(std::env::temp_dir().join("sleipnir_no_such_dir"));
+
+        for path in [
+            "../escaped.svg",
+            "svg/../../escaped.svg",
+            "/escaped.svg",
+            "./a.svg",
+        ] {
+            assert_eq!(
+                io::ErrorKind::InvalidInput,
+                sink.write(path, b"").unwrap_err().kind(),
+                "{path}"
+            );
+        }
+    }
+}
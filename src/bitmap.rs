@@ -0,0 +1,463 @@
+//! Embedded bitmaps (CBDT/CBLC and sbix), for fonts that draw some or all glyphs as images
+
+use skrifa::{
+    raw::{
+        tables::{
+            bitmap::{BitmapContent, BitmapDataFormat, BitmapMetrics},
+            cbdt::Cbdt,
+            cblc::Cblc,
+            sbix::Sbix,
+        },
+        FontRef, ReadError, TableProvider, TopLevelTable,
+    },
+    GlyphId,
+};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StrikeSource {
+    Cbdt,
+    Sbix,
+}
+
+/// A set of bitmaps drawn for one pixel size
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Strike {
+    pub source: StrikeSource,
+    /// Index of the strike in its table
+    pub index: usize,
+    /// Pixels per em, vertically for CBLC
+    pub ppem: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Tiff,
+    /// Uncompressed pixels, rows padded to a byte boundary if `byte_aligned`
+    Packed {
+        bit_depth: u8,
+        byte_aligned: bool,
+    },
+}
+
+/// The image for a glyph in one strike, as stored in the font.
+///
+/// The image is not decoded, `data` holds the bytes the font stores, e.g. a whole png file, for
+/// the caller to embed or decode according to `format`. Positions are in pixels relative to the
+/// glyph origin, Y up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapGlyph<'a> {
+    pub strike: Strike,
+    pub format: ImageFormat,
+    /// The image as stored in the font, not decoded
+    pub data: &'a [u8],
+    /// Unknown for sbix jpeg and tiff images
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub left: i32,
+    pub bottom: i32,
+}
+
+/// Lists the strikes that have an image for `gid`, smallest first
+pub fn strikes(font: &FontRef, gid: GlyphId) -> Result<Vec<Strike>, ReadError> {
+    let mut strikes = Vec::new();
+    for strike in all_strikes(font)? {
+        if bitmap_glyph(font, strike, gid)?.is_some() {
+            strikes.push(strike);
+        }
+    }
+    strikes.sort_by_key(|s| (s.ppem, s.source == StrikeSource::Sbix, s.index));
+    Ok(strikes)
+}
+
+/// Picks the image for `gid` that best suits drawing at `pixel_size` pixels per em, as stored in
+/// the font.
+///
+/// That is the smallest strike at least as large as requested, so scaling is only ever down,
+/// or failing that the largest strike.
+pub fn select_bitmap<'a>(
+    font: &FontRef<'a>,
+    gid: GlyphId,
+    pixel_size: f32,
+) -> Result<Option<BitmapGlyph<'a>>, ReadError> {
+    let strikes = strikes(font, gid)?;
+    let best = strikes
+        .iter()
+        .find(|s| s.ppem as f32 >= pixel_size)
+        .or(strikes.last());
+    match best {
+        Some(strike) => bitmap_glyph(font, *strike, gid),
+        None => Ok(None),
+    }
+}
+
+/// The image for `gid` in `strike` as stored in the font, if it has one
+pub fn bitmap_glyph<'a>(
+    font: &FontRef<'a>,
+    strike: Strike,
+    gid: GlyphId,
+) -> Result<Option<BitmapGlyph<'a>>, ReadError> {
+    match strike.source {
+        StrikeSource::Cbdt => cbdt_glyph(font, strike, gid),
+        StrikeSource::Sbix => sbix_glyph(font, strike, gid, true),
+    }
+}
+
+fn all_strikes(font: &FontRef) -> Result<Vec<Strike>, ReadError> {
+    let mut strikes = Vec::new();
+    if font.table_data(Cblc::TAG).is_some() && font.table_data(Cbdt::TAG).is_some() {
+        for (index, size) in font.cblc()?.bitmap_sizes().iter().enumerate() {
+            strikes.push(Strike {
+                source: StrikeSource::Cbdt,
+                index,
+                ppem: size.ppem_y() as u16,
+            });
+        }
+    }
+    if font.table_data(Sbix::TAG).is_some() {
+        for (index, strike) in font.sbix()?.strikes().iter().enumerate() {
+            strikes.push(Strike {
+                source: StrikeSource::Sbix,
+                index,
+                ppem: strike?.ppem(),
+            });
+        }
+    }
+    Ok(strikes)
+}
+
+fn cbdt_glyph<'a>(
+    font: &FontRef<'a>,
+    strike: Strike,
+    gid: GlyphId,
+) -> Result<Option<BitmapGlyph<'a>>, ReadError> {
+    let cblc = font.cblc()?;
+    let size = cblc
+        .bitmap_sizes()
+        .get(strike.index)
+        .ok_or(ReadError::OutOfBounds)?;
+    // Glyphs outside the strike are reported as out of bounds
+    let Ok(location) = size.location(cblc.offset_data(), gid) else {
+        return Ok(None);
+    };
+    if location.is_empty() {
+        return Ok(None);
+    }
+    let bitmap = font.cbdt()?.data(&location)?;
+    let BitmapContent::Data(format, data) = bitmap.content else {
+        // Composites are only used by EBDT
+        return Ok(None);
+    };
+    let (width, height, left, top) = match bitmap.metrics {
+        BitmapMetrics::Small(m) => (m.width, m.height, m.bearing_x.get(), m.bearing_y.get()),
+        BitmapMetrics::Big(m) => (
+            m.width,
+            m.height,
+            m.hori_bearing_x.get(),
+            m.hori_bearing_y.get(),
+        ),
+    };
+    let format = match format {
+        BitmapDataFormat::Png => ImageFormat::Png,
+        BitmapDataFormat::ByteAligned => ImageFormat::Packed {
+            bit_depth: location.bit_depth,
+            byte_aligned: true,
+        },
+        BitmapDataFormat::BitAligned => ImageFormat::Packed {
+            bit_depth: location.bit_depth,
+            byte_aligned: false,
+        },
+    };
+    Ok(Some(BitmapGlyph {
+        strike,
+        format,
+        data,
+        width: Some(width as u32),
+        height: Some(height as u32),
+        left: left as i32,
+        bottom: top as i32 - height as i32,
+    }))
+}
+
+fn sbix_glyph<'a>(
+    font: &FontRef<'a>,
+    strike: Strike,
+    gid: GlyphId,
+    follow_dupe: bool,
+) -> Result<Option<BitmapGlyph<'a>>, ReadError> {
+    let sbix = font.sbix()?;
+    let Some(glyph) = sbix.strikes().get(strike.index)?.glyph_data(gid)? else {
+        return Ok(None);
+    };
+    let data = glyph.data();
+    let format = match &glyph.graphic_type().to_be_bytes() {
+        b"png " => ImageFormat::Png,
+        b"jpg " => ImageFormat::Jpeg,
+        b"tiff" => ImageFormat::Tiff,
+        b"dupe" if follow_dupe => {
+            // The data is the glyph id whose image to use
+            let [hi, lo] = data else {
+                return Err(ReadError::MalformedData("sbix dupe must be a glyph id"));
+            };
+            let target = GlyphId::new(u16::from_be_bytes([*hi, *lo]));
+            return sbix_glyph(font, strike, target, false);
+        }
+        _ => return Ok(None),
+    };
+    let (width, height) = match format {
+        ImageFormat::Png => png_size(data).unzip(),
+        _ => (None, None),
+    };
+    Ok(Some(BitmapGlyph {
+        strike,
+        format,
+        data,
+        width,
+        height,
+        left: glyph.origin_offset_x() as i32,
+        bottom: glyph.origin_offset_y() as i32,
+    }))
+}
+
+/// Reads the size from the IHDR chunk, which the PNG spec requires to come first
+fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+    let ihdr = data.strip_prefix(PNG_SIGNATURE)?;
+    if ihdr.get(4..8)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(ihdr.get(8..12)?.try_into().ok()?);
+    let height = u32::from_be_bytes(ihdr.get(12..16)?.try_into().ok()?);
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{
+        raw::{types::Tag, TableProvider},
+        FontRef, GlyphId,
+    };
+    use write_fonts::FontBuilder;
+
+    use crate::{
+        bitmap::{
+            png_size, select_bitmap, strikes, ImageFormat, Strike, StrikeSource, PNG_SIGNATURE,
+        },
+        testdata,
+    };
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend(13u32.to_be_bytes());
+        png.extend(b"IHDR");
+        png.extend(width.to_be_bytes());
+        png.extend(height.to_be_bytes());
+        png
+    }
+
+    /// An sbix with a strike per ppem where gid 1 is a png and gid 2 a dupe of it
+    fn sbix(num_glyphs: u16, ppems: &[u16]) -> Vec<u8> {
+        let mut strikes = Vec::new();
+        for ppem in ppems {
+            let glyphs: Vec<Vec<u8>> = (0..num_glyphs)
+                .map(|gid| {
+                    let (graphic_type, data) = match gid {
+                        1 => (b"png ", png(*ppem as u32, *ppem as u32)),
+                        2 => (b"dupe", 1u16.to_be_bytes().to_vec()),
+                        _ => return Vec::new(),
+                    };
+                    let mut glyph = Vec::new();
+                    glyph.extend(1i16.to_be_bytes());
+                    glyph.extend((-2i16).to_be_bytes());
+                    glyph.extend(graphic_type);
+                    glyph.extend(data);
+                    glyph
+                })
+                .collect();
+            let mut strike = Vec::new();
+            strike.extend(ppem.to_be_bytes());
+            strike.extend(72u16.to_be_bytes());
+            let mut offset = 4 + 4 * (num_glyphs as u32 + 1);
+            for glyph in glyphs.iter() {
+                strike.extend(offset.to_be_bytes());
+                offset += glyph.len() as u32;
+            }
+            strike.extend(offset.to_be_bytes());
+            strike.extend(glyphs.concat());
+            strikes.push(strike);
+        }
+        let mut sbix = Vec::new();
+        sbix.extend(1u16.to_be_bytes());
+        sbix.extend(1u16.to_be_bytes());
+        sbix.extend((strikes.len() as u32).to_be_bytes());
+        let mut offset = 8 + 4 * strikes.len() as u32;
+        for strike in strikes.iter() {
+            sbix.extend(offset.to_be_bytes());
+            offset += strike.len() as u32;
+        }
+        sbix.extend(strikes.concat());
+        sbix
+    }
+
+    /// A CBLC and CBDT with one strike where gid 1 is a png and gid 2 has no image
+    fn cblc_cbdt(ppem: u8) -> (Vec<u8>, Vec<u8>) {
+        let image = png(ppem as u32, ppem as u32);
+        let mut cbdt = Vec::new();
+        cbdt.extend(3u16.to_be_bytes());
+        cbdt.extend(0u16.to_be_bytes());
+        // Format 17, small metrics: height, width, bearing x, bearing y, advance
+        cbdt.extend([ppem, ppem, 1, ppem - 2, ppem]);
+        cbdt.extend((image.len() as u32).to_be_bytes());
+        cbdt.extend(&image);
+        let glyph_len = cbdt.len() as u32 - 4;
+
+        // Index subtable format 1, image format 17, image data from after the CBDT header
+        let mut index_subtable = Vec::new();
+        index_subtable.extend(1u16.to_be_bytes());
+        index_subtable.extend(17u16.to_be_bytes());
+        index_subtable.extend(4u32.to_be_bytes());
+        for offset in [0, glyph_len, glyph_len] {
+            index_subtable.extend(offset.to_be_bytes());
+        }
+        let mut index_subtables = Vec::new();
+        index_subtables.extend(1u16.to_be_bytes());
+        index_subtables.extend(2u16.to_be_bytes());
+        index_subtables.extend(8u32.to_be_bytes());
+        index_subtables.extend(index_subtable);
+
+        let mut cblc = Vec::new();
+        cblc.extend(3u16.to_be_bytes());
+        cblc.extend(0u16.to_be_bytes());
+        cblc.extend(1u32.to_be_bytes());
+        // The one bitmap size, its index subtables follow it
+        cblc.extend(56u32.to_be_bytes());
+        cblc.extend((index_subtables.len() as u32).to_be_bytes());
+        cblc.extend(1u32.to_be_bytes());
+        cblc.extend(0u32.to_be_bytes());
+        // Horizontal and vertical line metrics
+        cblc.extend([0; 24]);
+        cblc.extend(1u16.to_be_bytes());
+        cblc.extend(2u16.to_be_bytes());
+        // ppem x and y, bit depth, horizontal metrics flag
+        cblc.extend([ppem, ppem, 32, 1]);
+        cblc.extend(index_subtables);
+        (cblc, cbdt)
+    }
+
+    fn font_with_cbdt(ppem: u8) -> Vec<u8> {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let (cblc, cbdt) = cblc_cbdt(ppem);
+        FontBuilder::new()
+            .add_raw(Tag::new(b"CBLC"), cblc)
+            .add_raw(Tag::new(b"CBDT"), cbdt)
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    fn font_with_sbix(ppems: &[u16]) -> Vec<u8> {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let num_glyphs = font.maxp().unwrap().num_glyphs();
+        FontBuilder::new()
+            .add_raw(Tag::new(b"sbix"), sbix(num_glyphs, ppems))
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    #[test]
+    fn no_bitmaps() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        assert!(strikes(&font, GlyphId::new(1)).unwrap().is_empty());
+        assert_eq!(None, select_bitmap(&font, GlyphId::new(1), 24.0).unwrap());
+    }
+
+    #[test]
+    fn list_strikes() {
+        let data = font_with_sbix(&[40, 20]);
+        let font = FontRef::new(&data).unwrap();
+
+        assert_eq!(
+            vec![
+                Strike {
+                    source: StrikeSource::Sbix,
+                    index: 1,
+                    ppem: 20
+                },
+                Strike {
+                    source: StrikeSource::Sbix,
+                    index: 0,
+                    ppem: 40
+                },
+            ],
+            strikes(&font, GlyphId::new(1)).unwrap()
+        );
+        assert!(strikes(&font, GlyphId::new(3)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn select_smallest_strike_not_smaller_than_requested() {
+        let data = font_with_sbix(&[20, 40, 80]);
+        let font = FontRef::new(&data).unwrap();
+
+        let ppem = |size| {
+            select_bitmap(&font, GlyphId::new(1), size)
+                .unwrap()
+                .unwrap()
+                .strike
+                .ppem
+        };
+
+        assert_eq!(20, ppem(16.0));
+        assert_eq!(40, ppem(24.0));
+        assert_eq!(80, ppem(48.0));
+        assert_eq!(80, ppem(96.0));
+    }
+
+    #[test]
+    fn sbix_png() {
+        let data = font_with_sbix(&[20]);
+        let font = FontRef::new(&data).unwrap();
+
+        let glyph = select_bitmap(&font, GlyphId::new(2), 20.0)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(ImageFormat::Png, glyph.format);
+        assert_eq!(png(20, 20), glyph.data);
+        assert_eq!((Some(20), Some(20)), (glyph.width, glyph.height));
+        assert_eq!((1, -2), (glyph.left, glyph.bottom));
+    }
+
+    #[test]
+    fn png_sizes() {
+        assert_eq!(Some((16, 32)), png_size(&png(16, 32)));
+        assert_eq!(None, png_size(b"GIF89a"));
+    }
+
+    #[test]
+    fn cbdt_png() {
+        let data = font_with_cbdt(20);
+        let font = FontRef::new(&data).unwrap();
+        let strike = Strike {
+            source: StrikeSource::Cbdt,
+            index: 0,
+            ppem: 20,
+        };
+
+        let glyph = select_bitmap(&font, GlyphId::new(1), 24.0)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(vec![strike], strikes(&font, GlyphId::new(1)).unwrap());
+        assert_eq!(strike, glyph.strike);
+        assert_eq!(ImageFormat::Png, glyph.format);
+        assert_eq!(png(20, 20), glyph.data);
+        assert_eq!((Some(20), Some(20)), (glyph.width, glyph.height));
+        assert_eq!((1, -2), (glyph.left, glyph.bottom));
+        // Empty in the strike, or outside it
+        for gid in [2, 3] {
+            assert!(strikes(&font, GlyphId::new(gid)).unwrap().is_empty());
+        }
+    }
+}
@@ -0,0 +1,490 @@
+//! Memoizes drawing so that repeatedly rendering the same icons is cheap
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use kurbo::BezPath;
+use skrifa::{instance::LocationRef, FontRef, GlyphId, Tag};
+
+use crate::{
+    document::{draw_glyph_id, draw_location, IconDocument},
+    error::DrawSvgError,
+    icon2svg::{write_svg, Accessibility, DrawOptions, Fill, Framing, RenderHints},
+    iconid::IconIdentifier,
+    pathstyle::PathStyle,
+};
+
+/// Identifies a font by where its data is and a digest of its contents
+///
+/// The digest covers the tag, checksum and length of every table, which change with any edit
+/// to the font even when head's checksum adjustment, often left at zero, does not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId {
+    pub address: usize,
+    pub len: usize,
+    pub digest: u64,
+}
+
+impl FontId {
+    pub fn new(font: &FontRef) -> FontId {
+        let data = font.table_directory.offset_data().as_bytes();
+        let mut hasher = DefaultHasher::new();
+        for record in font.table_directory.table_records() {
+            record.tag().hash(&mut hasher);
+            record.checksum().hash(&mut hasher);
+            record.length().hash(&mut hasher);
+        }
+        FontId {
+            address: data.as_ptr() as usize,
+            len: data.len(),
+            digest: hasher.finish(),
+        }
+    }
+}
+
+/// Identifies a drawn glyph, coordinates are normalized
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PathKey {
+    pub font: FontId,
+    pub gid: GlyphId,
+    pub coords: Vec<i16>,
+}
+
+/// Identifies a serialized svg, keyed by identifier so a hit also skips resolution
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SvgKey {
+    pub font: FontId,
+    pub identifier: IconIdentifier,
+    /// Where the icon is drawn, with user coordinates and optical size applied
+    pub coords: Vec<i16>,
+    pub style: PathStyle,
+    /// The bits of the f32 width_height
+    pub width_height: u32,
+    /// The bits of the transform coefficients
+    pub transform: [u64; 6],
+    pub framing: Framing,
+    pub canonical: bool,
+    pub hints: RenderHints,
+    pub features: Vec<Tag>,
+    pub color: bool,
+    /// The bits of the f32 stroke width
+    pub stroke_width: Option<u32>,
+    pub stroke_color: Option<[u8; 3]>,
+    pub auto_mirror: Option<bool>,
+    pub remove_overlaps: bool,
+    pub accessibility: Option<Accessibility>,
+    pub fill: Fill,
+}
+
+impl SvgKey {
+    fn new(font: FontId, coords: Vec<i16>, options: &DrawOptions<'_>) -> SvgKey {
+        // Destructured so that a new option can't be left out of the key
+        let DrawOptions {
+            identifier,
+            width_height,
+            location: _,
+            style,
+            transform,
+            // Fallbacks are never cached
+            fallback: _,
+            framing,
+            canonical,
+            hints,
+            optical_size: _,
+            features,
+            color,
+            stroke_width,
+            stroke_color,
+            auto_mirror,
+            user_coords: _,
+            remove_overlaps,
+            accessibility,
+            fill,
+            // Only saves looking names up, the output is the same
+            icon_index: _,
+        } = options;
+        SvgKey {
+            font,
+            identifier: identifier.clone(),
+            coords,
+            style: *style,
+            width_height: width_height.to_bits(),
+            transform: transform.as_coeffs().map(f64::to_bits),
+            framing: *framing,
+            canonical: *canonical,
+            hints: hints.clone(),
+            features: features.clone(),
+            color: *color,
+            stroke_width: stroke_width.map(f32::to_bits),
+            stroke_color: *stroke_color,
+            auto_mirror: *auto_mirror,
+            remove_overlaps: *remove_overlaps,
+            accessibility: accessibility.clone(),
+            fill: fill.clone(),
+        }
+    }
+}
+
+/// Storage for drawn paths and svgs, shared between threads
+pub trait RenderCache: Sync {
+    fn get_path(&self, key: &PathKey) -> Option<Arc<BezPath>>;
+    fn put_path(&self, key: PathKey, path: Arc<BezPath>);
+    fn get_svg(&self, key: &SvgKey) -> Option<Arc<str>>;
+    fn put_svg(&self, key: SvgKey, svg: Arc<str>);
+}
+
+/// Least recently used eviction, eviction is a linear scan so keep capacity modest
+struct Lru<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Lru {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        *last_used = self.tick;
+        Some(value.clone())
+    }
+
+    fn put(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (value, self.tick));
+    }
+}
+
+/// An in-memory [RenderCache] holding up to `capacity` paths and `capacity` svgs
+pub struct LruRenderCache {
+    paths: Mutex<Lru<PathKey, Arc<BezPath>>>,
+    svgs: Mutex<Lru<SvgKey, Arc<str>>>,
+}
+
+impl LruRenderCache {
+    pub fn new(capacity: usize) -> LruRenderCache {
+        LruRenderCache {
+            paths: Mutex::new(Lru::new(capacity)),
+            svgs: Mutex::new(Lru::new(capacity)),
+        }
+    }
+}
+
+impl RenderCache for LruRenderCache {
+    fn get_path(&self, key: &PathKey) -> Option<Arc<BezPath>> {
+        self.paths.lock().unwrap().get(key)
+    }
+
+    fn put_path(&self, key: PathKey, path: Arc<BezPath>) {
+        self.paths.lock().unwrap().put(key, path)
+    }
+
+    fn get_svg(&self, key: &SvgKey) -> Option<Arc<str>> {
+        self.svgs.lock().unwrap().get(key)
+    }
+
+    fn put_svg(&self, key: SvgKey, svg: Arc<str>) {
+        self.svgs.lock().unwrap().put(key, svg)
+    }
+}
+
+/// As [crate::icon2svg::draw_icon] but checks `cache` first and populates it on a miss.
+///
+/// A cached svg skips resolution and drawing entirely. Otherwise the icon is resolved and a
+/// cached path, e.g. from a draw at another size, skips drawing.
+pub fn draw_icon_cached(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+    cache: &impl RenderCache,
+) -> Result<String, DrawSvgError> {
+    let font_id = FontId::new(font);
+    // Keyed on where we actually draw, so automatic optical sizing shares entries
+    let resolved = draw_location(font, options);
+    let location = resolved
+        .as_ref()
+        .map(LocationRef::from)
+        .unwrap_or(options.location);
+    let svg_key = SvgKey::new(font_id, coord_bits(location), options);
+    if let Some(svg) = cache.get_svg(&svg_key) {
+        return Ok(svg.to_string());
+    }
+
+    let paths = PathCache {
+        font: font_id,
+        cache,
+    };
+    let document = IconDocument::draw_cached(font, options, Some(&paths))?;
+    let svg = write_svg(&document, options);
+    // Fallbacks aren't cached so a later draw without one still fails
+    if document.fallback_for.is_none() {
+        cache.put_svg(svg_key, svg.as_str().into());
+    }
+    Ok(svg)
+}
+
+fn coord_bits(location: LocationRef) -> Vec<i16> {
+    location.coords().iter().map(|c| c.to_bits()).collect()
+}
+
+/// Drawn outlines of one font in a [RenderCache]
+pub(crate) struct PathCache<'a> {
+    font: FontId,
+    cache: &'a dyn RenderCache,
+}
+
+impl PathCache<'_> {
+    /// As [draw_glyph_id], from the cache if `gid` was already drawn at `location`
+    pub(crate) fn draw_glyph_id(
+        &self,
+        font: &FontRef,
+        identifier: &IconIdentifier,
+        gid: GlyphId,
+        location: LocationRef,
+    ) -> Result<BezPath, DrawSvgError> {
+        let key = PathKey {
+            font: self.font,
+            gid,
+            coords: coord_bits(location),
+        };
+        if let Some(path) = self.cache.get_path(&key) {
+            return Ok((*path).clone());
+        }
+        let path = draw_glyph_id(font, identifier, gid, location)?;
+        self.cache.put_path(key, Arc::new(path.clone()));
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::Affine;
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        cache::{draw_icon_cached, Lru, LruRenderCache},
+        icon2svg::{draw_icon, Accessibility, DrawOptions, Fallback, Framing},
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn cached_matches_uncached() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+        let cache = LruRenderCache::new(16);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let first = draw_icon_cached(&font, &options, &cache).unwrap();
+        let second = draw_icon_cached(&font, &options, &cache).unwrap();
+
+        assert_eq!(draw_icon(&font, &options).unwrap(), first);
+        assert_eq!(first, second);
+        assert_eq!(1, cache.paths.lock().unwrap().entries.len());
+        assert_eq!(1, cache.svgs.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn path_shared_between_sizes() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+
+        for size in [24.0, 48.0] {
+            let options =
+                DrawOptions::new(iconid::LAN.clone(), size, (&loc).into(), PathStyle::Compact);
+            draw_icon_cached(&font, &options, &cache).unwrap();
+        }
+
+        assert_eq!(1, cache.paths.lock().unwrap().entries.len());
+        assert_eq!(2, cache.svgs.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn transform_is_part_of_key() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+        let options =
+            DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+        let nudged = DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact)
+            .with_transform(Affine::translate((0.0, -20.0)));
+
+        let plain = draw_icon_cached(&font, &options, &cache).unwrap();
+        let moved = draw_icon_cached(&font, &nudged, &cache).unwrap();
+
+        assert_ne!(plain, moved);
+        assert_eq!(draw_icon(&font, &nudged).unwrap(), moved);
+        assert_eq!(1, cache.paths.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn accessibility_is_part_of_key() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Compact,
+        );
+        let accessible = options.clone().with_accessibility(Accessibility::default());
+
+        let plain = draw_icon_cached(&font, &options, &cache).unwrap();
+        let labelled = draw_icon_cached(&font, &accessible, &cache).unwrap();
+
+        assert_ne!(plain, labelled);
+        assert_eq!(draw_icon(&font, &accessible).unwrap(), labelled);
+        assert!(labelled.contains("<title>mail</title>"), "{labelled}");
+    }
+
+    #[test]
+    fn remove_overlaps_is_honored() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        // Outlined lan has overlapping contours
+        let loc = font.axes().location(&[("FILL", 0.0)]);
+        let cache = LruRenderCache::new(16);
+        let options =
+            DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+        let merged = options.clone().with_remove_overlaps(true);
+
+        let plain = draw_icon_cached(&font, &options, &cache).unwrap();
+        let removed = draw_icon_cached(&font, &merged, &cache).unwrap();
+
+        assert_ne!(plain, removed);
+        assert_eq!(draw_icon(&font, &merged).unwrap(), removed);
+        assert_eq!(1, cache.paths.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn framing_is_honored() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+        let options =
+            DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact)
+                .with_framing(Framing::Metrics);
+
+        assert_eq!(
+            draw_icon(&font, &options).unwrap(),
+            draw_icon_cached(&font, &options, &cache).unwrap()
+        );
+    }
+
+    #[test]
+    fn optical_size_follows_size() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+
+        for size in [20.0, 48.0] {
+            let options =
+                DrawOptions::new(iconid::LAN.clone(), size, (&loc).into(), PathStyle::Compact)
+                    .with_automatic_optical_size(true);
+            assert_eq!(
+                draw_icon(&font, &options).unwrap(),
+                draw_icon_cached(&font, &options, &cache).unwrap()
+            );
+        }
+
+        assert_eq!(2, cache.paths.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn fallbacks_are_not_cached() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let cache = LruRenderCache::new(16);
+        let missing = IconIdentifier::Name("no_such_icon".into());
+        let options = DrawOptions::new(missing.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+        let with_fallback = DrawOptions::new(missing, 24.0, (&loc).into(), PathStyle::Compact)
+            .with_fallback(Fallback::Notdef);
+
+        assert_eq!(
+            draw_icon(&font, &with_fallback).unwrap(),
+            draw_icon_cached(&font, &with_fallback, &cache).unwrap()
+        );
+        assert!(draw_icon_cached(&font, &options, &cache).is_err());
+        assert_eq!(0, cache.svgs.lock().unwrap().entries.len());
+    }
+
+    #[cfg(feature = "write-fonts")]
+    #[test]
+    fn rebuilt_fonts_are_told_apart() {
+        use skrifa::raw::TableProvider;
+
+        use crate::instance::static_instance;
+
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let outlined = static_instance(&font, [("FILL", 0.0)]).unwrap();
+        let filled = static_instance(&font, [("FILL", 1.0)]).unwrap();
+        let outlined = FontRef::new(&outlined).unwrap();
+        let filled = FontRef::new(&filled).unwrap();
+        assert_eq!(
+            outlined.head().unwrap().checksum_adjustment(),
+            filled.head().unwrap().checksum_adjustment()
+        );
+        let cache = LruRenderCache::new(16);
+
+        for font in [&outlined, &filled] {
+            let options = DrawOptions::new(
+                iconid::LAN.clone(),
+                24.0,
+                Default::default(),
+                PathStyle::Compact,
+            );
+            assert_eq!(
+                draw_icon(font, &options).unwrap(),
+                draw_icon_cached(font, &options, &cache).unwrap()
+            );
+        }
+        assert_eq!(2, cache.paths.lock().unwrap().entries.len());
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(2);
+        lru.put("a", 1);
+        lru.put("b", 2);
+        assert_eq!(Some(1), lru.get(&"a"));
+        lru.put("c", 3);
+
+        assert_eq!(Some(1), lru.get(&"a"));
+        assert_eq!(None, lru.get(&"b"));
+        assert_eq!(Some(3), lru.get(&"c"));
+    }
+}
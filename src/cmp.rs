@@ -7,17 +7,19 @@ use crate::{
     pens::SvgPathPen,
 };
 use core::cmp::PartialEq;
-use kurbo::BezPath;
+use kurbo::{BezPath, PathEl, Point, Shape};
 use rayon::prelude::*;
 use skrifa::{
-    instance::{Location, Size},
+    instance::{Location, LocationRef, Size},
+    metrics::GlyphMetrics,
     outline::DrawSettings,
     raw::{tables::gvar::Gvar, FontRef, ReadError, TableProvider},
     GlyphId, MetadataProvider, OutlineGlyph, OutlineGlyphCollection,
 };
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CompareResult {
     /// Names of icons present in new but not old font.
     pub added: Vec<String>,
@@ -25,29 +27,519 @@ pub struct CompareResult {
     pub modified: Vec<String>,
     /// Names of icons present in old but not new font.
     pub removed: Vec<String>,
+    /// Axes added, removed or whose range changed, in old then new fvar order.
+    pub axes: Vec<AxisChange>,
+    /// The old and new units per em, if they differ.
+    pub upem: Option<(u16, u16)>,
+    /// Icons present in both fonts whose codepoints changed, sorted by name.
+    pub codepoints: Vec<CodepointChange>,
+    /// Icons present in both fonts whose advance at the default location changed, sorted by name.
+    pub advance_widths: Vec<AdvanceChange>,
+}
+
+/// An fvar axis range, in user coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisRange {
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisChange {
+    pub tag: String,
+    /// None if the axis was added
+    pub old: Option<AxisRange>,
+    /// None if the axis was removed
+    pub new: Option<AxisRange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodepointChange {
+    pub name: String,
+    /// Sorted
+    pub old: Vec<u32>,
+    /// Sorted
+    pub new: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdvanceChange {
+    pub name: String,
+    pub old: f32,
+    pub new: f32,
+}
+
+/// How far outlines may drift and still compare equal, e.g. to ignore rounding noise from a
+/// build tool upgrade. The default is exact equality.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tolerance {
+    /// The furthest any point, or gvar delta on either axis, may move, in font units
+    pub max_point_deviation: f64,
+    /// The most the filled area may change, as a fraction of the old area
+    pub max_area_difference: f64,
 }
 
 /// Compares 2 icon fonts.
 pub fn compare_fonts(old: &FontRef, new: &FontRef) -> Result<CompareResult, IconResolutionError> {
+    compare_fonts_with_tolerance(old, new, Tolerance::default())
+}
+
+/// As [compare_fonts], icons whose outlines are all within `tolerance` aren't modified.
+///
+/// Outlines still need the same structure, a point added or a line turned into a curve is
+/// always a modification.
+pub fn compare_fonts_with_tolerance(
+    old: &FontRef,
+    new: &FontRef,
+    tolerance: Tolerance,
+) -> Result<CompareResult, IconResolutionError> {
     let old_icons = old.icons()?;
     let new_icons = new.icons()?;
+    let metadata = diff_metadata(old, new, &old_icons, &new_icons)?;
     let old_icons: HashMap<String, GlyphId> = map_by_names(old_icons);
     let new_icons: HashMap<String, GlyphId> = map_by_names(new_icons);
     let added = in_first_but_not_second(&new_icons, &old_icons);
     let removed = in_first_but_not_second(&old_icons, &new_icons);
-    let modified = diff_glyphs(old_icons, new_icons, old, new)?;
+    let modified = diff_glyphs(old_icons, new_icons, old, new, tolerance)?;
     Ok(CompareResult {
         added,
         modified,
         removed,
+        ..metadata
+    })
+}
+
+/// Fills in everything but the added, modified and removed icons
+fn diff_metadata(
+    old: &FontRef,
+    new: &FontRef,
+    old_icons: &[Icon],
+    new_icons: &[Icon],
+) -> Result<CompareResult, IconResolutionError> {
+    let axis_ranges = |font: &FontRef| -> Vec<(String, AxisRange)> {
+        font.axes()
+            .iter()
+            .map(|axis| {
+                let range = AxisRange {
+                    min: axis.min_value(),
+                    default: axis.default_value(),
+                    max: axis.max_value(),
+                };
+                (axis.tag().to_string(), range)
+            })
+            .collect()
+    };
+    let old_axes = axis_ranges(old);
+    let new_axes = axis_ranges(new);
+    let find = |axes: &[(String, AxisRange)], tag: &str| {
+        axes.iter()
+            .find(|(other, _)| other == tag)
+            .map(|(_, range)| *range)
+    };
+    let mut axes: Vec<AxisChange> = old_axes
+        .iter()
+        .map(|(tag, range)| AxisChange {
+            tag: tag.clone(),
+            old: Some(*range),
+            new: find(&new_axes, tag),
+        })
+        .filter(|change| change.old != change.new)
+        .collect();
+    axes.extend(
+        new_axes
+            .iter()
+            .filter(|(tag, _)| find(&old_axes, tag).is_none())
+            .map(|(tag, range)| AxisChange {
+                tag: tag.clone(),
+                old: None,
+                new: Some(*range),
+            }),
+    );
+
+    let (old_upem, new_upem) = (old.head()?.units_per_em(), new.head()?.units_per_em());
+    let upem = (old_upem != new_upem).then_some((old_upem, new_upem));
+
+    let by_name = |icons: &[Icon]| -> HashMap<String, (GlyphId, Vec<u32>)> {
+        let mut by_name: HashMap<String, (GlyphId, Vec<u32>)> = HashMap::new();
+        for icon in icons {
+            for name in icon.names.iter() {
+                let (_, codepoints) = by_name
+                    .entry(name.clone())
+                    .or_insert_with(|| (icon.gid, Vec::new()));
+                codepoints.extend(icon.codepoints.iter());
+                codepoints.sort();
+                codepoints.dedup();
+            }
+        }
+        by_name
+    };
+    let old_by_name = by_name(old_icons);
+    let new_by_name = by_name(new_icons);
+    let old_metrics = old.glyph_metrics(Size::unscaled(), LocationRef::default());
+    let new_metrics = new.glyph_metrics(Size::unscaled(), LocationRef::default());
+    let mut codepoints = Vec::new();
+    let mut advance_widths = Vec::new();
+    for (name, (old_gid, old_codepoints)) in old_by_name.iter() {
+        let Some((new_gid, new_codepoints)) = new_by_name.get(name) else {
+            continue;
+        };
+        if old_codepoints != new_codepoints {
+            codepoints.push(CodepointChange {
+                name: name.clone(),
+                old: old_codepoints.clone(),
+                new: new_codepoints.clone(),
+            });
+        }
+        let old_advance = old_metrics.advance_width(*old_gid).unwrap_or_default();
+        let new_advance = new_metrics.advance_width(*new_gid).unwrap_or_default();
+        if old_advance != new_advance {
+            advance_widths.push(AdvanceChange {
+                name: name.clone(),
+                old: old_advance,
+                new: new_advance,
+            });
+        }
+    }
+    codepoints.sort_by(|a, b| a.name.cmp(&b.name));
+    advance_widths.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(CompareResult {
+        axes,
+        upem,
+        codepoints,
+        advance_widths,
+        ..Default::default()
+    })
+}
+
+/// What differs between an old glyph and the new one drawn in its place
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum GlyphChange {
+    /// The default outline
+    Outline,
+    /// The gvar deltas, so the outline differs somewhere away from the default
+    Variations,
+    /// The advance or left side bearing at the default location
+    Metrics,
+}
+
+/// One changed glyph of a modified icon, with the outlines at the default location
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphDiff {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::glyph_id"))]
+    pub old_gid: GlyphId,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::glyph_id"))]
+    pub new_gid: GlyphId,
+    pub changes: Vec<GlyphChange>,
+    /// Svg path, Y-down
+    pub old_path: String,
+    /// Svg path, Y-down
+    pub new_path: String,
+}
+
+/// Why an icon present in both fonts is modified
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IconDiff {
+    pub name: String,
+    /// The icon reaches a different number of glyphs through GSUB, e.g. it gained a filled
+    /// variant. Only the icon glyph itself is then compared, the closures can't be paired up.
+    pub closure_changed: bool,
+    /// Glyphs of the closure that differ, paired in glyph id order
+    pub glyphs: Vec<GlyphDiff>,
+}
+
+/// As [compare_fonts], also detailing what changed in each modified icon.
+///
+/// Unlike [compare_fonts], a change to the metrics alone also marks an icon modified. Results
+/// are sorted by name.
+pub fn compare_fonts_detailed(
+    old: &FontRef,
+    new: &FontRef,
+) -> Result<(CompareResult, Vec<IconDiff>), IconResolutionError> {
+    let old_icons = old.icons()?;
+    let new_icons = new.icons()?;
+    let metadata = diff_metadata(old, new, &old_icons, &new_icons)?;
+    let old_icons = map_by_names(old_icons);
+    let new_icons = map_by_names(new_icons);
+    let added = in_first_but_not_second(&new_icons, &old_icons);
+    let removed = in_first_but_not_second(&old_icons, &new_icons);
+
+    let old_tables = Tables::new(old)?;
+    let new_tables = Tables::new(new)?;
+    let common: Vec<(String, GlyphId, GlyphId)> = old_icons
+        .into_iter()
+        .filter_map(|(k, v)| new_icons.get(&k).map(|r_gid| (k, v, *r_gid)))
+        .collect();
+    let mut diffs = common
+        .par_iter()
+        .map(|(name, old_gid, new_gid)| {
+            let old_closure = sorted_closure(old, *old_gid)?;
+            let new_closure = sorted_closure(new, *new_gid)?;
+            let closure_changed = old_closure.len() != new_closure.len();
+            let pairs = if closure_changed {
+                vec![(*old_gid, *new_gid)]
+            } else {
+                old_closure.into_iter().zip(new_closure).collect()
+            };
+            let mut glyphs = Vec::new();
+            for (old_gid, new_gid) in pairs {
+                let changes = changes(&old_tables, &new_tables, old_gid, new_gid)?;
+                if changes.is_empty() {
+                    continue;
+                }
+                let path = |tables: &Tables, gid| {
+                    tables
+                        .outlines
+                        .get(gid)
+                        .map(|glyph| draw_outline(glyph).to_svg())
+                        .unwrap_or_default()
+                };
+                glyphs.push(GlyphDiff {
+                    old_gid,
+                    new_gid,
+                    changes,
+                    old_path: path(&old_tables, old_gid),
+                    new_path: path(&new_tables, new_gid),
+                });
+            }
+            Ok::<_, IconResolutionError>((closure_changed || !glyphs.is_empty()).then(|| {
+                IconDiff {
+                    name: name.clone(),
+                    closure_changed,
+                    glyphs,
+                }
+            }))
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    diffs.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok((
+        CompareResult {
+            added,
+            modified: diffs.iter().map(|diff| diff.name.clone()).collect(),
+            removed,
+            ..metadata
+        },
+        diffs,
+    ))
+}
+
+/// Where [compare_fonts_at] draws outlines
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompareLocations {
+    /// Each location is a list of axis tags and user coordinates, e.g. `[("wght", 700.0)]`,
+    /// axes not listed are at their default
+    Explicit(Vec<Vec<(String, f32)>>),
+    /// Every named instance of the old font
+    NamedInstances,
+    /// Every combination of each axis of the old font at its min and max, an error if the
+    /// font has more than [MAX_CORNER_AXES] axes
+    Corners,
+}
+
+/// The most axes [CompareLocations::Corners] combines, each axis doubles the locations drawn
+pub const MAX_CORNER_AXES: usize = 16;
+
+/// The icons that draw differently at one location
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocationDiff {
+    /// Axis tags and user coordinates
+    pub location: Vec<(String, f32)>,
+    /// Names of icons present in both fonts that draw differently here, sorted
+    pub modified: Vec<String>,
+}
+
+/// Compares the outlines of icons present in both fonts at each of `locations`, in order.
+///
+/// Two variable fonts can match at the default location and still differ at, say, wght 700.
+/// Icons in only one font aren't reported, use [compare_fonts] for those. Locations are applied
+/// to each font by axis tag, so the fonts needn't list their axes in the same order.
+pub fn compare_fonts_at(
+    old: &FontRef,
+    new: &FontRef,
+    locations: &CompareLocations,
+) -> Result<Vec<LocationDiff>, IconResolutionError> {
+    let locations = user_locations(old, locations)?;
+    let old_icons = map_by_names(old.icons()?);
+    let new_icons = map_by_names(new.icons()?);
+    // Closures are the same everywhere, pair them up once
+    let common = old_icons
+        .into_iter()
+        .filter_map(|(name, old_gid)| {
+            new_icons
+                .get(&name)
+                .map(|new_gid| (name, old_gid, *new_gid))
+        })
+        .map(|(name, old_gid, new_gid)| {
+            let old_closure = sorted_closure(old, old_gid)?;
+            let new_closure = sorted_closure(new, new_gid)?;
+            let pairs = (old_closure.len() == new_closure.len())
+                .then(|| old_closure.into_iter().zip(new_closure).collect::<Vec<_>>());
+            Ok((name, pairs))
+        })
+        .collect::<Result<Vec<_>, IconResolutionError>>()?;
+
+    let old_outlines = old.outline_glyphs();
+    let new_outlines = new.outline_glyphs();
+    Ok(locations
+        .into_iter()
+        .map(|location| {
+            let old_location = old
+                .axes()
+                .location(location.iter().map(|(t, v)| (t.as_str(), *v)));
+            let new_location = new
+                .axes()
+                .location(location.iter().map(|(t, v)| (t.as_str(), *v)));
+            let draw = |outlines: &OutlineGlyphCollection, gid, location: &Location| {
+                outlines
+                    .get(gid)
+                    .map(|glyph| draw_outline_at(glyph, location.into()))
+            };
+            let mut modified: Vec<String> = common
+                .par_iter()
+                .filter(|(_, pairs)| {
+                    // A changed closure means the icon is modified everywhere
+                    pairs.as_ref().is_none_or(|pairs| {
+                        pairs.iter().any(|(old_gid, new_gid)| {
+                            draw(&old_outlines, *old_gid, &old_location)
+                                != draw(&new_outlines, *new_gid, &new_location)
+                        })
+                    })
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+            modified.sort();
+            LocationDiff { location, modified }
+        })
+        .collect())
+}
+
+fn user_locations(
+    font: &FontRef,
+    locations: &CompareLocations,
+) -> Result<Vec<Vec<(String, f32)>>, IconResolutionError> {
+    let axes = font.axes();
+    let tags: Vec<String> = axes.iter().map(|axis| axis.tag().to_string()).collect();
+    if *locations == CompareLocations::Corners && axes.len() > MAX_CORNER_AXES {
+        return Err(IconResolutionError::Invalid(format!(
+            "Corners of {} axes are too many to compare, at most {MAX_CORNER_AXES} are supported",
+            axes.len()
+        )));
+    }
+    Ok(match locations {
+        CompareLocations::Explicit(locations) => locations.clone(),
+        CompareLocations::NamedInstances => font
+            .named_instances()
+            .iter()
+            .map(|instance| tags.iter().cloned().zip(instance.user_coords()).collect())
+            .collect(),
+        CompareLocations::Corners => (0..1usize << axes.len())
+            .map(|corner| {
+                axes.iter()
+                    .enumerate()
+                    .map(|(i, axis)| {
+                        let value = if corner & (1 << i) == 0 {
+                            axis.min_value()
+                        } else {
+                            axis.max_value()
+                        };
+                        (tags[i].clone(), value)
+                    })
+                    .collect()
+            })
+            .collect(),
     })
 }
 
+/// An icon name and codepoint that were paired in the old font but aren't in the new one
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodepointViolation {
+    pub name: String,
+    pub codepoint: u32,
+    /// The codepoints of `name` in the new font, empty if the name was removed
+    pub name_now_has: Vec<u32>,
+    /// The names of `codepoint` in the new font, empty if the codepoint was removed
+    pub codepoint_now_has: Vec<String>,
+}
+
+/// Checks that every icon name keeps its codepoints and every codepoint keeps its names.
+///
+/// New names and codepoints are fine, anything that breaks a pairing from `old` is reported,
+/// sorted by codepoint then name. Geometry is not considered, see [compare_fonts] for that.
+pub fn check_codepoint_stability(
+    old: &FontRef,
+    new: &FontRef,
+) -> Result<Vec<CodepointViolation>, IconResolutionError> {
+    let new_icons = new.icons()?;
+    let mut new_by_name: HashMap<&str, Vec<u32>> = HashMap::new();
+    let mut new_by_codepoint: HashMap<u32, Vec<String>> = HashMap::new();
+    for icon in new_icons.iter() {
+        for name in icon.names.iter() {
+            new_by_name
+                .entry(name.as_str())
+                .or_default()
+                .extend(icon.codepoints.iter());
+            for codepoint in icon.codepoints.iter() {
+                new_by_codepoint
+                    .entry(*codepoint)
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+    }
+
+    for codepoints in new_by_name.values_mut() {
+        codepoints.sort();
+        codepoints.dedup();
+    }
+
+    let mut violations = Vec::new();
+    for icon in old.icons()? {
+        for name in icon.names.iter() {
+            for codepoint in icon.codepoints.iter() {
+                let name_now_has = new_by_name.get(name.as_str()).cloned().unwrap_or_default();
+                if name_now_has.contains(codepoint) {
+                    continue;
+                }
+                let mut codepoint_now_has =
+                    new_by_codepoint.get(codepoint).cloned().unwrap_or_default();
+                codepoint_now_has.sort();
+                violations.push(CodepointViolation {
+                    name: name.clone(),
+                    codepoint: *codepoint,
+                    name_now_has,
+                    codepoint_now_has,
+                });
+            }
+        }
+    }
+    violations.sort_by(|a, b| (a.codepoint, &a.name).cmp(&(b.codepoint, &b.name)));
+    Ok(violations)
+}
+
 fn diff_glyphs(
     old_icons: HashMap<String, GlyphId>,
     new_icons: HashMap<String, GlyphId>,
     old: &FontRef,
     new: &FontRef,
+    tolerance: Tolerance,
 ) -> Result<Vec<String>, IconResolutionError> {
     let old_outlines = Tables::new(old)?;
     let new_outlines = Tables::new(new)?;
@@ -60,24 +552,14 @@ fn diff_glyphs(
         .par_iter()
         // Returns the names of modified icons, or None.
         .map(|(name, old_gid, new_gid)| {
-            let mut old_closure: Vec<_> = old
-                .gsub()?
-                .closure_glyphs([*old_gid].into())?
-                .into_iter()
-                .collect();
-            let mut new_closure: Vec<_> = new
-                .gsub()?
-                .closure_glyphs([*new_gid].into())?
-                .into_iter()
-                .collect();
+            let old_closure = sorted_closure(old, *old_gid)?;
+            let new_closure = sorted_closure(new, *new_gid)?;
             if old_closure.len() != new_closure.len() {
                 // If closure changed assume the icon is modified.
                 return Ok::<Option<String>, IconResolutionError>(Some(name.to_string()));
             }
-            old_closure.sort();
-            new_closure.sort();
             for (old_gid, new_gid) in old_closure.iter().zip(new_closure.iter()) {
-                if !eq(&old_outlines, &new_outlines, *old_gid, *new_gid)? {
+                if !eq(&old_outlines, &new_outlines, *old_gid, *new_gid, tolerance)? {
                     // Icon draws differently.
                     return Ok(Some(name.to_string()));
                 }
@@ -92,9 +574,20 @@ fn diff_glyphs(
         .collect())
 }
 
+fn sorted_closure(font: &FontRef, gid: GlyphId) -> Result<Vec<GlyphId>, IconResolutionError> {
+    let mut closure: Vec<_> = font
+        .gsub()?
+        .closure_glyphs([gid].into())?
+        .into_iter()
+        .collect();
+    closure.sort();
+    Ok(closure)
+}
+
 struct Tables<'a> {
     gvar: Option<Gvar<'a>>,
     outlines: OutlineGlyphCollection<'a>,
+    metrics: GlyphMetrics<'a>,
 }
 
 impl<'a> Tables<'a> {
@@ -102,15 +595,88 @@ impl<'a> Tables<'a> {
         Ok(Tables {
             gvar: font.gvar().ok(),
             outlines: font.outline_glyphs(),
+            metrics: font.glyph_metrics(Size::unscaled(), LocationRef::default()),
         })
     }
 }
 
+fn changes(
+    old: &Tables,
+    new: &Tables,
+    old_gid: GlyphId,
+    new_gid: GlyphId,
+) -> Result<Vec<GlyphChange>, IconResolutionError> {
+    let mut changes = Vec::new();
+    let exact = Tolerance::default();
+    if !outlines_eq(old, new, old_gid, new_gid, exact) {
+        changes.push(GlyphChange::Outline);
+    }
+    if !variations_eq(old, new, old_gid, new_gid, exact)? {
+        changes.push(GlyphChange::Variations);
+    }
+    if old.metrics.advance_width(old_gid) != new.metrics.advance_width(new_gid)
+        || old.metrics.left_side_bearing(old_gid) != new.metrics.left_side_bearing(new_gid)
+    {
+        changes.push(GlyphChange::Metrics);
+    }
+    Ok(changes)
+}
+
 fn eq(
     old: &Tables,
     new: &Tables,
     old_gid: GlyphId,
     new_gid: GlyphId,
+    tolerance: Tolerance,
+) -> Result<bool, IconResolutionError> {
+    Ok(outlines_eq(old, new, old_gid, new_gid, tolerance)
+        && variations_eq(old, new, old_gid, new_gid, tolerance)?)
+}
+
+fn outlines_eq(
+    old: &Tables,
+    new: &Tables,
+    old_gid: GlyphId,
+    new_gid: GlyphId,
+    tolerance: Tolerance,
+) -> bool {
+    let l = old.outlines.get(old_gid).map(|f| draw_outline(f));
+    let r = new.outlines.get(new_gid).map(|f| draw_outline(f));
+    match (l, r) {
+        (Some(l), Some(r)) => paths_within(&l, &r, tolerance),
+        (l, r) => l.is_none() && r.is_none(),
+    }
+}
+
+/// Whether the paths have the same elements, each point within tolerance, and close enough
+/// areas
+fn paths_within(old: &BezPath, new: &BezPath, tolerance: Tolerance) -> bool {
+    let (old_els, new_els) = (old.elements(), new.elements());
+    if old_els.len() != new_els.len() {
+        return false;
+    }
+    let near = |p: &Point, q: &Point| p.distance(*q) <= tolerance.max_point_deviation;
+    let points_within = old_els.iter().zip(new_els).all(|pair| match pair {
+        (PathEl::MoveTo(p0), PathEl::MoveTo(q0)) | (PathEl::LineTo(p0), PathEl::LineTo(q0)) => {
+            near(p0, q0)
+        }
+        (PathEl::QuadTo(p0, p1), PathEl::QuadTo(q0, q1)) => near(p0, q0) && near(p1, q1),
+        (PathEl::CurveTo(p0, p1, p2), PathEl::CurveTo(q0, q1, q2)) => {
+            near(p0, q0) && near(p1, q1) && near(p2, q2)
+        }
+        (PathEl::ClosePath, PathEl::ClosePath) => true,
+        _ => false,
+    });
+    points_within
+        && (old.area() - new.area()).abs() <= tolerance.max_area_difference * old.area().abs()
+}
+
+fn variations_eq(
+    old: &Tables,
+    new: &Tables,
+    old_gid: GlyphId,
+    new_gid: GlyphId,
+    tolerance: Tolerance,
 ) -> Result<bool, IconResolutionError> {
     if old.gvar.is_some() != new.gvar.is_some() {
         return Err(IconResolutionError::Invalid(String::from(
@@ -118,11 +684,6 @@ fn eq(
             same type of glyph variation data (either both with gvar or both without).",
         )));
     }
-    let l = old.outlines.get(old_gid).map(|f| draw_outline(f));
-    let r = new.outlines.get(new_gid).map(|f| draw_outline(f));
-    if l != r {
-        return Ok(false);
-    }
 
     if let (Some(gvar), Some(other_gvar)) = (&old.gvar, &new.gvar) {
         let (data, other_data) = (
@@ -135,10 +696,18 @@ fn eq(
             match (tuples.next(), other_tuples.next()) {
                 // we have an item from both tuple lists
                 (Some(tuple), Some(other_tuple)) => {
-                    // note: iterators have eq() and ne() methods that work when
-                    // the item impls PartialEq
-                    if tuple.peak() != other_tuple.peak() || tuple.deltas().ne(other_tuple.deltas())
-                    {
+                    let deltas: Vec<_> = tuple.deltas().collect();
+                    let other_deltas: Vec<_> = other_tuple.deltas().collect();
+                    let deltas_within = deltas.len() == other_deltas.len()
+                        && deltas.iter().zip(other_deltas.iter()).all(|(d, other)| {
+                            let within = |a: i16, b: i16| {
+                                (a as f64 - b as f64).abs() <= tolerance.max_point_deviation
+                            };
+                            d.position == other.position
+                                && within(d.x_delta, other.x_delta)
+                                && within(d.y_delta, other.y_delta)
+                        });
+                    if tuple.peak() != other_tuple.peak() || !deltas_within {
                         return Ok(false);
                     }
                 }
@@ -154,12 +723,13 @@ fn eq(
 }
 
 fn draw_outline(old: OutlineGlyph) -> BezPath {
-    let mut old_pen = SvgPathPen::new();
-    let _ = old.draw(
-        DrawSettings::unhinted(Size::unscaled(), &Location::default()),
-        &mut old_pen,
-    );
-    old_pen.into_inner()
+    draw_outline_at(old, LocationRef::default())
+}
+
+fn draw_outline_at(glyph: OutlineGlyph, location: LocationRef) -> BezPath {
+    let mut pen = SvgPathPen::new();
+    let _ = glyph.draw(DrawSettings::unhinted(Size::unscaled(), location), &mut pen);
+    pen.into_inner()
 }
 
 fn map_by_names(icons: Vec<Icon>) -> HashMap<String, GlyphId> {
@@ -182,10 +752,33 @@ fn in_first_but_not_second(
 
 #[cfg(test)]
 mod tests {
-    use skrifa::FontRef;
+    use kurbo::BezPath;
+    use skrifa::{
+        raw::{types::Fixed, TableProvider},
+        FontRef, GlyphId, MetadataProvider,
+    };
+    use write_fonts::{
+        from_obj::ToOwnedTable,
+        tables::{
+            cmap::Cmap,
+            fvar::Fvar,
+            head::Head,
+            hhea::Hhea,
+            hmtx::{Hmtx, LongMetric},
+        },
+        types::Tag,
+        FontBuilder,
+    };
 
     use crate::{
-        cmp::{compare_fonts, CompareResult},
+        cmp::{
+            check_codepoint_stability, compare_fonts, compare_fonts_at, compare_fonts_detailed,
+            compare_fonts_with_tolerance, paths_within, AdvanceChange, AxisChange, AxisRange,
+            CodepointChange, CodepointViolation, CompareLocations, CompareResult, GlyphChange,
+            Tolerance, MAX_CORNER_AXES,
+        },
+        error::IconResolutionError,
+        iconid::Icons,
         testdata,
     };
     use std::time::Instant;
@@ -203,6 +796,7 @@ mod tests {
                 "label".to_string(),
             ],
             removed: vec!["menu".to_string()],
+            ..Default::default()
         };
 
         let actual = compare_fonts(&font, &new_font).unwrap();
@@ -222,6 +816,7 @@ mod tests {
             added: vec![],
             modified: vec![],
             removed: vec![],
+            ..Default::default()
         };
 
         let actual = compare_fonts(&new_font, &font).unwrap();
@@ -233,10 +828,314 @@ mod tests {
         println!("Elapsed time: {:.2?} seconds", elapsed_time);
     }
 
+    #[test]
+    fn compare_fonts_detailed_explains_modified() {
+        let font = FontRef::new(testdata::FULL_VF_OLD).unwrap();
+        let new_font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+
+        let (result, diffs) = compare_fonts_detailed(&font, &new_font).unwrap();
+
+        assert_eq_diff(result, compare_fonts(&font, &new_font).unwrap());
+        assert_eq!(
+            vec!["all_match", "backspace", "label"],
+            diffs.iter().map(|d| d.name.as_str()).collect::<Vec<_>>()
+        );
+        for diff in diffs {
+            assert!(diff.closure_changed || !diff.glyphs.is_empty(), "{diff:?}");
+            for glyph in diff.glyphs {
+                assert!(!glyph.changes.is_empty(), "{glyph:?}");
+                assert_eq!(
+                    glyph.changes.contains(&GlyphChange::Outline),
+                    glyph.old_path != glyph.new_path,
+                    "{glyph:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compare_fonts_detailed_same_fonts_empty_diff() {
+        let font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+
+        let (result, diffs) = compare_fonts_detailed(&font, &font).unwrap();
+
+        assert!(result.modified.is_empty(), "{result:?}");
+        assert!(diffs.is_empty(), "{diffs:?}");
+    }
+
+    #[test]
+    fn compare_fonts_at_corners() {
+        let font = FontRef::new(testdata::FULL_VF_OLD).unwrap();
+        let new_font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+
+        let diffs = compare_fonts_at(&font, &new_font, &CompareLocations::Corners).unwrap();
+
+        assert_eq!(16, diffs.len());
+        assert_eq!(
+            vec![
+                ("FILL".to_string(), 1.0),
+                ("GRAD".to_string(), -50.0),
+                ("opsz".to_string(), 20.0),
+                ("wght".to_string(), 100.0)
+            ],
+            diffs[1].location
+        );
+        for diff in diffs {
+            assert_eq!(vec!["all_match", "backspace", "label"], diff.modified);
+        }
+    }
+
+    #[test]
+    fn compare_fonts_at_too_many_corners() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let mut fvar: Fvar = font.fvar().unwrap().to_owned_table();
+        let axis = fvar.axis_instance_arrays.axes[0].clone();
+        fvar.axis_instance_arrays.axes = (0..=MAX_CORNER_AXES)
+            .map(|i| {
+                let mut axis = axis.clone();
+                axis.axis_tag = Tag::new(format!("AX{i:02}").as_bytes().try_into().unwrap());
+                axis
+            })
+            .collect();
+        fvar.axis_instance_arrays.instances.clear();
+        let font_data = FontBuilder::new()
+            .add_table(&fvar)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build();
+        let font = FontRef::new(&font_data).unwrap();
+
+        let result = compare_fonts_at(&font, &font, &CompareLocations::Corners);
+
+        assert!(
+            matches!(result, Err(IconResolutionError::Invalid(..))),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn compare_fonts_at_same_fonts_empty_diff() {
+        let font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+        let bold = vec![("wght".to_string(), 700.0)];
+
+        let diffs = compare_fonts_at(
+            &font,
+            &font,
+            &CompareLocations::Explicit(vec![vec![], bold.clone()]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![Vec::<String>::new(), Vec::new()],
+            diffs.iter().map(|d| d.modified.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(bold, diffs[1].location);
+    }
+
+    /// Moves mail from U+E158 to U+E159, widens it by 10, sets upem to 1000 and caps wght at 600
+    fn rebuild_font_with_new_metadata(fontdata: &[u8]) -> Vec<u8> {
+        let font = FontRef::new(fontdata).unwrap();
+        let cmap = Cmap::from_mappings(font.charmap().mappings().map(|(cp, gid)| {
+            let cp = if cp == 0xe158 { 0xe159 } else { cp };
+            (char::from_u32(cp).unwrap(), gid)
+        }))
+        .unwrap();
+        let mut head: Head = font.head().unwrap().to_owned_table();
+        head.units_per_em = 1000;
+        let mut fvar: Fvar = font.fvar().unwrap().to_owned_table();
+        for axis in fvar.axis_instance_arrays.axes.iter_mut() {
+            if axis.axis_tag == Tag::new(b"wght") {
+                axis.max_value = Fixed::from_f64(600.0);
+            }
+        }
+        // Give every glyph its own advance so mail's can change alone
+        let hmtx = font.hmtx().unwrap();
+        let num_glyphs = font.maxp().unwrap().num_glyphs();
+        let mut h_metrics: Vec<_> = (0..num_glyphs)
+            .map(|gid| {
+                let gid = GlyphId::new(gid);
+                LongMetric::new(hmtx.advance(gid).unwrap(), hmtx.side_bearing(gid).unwrap())
+            })
+            .collect();
+        h_metrics[1].advance += 10;
+        let hmtx = Hmtx::new(h_metrics, vec![]);
+        let mut hhea: Hhea = font.hhea().unwrap().to_owned_table();
+        hhea.number_of_long_metrics = num_glyphs;
+        FontBuilder::new()
+            .add_table(&cmap)
+            .unwrap()
+            .add_table(&head)
+            .unwrap()
+            .add_table(&fvar)
+            .unwrap()
+            .add_table(&hmtx)
+            .unwrap()
+            .add_table(&hhea)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    #[test]
+    fn compare_fonts_metadata() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let new_data = rebuild_font_with_new_metadata(testdata::ICON_FONT);
+        let new_font = FontRef::new(&new_data).unwrap();
+
+        let actual = compare_fonts(&font, &new_font).unwrap();
+
+        assert_eq!(
+            vec![AxisChange {
+                tag: "wght".to_string(),
+                old: Some(AxisRange {
+                    min: 100.0,
+                    default: 400.0,
+                    max: 700.0
+                }),
+                new: Some(AxisRange {
+                    min: 100.0,
+                    default: 400.0,
+                    max: 600.0
+                }),
+            }],
+            actual.axes
+        );
+        assert_eq!(Some((960, 1000)), actual.upem);
+        assert_eq!(
+            vec![CodepointChange {
+                name: "mail".to_string(),
+                old: vec![0xe158],
+                new: vec![0xe159],
+            }],
+            actual.codepoints
+        );
+        assert_eq!(
+            vec![AdvanceChange {
+                name: "mail".to_string(),
+                old: 960.0,
+                new: 970.0,
+            }],
+            actual.advance_widths
+        );
+    }
+
+    fn triangle(apex_x: f64) -> BezPath {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((100.0, 0.0));
+        path.quad_to((apex_x, 50.0), (0.0, 100.0));
+        path.close_path();
+        path
+    }
+
+    #[test]
+    fn rounding_noise_within_tolerance() {
+        let tolerance = Tolerance {
+            max_point_deviation: 0.01,
+            max_area_difference: 0.001,
+        };
+
+        assert!(paths_within(&triangle(50.0), &triangle(50.001), tolerance));
+        assert!(!paths_within(
+            &triangle(50.0),
+            &triangle(50.001),
+            Tolerance::default()
+        ));
+        assert!(paths_within(
+            &triangle(50.0),
+            &triangle(50.0),
+            Tolerance::default()
+        ));
+    }
+
+    #[test]
+    fn deviation_beyond_tolerance() {
+        let tolerance = Tolerance {
+            max_point_deviation: 0.01,
+            max_area_difference: 1.0,
+        };
+        assert!(!paths_within(&triangle(50.0), &triangle(51.0), tolerance));
+    }
+
+    #[test]
+    fn area_beyond_tolerance() {
+        let tolerance = Tolerance {
+            max_point_deviation: 10.0,
+            max_area_difference: 0.001,
+        };
+        assert!(!paths_within(&triangle(50.0), &triangle(51.0), tolerance));
+    }
+
+    #[test]
+    fn structure_change_is_never_within_tolerance() {
+        let mut lines = BezPath::new();
+        lines.move_to((0.0, 0.0));
+        lines.line_to((100.0, 0.0));
+        lines.line_to((0.0, 100.0));
+        lines.close_path();
+        let tolerance = Tolerance {
+            max_point_deviation: f64::MAX,
+            max_area_difference: f64::MAX,
+        };
+
+        assert!(!paths_within(&triangle(50.0), &lines, tolerance));
+    }
+
+    #[test]
+    fn compare_fonts_default_tolerance_is_exact() {
+        let font = FontRef::new(testdata::FULL_VF_OLD).unwrap();
+        let new_font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+
+        assert_eq_diff(
+            compare_fonts_with_tolerance(&font, &new_font, Tolerance::default()).unwrap(),
+            compare_fonts(&font, &new_font).unwrap(),
+        );
+    }
+
+    #[test]
+    fn codepoint_stability_same_font() {
+        let font = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+        assert_eq!(
+            Vec::<CodepointViolation>::new(),
+            check_codepoint_stability(&font, &font).unwrap()
+        );
+    }
+
+    #[test]
+    fn codepoint_stability_removed_icon() {
+        let old = FontRef::new(testdata::FULL_VF_OLD).unwrap();
+        let new = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+        let menu = old
+            .icons()
+            .unwrap()
+            .into_iter()
+            .find(|icon| icon.names.contains(&"menu".to_string()))
+            .unwrap();
+
+        let violations = check_codepoint_stability(&old, &new).unwrap();
+
+        assert_eq!(
+            menu.codepoints
+                .iter()
+                .map(|cp| CodepointViolation {
+                    name: "menu".to_string(),
+                    codepoint: *cp,
+                    name_now_has: vec![],
+                    codepoint_now_has: vec![],
+                })
+                .collect::<Vec<_>>(),
+            violations
+        );
+    }
+
     fn assert_eq_diff(actual: CompareResult, expected: CompareResult) {
         assert_eq_vec(&actual.added, &expected.added);
         assert_eq_vec(&actual.modified, &expected.modified);
         assert_eq_vec(&actual.removed, &expected.removed);
+        assert_eq!(actual.axes, expected.axes);
+        assert_eq!(actual.upem, expected.upem);
+        assert_eq!(actual.codepoints, expected.codepoints);
+        assert_eq!(actual.advance_widths, expected.advance_widths);
     }
 
     fn assert_eq_vec(actual: &[String], expected: &[String]) {
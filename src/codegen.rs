@@ -0,0 +1,210 @@
+//! Generates source code exposing the icons in a font as named constants
+
+use std::collections::{BTreeMap, HashSet};
+
+use skrifa::FontRef;
+
+use crate::{error::IconResolutionError, iconid::Icons};
+
+// https://dart.dev/language/keywords, reserved words and built-in identifiers
+const DART_KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "assert",
+    "async",
+    "await",
+    "base",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "covariant",
+    "default",
+    "deferred",
+    "do",
+    "dynamic",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "extension",
+    "external",
+    "factory",
+    "false",
+    "final",
+    "finally",
+    "for",
+    "function",
+    "get",
+    "hide",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "interface",
+    "is",
+    "late",
+    "library",
+    "mixin",
+    "new",
+    "null",
+    "of",
+    "on",
+    "operator",
+    "part",
+    "required",
+    "rethrow",
+    "return",
+    "sealed",
+    "set",
+    "show",
+    "static",
+    "super",
+    "switch",
+    "sync",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typedef",
+    "var",
+    "void",
+    "when",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Every icon name with its lowest codepoint, sorted by name
+fn names_to_codepoints(font: &FontRef) -> Result<BTreeMap<String, u32>, IconResolutionError> {
+    let mut names = BTreeMap::new();
+    for icon in font.icons()? {
+        let Some(codepoint) = icon.codepoints.iter().min() else {
+            continue;
+        };
+        for name in icon.names {
+            names.insert(name, *codepoint);
+        }
+    }
+    Ok(names)
+}
+
+/// Generates a Dart class with a `static const IconData` per icon name for Flutter.
+///
+/// Names that aren't valid Dart identifiers are adjusted the way the Flutter icon packages do,
+/// a leading digit gets a `$` prefix (`$10k`) and keywords a `_` suffix (`class_`).
+pub fn dart_icon_data(
+    font: &FontRef,
+    class_name: &str,
+    font_family: &str,
+) -> Result<String, IconResolutionError> {
+    let family = font_family.replace('\\', "\\\\").replace('\'', "\\'");
+    let mut dart = String::new();
+    dart.push_str("// Generated by sleipnir, do not edit.\n\n");
+    dart.push_str("import 'package:flutter/widgets.dart';\n\n");
+    dart.push_str(&format!("class {class_name} {{\n"));
+    dart.push_str(&format!("  {class_name}._();\n"));
+
+    let mut seen = HashSet::new();
+    for (name, codepoint) in names_to_codepoints(font)? {
+        let identifier = dart_identifier(&name);
+        if seen.contains(&identifier) {
+            continue;
+        }
+        dart.push_str(&format!(
+            "\n  static const IconData {identifier} = IconData(0x{codepoint:04X}, fontFamily: '{family}');\n"
+        ));
+        seen.insert(identifier);
+    }
+    dart.push_str("}\n");
+    Ok(dart)
+}
+
+/// Generates a TypeScript module mapping icon names to the string to render with the font.
+///
+/// Exports `<const_name>`, an `as const` object, and `<const_name>Name`, the union of its
+/// keys, so icon names are checked at compile time.
+pub fn typescript_codepoints(
+    font: &FontRef,
+    const_name: &str,
+) -> Result<String, IconResolutionError> {
+    let mut ts = String::new();
+    ts.push_str("// Generated by sleipnir, do not edit.\n\n");
+    ts.push_str(&format!("export const {const_name} = {{\n"));
+    for (name, codepoint) in names_to_codepoints(font)? {
+        let name = name.replace('\\', "\\\\").replace('\'', "\\'");
+        ts.push_str(&format!("  '{name}': '\\u{{{codepoint:x}}}',\n"));
+    }
+    ts.push_str("} as const;\n\n");
+    ts.push_str(&format!(
+        "export type {const_name}Name = keyof typeof {const_name};\n"
+    ));
+    Ok(ts)
+}
+
+fn dart_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) || identifier.is_empty() {
+        identifier.insert(0, '$');
+    }
+    if DART_KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::{
+        codegen::{dart_icon_data, dart_identifier, typescript_codepoints},
+        testdata,
+    };
+
+    #[test]
+    fn dart_class() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+
+        let dart = dart_icon_data(&font, "MaterialSymbols", "Material Symbols").unwrap();
+
+        assert!(dart.contains("class MaterialSymbols {\n  MaterialSymbols._();\n"));
+        assert!(dart.contains(
+            "\n  static const IconData mic = IconData(0xE029, fontFamily: 'Material Symbols');\n"
+        ));
+        assert!(dart.ends_with("}\n"));
+    }
+
+    #[test]
+    fn typescript_module() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+
+        let ts = typescript_codepoints(&font, "MaterialSymbols").unwrap();
+
+        assert!(ts.contains("export const MaterialSymbols = {\n"));
+        assert!(ts.contains("\n  'mic': '\\u{e029}',\n"));
+        assert!(ts.ends_with(
+            "} as const;\n\nexport type MaterialSymbolsName = keyof typeof MaterialSymbols;\n"
+        ));
+    }
+
+    #[test]
+    fn dart_identifiers() {
+        assert_eq!("mail", dart_identifier("mail"));
+        assert_eq!("$10k", dart_identifier("10k"));
+        assert_eq!("class_", dart_identifier("class"));
+        assert_eq!("a_b", dart_identifier("a-b"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Composes several icons into one drawing, e.g. an icon with a badge in its corner
+
+use kurbo::Affine;
+use skrifa::FontRef;
+
+use crate::{
+    document::{units_per_em, Color, IconDocument, Paint},
+    error::DrawSvgError,
+    icon2svg::{write_svg, DrawOptions},
+    icon2xml::{declare_namespaces, is_directional, write_vector},
+    iconid::IconIdentifier,
+    xml::Namespace,
+};
+
+/// One icon of a composition
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComposeLayer {
+    identifier: IconIdentifier,
+    transform: Affine,
+    color: Option<[u8; 3]>,
+}
+
+impl ComposeLayer {
+    /// Drawn as is, in the colors it would be drawn in alone
+    pub fn new(identifier: IconIdentifier) -> ComposeLayer {
+        ComposeLayer {
+            identifier,
+            transform: Affine::IDENTITY,
+            color: None,
+        }
+    }
+
+    /// Places the icon, in font units with Y down as [DrawOptions::with_transform]. For
+    /// example a badge at half size in the top right corner of a 960 upem icon is
+    /// `Affine::translate((480.0, -960.0)) * Affine::scale(0.5) * Affine::translate((0.0, 960.0))`.
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Fills the whole icon in `rgb`, replacing any colors it has
+    pub fn with_color(mut self, rgb: [u8; 3]) -> Self {
+        self.color = Some(rgb);
+        self
+    }
+}
+
+/// Draws `layers` bottom to top into one svg.
+///
+/// Each layer is drawn with `options`, whose own identifier is ignored, then its transform
+/// applied before the transform in `options`. The view box is that of the first layer.
+pub fn compose_svg(
+    font: &FontRef,
+    layers: &[ComposeLayer],
+    options: &DrawOptions<'_>,
+) -> Result<String, DrawSvgError> {
+    Ok(write_svg(&compose(font, layers, options)?, options))
+}
+
+/// As [compose_svg] as an Android VectorDrawable, mirrored by default when the first layer is a
+/// directional icon as for [crate::icon2xml::draw_icon].
+pub fn compose_xml(
+    font: &FontRef,
+    layers: &[ComposeLayer],
+    options: &DrawOptions<'_>,
+) -> Result<String, DrawSvgError> {
+    let mut vector = write_vector(&compose(font, layers, options)?, options);
+    let auto_mirror = options.auto_mirror.unwrap_or_else(|| {
+        layers
+            .first()
+            .is_some_and(|layer| is_directional(font, &layer.identifier, options.icon_index))
+    });
+    if auto_mirror {
+        vector.set_ns_attribute(&Namespace::ANDROID, "autoMirrored", true);
+    }
+    declare_namespaces(&mut vector);
+    Ok(vector.to_string())
+}
+
+fn compose(
+    font: &FontRef,
+    layers: &[ComposeLayer],
+    options: &DrawOptions<'_>,
+) -> Result<IconDocument, DrawSvgError> {
+    let mut composed: Option<IconDocument> = None;
+    for layer in layers {
+        let mut layer_options = options.clone();
+        layer_options.identifier = layer.identifier.clone();
+        layer_options.transform = options.transform * layer.transform;
+        let mut document = IconDocument::draw(font, &layer_options)?;
+        if let Some(rgb) = layer.color {
+            for drawn in document.layers.iter_mut() {
+                drawn.paint = Paint::Solid(Color {
+                    rgb: Some(rgb),
+                    alpha: 1.0,
+                });
+            }
+        }
+        let Some(composed) = composed.as_mut() else {
+            composed = Some(document);
+            continue;
+        };
+        // Keep blend groups of different icons apart
+        let next_group = composed
+            .layers
+            .iter()
+            .flat_map(|drawn| drawn.groups.iter())
+            .map(|group| group.id + 1)
+            .max()
+            .unwrap_or_default();
+        for mut drawn in document.layers {
+            for group in drawn.groups.iter_mut() {
+                group.id += next_group;
+            }
+            composed.layers.push(drawn);
+        }
+    }
+    match composed {
+        Some(composed) => Ok(composed),
+        None => Ok(IconDocument::from_layers(
+            units_per_em(font)?,
+            options.width_height,
+            Vec::new(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::Affine;
+    use skrifa::{FontRef, GlyphId};
+
+    use crate::{
+        compose::{compose_svg, compose_xml, ComposeLayer},
+        error::DrawSvgError,
+        icon2svg::{draw_icon, DrawOptions},
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn options() -> DrawOptions<'static> {
+        DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+    }
+
+    /// The path elements of an svg
+    fn paths(svg: &str) -> Vec<&str> {
+        svg.split("<path ")
+            .skip(1)
+            .map(|path| &path[..path.find("/>").unwrap()])
+            .collect()
+    }
+
+    #[test]
+    fn badge_over_icon() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let corner = Affine::translate((480.0, -960.0))
+            * Affine::scale(0.5)
+            * Affine::translate((0.0, 960.0));
+        let mut lan = options();
+        lan.identifier = iconid::LAN.clone();
+        let mail = draw_icon(&font, &options()).unwrap();
+        let badge = draw_icon(&font, &lan.with_transform(corner)).unwrap();
+
+        let composed = compose_svg(
+            &font,
+            &[
+                ComposeLayer::new(iconid::MAIL.clone()),
+                ComposeLayer::new(iconid::LAN.clone())
+                    .with_transform(corner)
+                    .with_color([0xb3, 0x26, 0x1e]),
+            ],
+            &options(),
+        )
+        .unwrap();
+
+        let badge_path = paths(&badge)[0];
+        assert_eq!(
+            vec![
+                paths(&mail)[0].to_string(),
+                format!("{badge_path} fill=\"#b3261e\"")
+            ],
+            paths(&composed)
+        );
+        assert!(composed.starts_with(&mail[..mail.find('>').unwrap()]));
+    }
+
+    #[test]
+    fn compose_vector_drawable() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let xml = compose_xml(
+            &font,
+            &[
+                ComposeLayer::new(iconid::MAIL.clone()),
+                ComposeLayer::new(IconIdentifier::GlyphId(GlyphId::new(5))).with_color([0, 0, 0]),
+            ],
+            &options(),
+        )
+        .unwrap();
+
+        assert_eq!(2, xml.matches("<path ").count(), "{xml}");
+        assert!(
+            xml.contains("android:fillColor=\"@android:color/white\""),
+            "{xml}"
+        );
+        assert!(xml.contains("android:fillColor=\"#000000\""), "{xml}");
+    }
+
+    #[test]
+    fn missing_layer_fails() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        assert!(matches!(
+            compose_svg(
+                &font,
+                &[
+                    ComposeLayer::new(iconid::MAIL.clone()),
+                    ComposeLayer::new(IconIdentifier::Name("nope".into())),
+                ],
+                &options(),
+            ),
+            Err(DrawSvgError::ResolutionError(..))
+        ));
+    }
+}
@@ -0,0 +1,491 @@
+//! The drawing of an icon independent of output format.
+//!
+//! Backends draw an [IconDocument] once and serialize it, so anything that changes what an
+//! icon looks like lands in every format at once.
+
+use kurbo::{Affine, BezPath, PathEl, Point, Rect, Shape};
+use skrifa::{
+    color::Extend,
+    instance::{Location, LocationRef, Size},
+    outline::DrawSettings,
+    raw::{tables::glyf::ToPathStyle, TableProvider},
+    FontRef, GlyphId, MetadataProvider, Tag,
+};
+
+use crate::{
+    cache::PathCache,
+    error::DrawSvgError,
+    icon2svg::{DrawOptions, Fallback, Framing},
+    iconid::{IconIdentifier, IconIndex},
+    overlap::remove_overlaps,
+    pens::{LayerPainter, SvgPathPen},
+};
+
+/// A color, `rgb` is None for the foreground color
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Color {
+    pub(crate) rgb: Option<[u8; 3]>,
+    /// From 0 to 1
+    pub(crate) alpha: f32,
+}
+
+impl Color {
+    pub(crate) const FOREGROUND: Color = Color {
+        rgb: None,
+        alpha: 1.0,
+    };
+}
+
+/// How a layer is filled, gradient geometry is in the same space as the layer's path
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Paint {
+    Solid(Color),
+    LinearGradient {
+        p0: Point,
+        p1: Point,
+        stops: Vec<(f32, Color)>,
+        extend: Extend,
+    },
+    RadialGradient {
+        c0: Point,
+        r0: f64,
+        c1: Point,
+        r1: f64,
+        stops: Vec<(f32, Color)>,
+        extend: Extend,
+    },
+}
+
+/// Layers composited together onto what is below them, `id` tells apart adjacent groups
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Group {
+    pub(crate) id: usize,
+    /// The css mix-blend-mode
+    pub(crate) blend: &'static str,
+}
+
+/// A path in font units, Y-down, and the transform to apply to it
+pub(crate) struct Layer {
+    pub(crate) path: BezPath,
+    pub(crate) transform: Affine,
+    pub(crate) paint: Paint,
+    /// The groups the layer is in, outermost first
+    pub(crate) groups: Vec<Group>,
+}
+
+impl Layer {
+    /// A layer in the foreground color
+    pub(crate) fn new(path: BezPath) -> Layer {
+        Layer::with_paint(path, Paint::Solid(Color::FOREGROUND))
+    }
+
+    pub(crate) fn with_paint(path: BezPath, paint: Paint) -> Layer {
+        Layer {
+            path,
+            transform: Affine::IDENTITY,
+            paint,
+            groups: Vec::new(),
+        }
+    }
+
+    pub(crate) fn transformed_path(&self) -> BezPath {
+        let mut path = self.path.clone();
+        if self.transform != Affine::IDENTITY {
+            path.apply_affine(self.transform);
+        }
+        path
+    }
+}
+
+/// An icon ready to be serialized, layers are painted in order in the foreground color
+pub(crate) struct IconDocument {
+    pub(crate) upem: u16,
+    /// The size of an em in the output
+    pub(crate) width_height: f32,
+    /// The area to output in path coordinates
+    pub(crate) view_box: Rect,
+    pub(crate) layers: Vec<Layer>,
+    /// Whether [IconDocument::paths] canonicalizes, see [DrawOptions::with_canonical_output]
+    pub(crate) canonical: bool,
+    /// Set when a [Fallback] was drawn, the error that would otherwise have been returned
+    pub(crate) fallback_for: Option<DrawSvgError>,
+    /// The font's name for the icon, only looked up for [DrawOptions::with_accessibility]
+    pub(crate) name: Option<String>,
+}
+
+impl IconDocument {
+    pub(crate) fn draw(font: &FontRef, options: &DrawOptions<'_>) -> Result<Self, DrawSvgError> {
+        Self::draw_cached(font, options, None)
+    }
+
+    /// As [IconDocument::draw], taking outlines from `paths` when given
+    pub(crate) fn draw_cached(
+        font: &FontRef,
+        options: &DrawOptions<'_>,
+        paths: Option<&PathCache>,
+    ) -> Result<Self, DrawSvgError> {
+        let upem = units_per_em(font)?;
+        let resolved = draw_location(font, options);
+        let location = resolved
+            .as_ref()
+            .map(LocationRef::from)
+            .unwrap_or(options.location);
+        let drawn = options
+            .identifier
+            .resolve_with_features(font, &location, &options.features)
+            .map_err(|e| DrawSvgError::ResolutionError(options.identifier.clone(), e))
+            .and_then(|gid| {
+                draw_layers(font, options, gid, location, paths).map(|layers| (Some(gid), layers))
+            });
+        let ((gid, layers), fallback_for) = match drawn {
+            Ok(drawn) => (drawn, None),
+            Err(e @ (DrawSvgError::ResolutionError(..) | DrawSvgError::NoOutline(..))) => {
+                match &options.fallback {
+                    Fallback::Error => return Err(e),
+                    Fallback::Notdef => {
+                        let Ok(path) =
+                            draw_glyph_id(font, &options.identifier, GlyphId::NOTDEF, location)
+                        else {
+                            return Err(e);
+                        };
+                        ((Some(GlyphId::NOTDEF), vec![Layer::new(path)]), Some(e))
+                    }
+                    Fallback::Path(path) => ((None, vec![Layer::new(path.clone())]), Some(e)),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        let layers = if options.remove_overlaps {
+            layers
+                .into_iter()
+                .map(|layer| Layer {
+                    path: remove_overlaps(&layer.path),
+                    ..layer
+                })
+                .collect()
+        } else {
+            layers
+        };
+        let mut document =
+            Self::from_layers(upem, options.width_height, layers).with_transform(options.transform);
+        document.view_box = framed_view_box(font, options.framing, gid, location, upem);
+        document.canonical = options.canonical;
+        document.fallback_for = fallback_for;
+        if options.accessibility.is_some() {
+            document.name = icon_names(font, &options.identifier, options.icon_index)
+                .into_iter()
+                .next();
+        }
+        Ok(document)
+    }
+
+    /// A document framed on the em box
+    pub(crate) fn from_layers(upem: u16, width_height: f32, layers: Vec<Layer>) -> Self {
+        let upem_f = upem as f64;
+        IconDocument {
+            upem,
+            width_height,
+            view_box: Rect::new(0.0, -upem_f, upem_f, 0.0),
+            layers,
+            canonical: false,
+            fallback_for: None,
+            name: None,
+        }
+    }
+
+    /// Applies `transform` after any transform each layer already has
+    pub(crate) fn with_transform(mut self, transform: Affine) -> Self {
+        for layer in self.layers.iter_mut() {
+            layer.transform = transform * layer.transform;
+        }
+        self
+    }
+
+    /// The size of the output, `width_height` per em along each side of the view box
+    pub(crate) fn output_size(&self) -> (f32, f32) {
+        let upem = self.upem as f64;
+        let width_height = self.width_height as f64;
+        (
+            (width_height * self.view_box.width() / upem) as f32,
+            (width_height * self.view_box.height() / upem) as f32,
+        )
+    }
+
+    /// The path of each layer with its transform applied, canonicalized if requested
+    pub(crate) fn paths(&self) -> impl Iterator<Item = BezPath> + '_ {
+        self.layers.iter().map(|layer| {
+            let path = layer.transformed_path();
+            if self.canonical {
+                canonicalize(&path)
+            } else {
+                path
+            }
+        })
+    }
+}
+
+/// Rounds to hundredths of a unit, without negative zero, and sorts subpaths top to bottom
+/// then left to right, so equivalent outlines produce identical paths.
+///
+/// Fills are nonzero so subpath order doesn't change what is painted.
+fn canonicalize(path: &BezPath) -> BezPath {
+    let round = |p: Point| {
+        // Adding 0.0 turns -0.0 into 0.0
+        Point::new(
+            (p.x * 100.0).round() / 100.0 + 0.0,
+            (p.y * 100.0).round() / 100.0 + 0.0,
+        )
+    };
+    let mut subpaths: Vec<BezPath> = Vec::new();
+    for el in path.elements() {
+        let el = match *el {
+            PathEl::MoveTo(p) => {
+                subpaths.push(BezPath::new());
+                PathEl::MoveTo(round(p))
+            }
+            PathEl::LineTo(p) => PathEl::LineTo(round(p)),
+            PathEl::QuadTo(c, p) => PathEl::QuadTo(round(c), round(p)),
+            PathEl::CurveTo(c0, c1, p) => PathEl::CurveTo(round(c0), round(c1), round(p)),
+            PathEl::ClosePath => PathEl::ClosePath,
+        };
+        match subpaths.last_mut() {
+            Some(subpath) => subpath.push(el),
+            None => subpaths.push(BezPath::from_vec(vec![el])),
+        }
+    }
+    subpaths.sort_by(|a, b| {
+        let (a, b) = (a.bounding_box(), b.bounding_box());
+        a.y0.total_cmp(&b.y0)
+            .then(a.x0.total_cmp(&b.x0))
+            .then(a.y1.total_cmp(&b.y1))
+            .then(a.x1.total_cmp(&b.x1))
+    });
+    subpaths.into_iter().flatten().collect()
+}
+
+/// The location with [DrawOptions::with_user_coords] applied, or None if there are none.
+pub(crate) fn user_location(font: &FontRef, options: &DrawOptions<'_>) -> Option<Location> {
+    if options.user_coords.is_empty() {
+        return None;
+    }
+    let axes = font.axes();
+    let mut location = copy_location(options.location, axes.len());
+    for setting in options.user_coords.iter() {
+        let Some(axis) = axes.get_by_tag(setting.selector) else {
+            continue;
+        };
+        let index = axis.index();
+        location.coords_mut()[index] = axes.location([*setting]).coords()[index];
+    }
+    Some(location)
+}
+
+/// Where to draw: the location with user coordinates applied and then, if
+/// [DrawOptions::with_automatic_optical_size] applies, opsz set from the size. None to draw at
+/// the location as given.
+pub(crate) fn draw_location(font: &FontRef, options: &DrawOptions<'_>) -> Option<Location> {
+    let user = user_location(font, options);
+    if !options.optical_size {
+        return user;
+    }
+    let location = user
+        .as_ref()
+        .map(LocationRef::from)
+        .unwrap_or(options.location);
+    let axes = font.axes();
+    let opsz_tag = Tag::new(b"opsz");
+    let Some(opsz) = axes.get_by_tag(opsz_tag).map(|axis| axis.index()) else {
+        return user;
+    };
+    // User coordinates say which axes they set, a location only where it isn't the default
+    let pinned = options.user_coords.iter().any(|s| s.selector == opsz_tag)
+        || options
+            .location
+            .coords()
+            .get(opsz)
+            .is_some_and(|coord| coord.to_bits() != 0);
+    if pinned {
+        return user;
+    }
+    let sized = axes.location([("opsz", options.width_height)]);
+    let mut location = copy_location(location, axes.len());
+    location.coords_mut()[opsz] = sized.coords()[opsz];
+    Some(location)
+}
+
+/// `location` padded or truncated to `num_axes`
+fn copy_location(location: LocationRef, num_axes: usize) -> Location {
+    let coords = location.coords();
+    let mut copy = Location::new(num_axes);
+    let len = coords.len().min(num_axes);
+    copy.coords_mut()[..len].copy_from_slice(&coords[..len]);
+    copy
+}
+
+/// The view box for `framing`, the em box or the advance by ascent to descent.
+///
+/// Without a glyph, e.g. for a placeholder path, the advance is taken to be one em.
+pub(crate) fn framed_view_box(
+    font: &FontRef,
+    framing: Framing,
+    gid: Option<GlyphId>,
+    location: LocationRef,
+    upem: u16,
+) -> Rect {
+    let upem = upem as f64;
+    match framing {
+        Framing::EmBox => Rect::new(0.0, -upem, upem, 0.0),
+        Framing::Metrics => {
+            let metrics = font.metrics(Size::unscaled(), location);
+            let advance = gid
+                .and_then(|gid| {
+                    font.glyph_metrics(Size::unscaled(), location)
+                        .advance_width(gid)
+                })
+                .map(|advance| advance as f64)
+                .unwrap_or(upem);
+            Rect::new(
+                0.0,
+                -metrics.ascent as f64,
+                advance,
+                -metrics.descent as f64,
+            )
+        }
+    }
+}
+
+pub(crate) fn units_per_em(font: &FontRef) -> Result<u16, DrawSvgError> {
+    Ok(font
+        .head()
+        .map_err(|e| DrawSvgError::ReadError("head", e))?
+        .units_per_em())
+}
+
+/// The names the font gives the icon, looked up in `index` if given else in a fresh index
+pub(crate) fn icon_names(
+    font: &FontRef,
+    identifier: &IconIdentifier,
+    index: Option<&IconIndex>,
+) -> Vec<String> {
+    if let IconIdentifier::Name(name) = identifier {
+        return vec![name.to_string()];
+    }
+    let Ok(gid) = identifier.resolve_unsubstituted(font) else {
+        return Vec::new();
+    };
+    match index {
+        Some(index) => index.names_for_gid(gid).to_vec(),
+        None => IconIndex::new(font)
+            .map(|index| index.names_for_gid(gid).to_vec())
+            .unwrap_or_default(),
+    }
+}
+
+/// Describes the icon by how it was identified, for output metadata such as a title
+pub(crate) fn title(identifier: &IconIdentifier) -> String {
+    match identifier {
+        IconIdentifier::Name(name) => name.to_string(),
+        IconIdentifier::Codepoint(cp) => format!("U+{cp:04X}"),
+        IconIdentifier::GlyphId(gid) => format!("glyph {}", gid.to_u32()),
+        IconIdentifier::Sequence(codepoints) => codepoints
+            .iter()
+            .map(|cp| format!("U+{cp:04X}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// The layers of a color glyph if color was requested and the font has one, else the outline
+fn draw_layers(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+    gid: GlyphId,
+    location: LocationRef,
+    paths: Option<&PathCache>,
+) -> Result<Vec<Layer>, DrawSvgError> {
+    let color_glyph = options
+        .color
+        .then(|| font.color_glyphs().get(gid))
+        .flatten();
+    let Some(color_glyph) = color_glyph else {
+        let path = match paths {
+            Some(paths) => paths.draw_glyph_id(font, &options.identifier, gid, location),
+            None => draw_glyph_id(font, &options.identifier, gid, location),
+        };
+        return Ok(vec![Layer::new(path?)]);
+    };
+    let mut painter = LayerPainter::new(font, &options.identifier, location)?;
+    color_glyph
+        .paint(location, &mut painter)
+        .map_err(|e| DrawSvgError::PaintError(options.identifier.clone(), gid, e))?;
+    painter.into_layers()
+}
+
+/// Draws `gid` at `location` in font units, flipped to be Y-down
+///
+/// `identifier` is only used to report errors.
+pub(crate) fn draw_glyph_id(
+    font: &FontRef,
+    identifier: &IconIdentifier,
+    gid: GlyphId,
+    location: LocationRef,
+) -> Result<BezPath, DrawSvgError> {
+    let glyph = font
+        .outline_glyphs()
+        .get(gid)
+        .ok_or(DrawSvgError::NoOutline(identifier.clone(), gid))?;
+
+    // Draw the glyph. Fonts are Y-up, svg Y-down so flip-y.
+    let mut svg_path_pen = SvgPathPen::new();
+
+    glyph
+        .draw(
+            DrawSettings::unhinted(Size::unscaled(), location)
+                .with_path_style(ToPathStyle::HarfBuzz),
+            &mut svg_path_pen,
+        )
+        .map_err(|e| DrawSvgError::DrawError(identifier.clone(), gid, e))?;
+
+    Ok(svg_path_pen.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{Affine, BezPath, Rect};
+
+    use crate::document::{canonicalize, IconDocument, Layer};
+
+    #[test]
+    fn view_box_is_em_box() {
+        let document = IconDocument::from_layers(960, 24.0, Vec::new());
+        assert_eq!(Rect::new(0.0, -960.0, 960.0, 0.0), document.view_box);
+        assert_eq!((24.0, 24.0), document.output_size());
+    }
+
+    #[test]
+    fn layers_apply_transform() {
+        let path = BezPath::from_svg("M0,0 L10,0 L10,10 Z").unwrap();
+        let mut layer = Layer::new(path.clone());
+        assert_eq!(path, layer.transformed_path());
+
+        layer.transform = Affine::translate((5.0, 0.0));
+        assert_eq!(
+            BezPath::from_svg("M5,0 L15,0 L15,10 Z").unwrap(),
+            layer.transformed_path()
+        );
+    }
+
+    #[test]
+    fn canonical_paths_ignore_subpath_order() {
+        let a = BezPath::from_svg("M0,100 L10,100 L10,110 Z M0,0 L10,0 L10,10 Z").unwrap();
+        let b = BezPath::from_svg("M0,0 L10,0 L10,10 Z M0,100 L10,100 L10,110 Z").unwrap();
+        assert_eq!(canonicalize(&b), canonicalize(&a));
+        assert_eq!(b, canonicalize(&a));
+    }
+
+    #[test]
+    fn canonical_paths_round() {
+        let path = BezPath::from_svg("M-0.001,1.23456 L10,0 Z").unwrap();
+        assert_eq!("M0,1.23 L10,0 Z", canonicalize(&path).to_svg());
+    }
+}
@@ -1,30 +1,192 @@
-use skrifa::{outline::DrawError, raw::ReadError, GlyphId};
+use skrifa::{color::PaintError, outline::DrawError, raw::ReadError, GlyphId, Tag};
+use std::io;
+#[cfg(feature = "write-fonts")]
+use std::path::PathBuf;
 
 use thiserror::Error;
 
 use crate::iconid::IconIdentifier;
+
+/// Any error from this crate.
+///
+/// New variants may be added as backends are, match on [Error::code] to handle specific
+/// failures without breaking when they are.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Resolution(IconResolutionError),
+    #[error(transparent)]
+    Draw(DrawSvgError),
+    #[error(transparent)]
+    Location(LocationError),
+    #[error(transparent)]
+    Batch(BatchError),
+    #[error(transparent)]
+    Lottie(LottieError),
+    #[error(transparent)]
+    AnimatedVector(AnimatedVectorError),
+    #[error(transparent)]
+    Xml(XmlError),
+    #[error(transparent)]
+    ImageDiff(ImageDiffError),
+    #[cfg(feature = "write-fonts")]
+    #[error(transparent)]
+    BuildFont(BuildFontError),
+    #[cfg(feature = "write-fonts")]
+    #[error(transparent)]
+    Subset(SubsetError),
+    #[cfg(feature = "write-fonts")]
+    #[error(transparent)]
+    Instance(InstanceError),
+    #[cfg(feature = "zip")]
+    #[error(transparent)]
+    Archive(ArchiveError),
+}
+
+impl Error {
+    /// A short identifier for the kind of failure, e.g. `no_outline`.
+    ///
+    /// Codes are stable across releases, messages are not.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Resolution(e) => resolution_code(e),
+            Error::Draw(e) => draw_code(e),
+            Error::Location(LocationError::NoAxis(..)) => "no_axis",
+            Error::Location(LocationError::OutOfRange(..)) => "out_of_range",
+            Error::Batch(BatchError::DrawSvgError(e)) => draw_code(e),
+            Error::Batch(BatchError::SinkError(..)) => "io",
+            Error::Batch(BatchError::ThreadPoolError(..)) => "thread_pool",
+            Error::Lottie(LottieError::DrawSvgError(e)) => draw_code(e),
+            Error::Lottie(LottieError::TooFewLocations(..)) => "too_few_locations",
+            Error::Lottie(LottieError::IncompatibleOutlines(..)) => "incompatible_outlines",
+            Error::AnimatedVector(AnimatedVectorError::DrawSvgError(e)) => draw_code(e),
+            Error::AnimatedVector(AnimatedVectorError::IncompatibleOutlines(..)) => {
+                "incompatible_outlines"
+            }
+            Error::Xml(XmlError::ParseError(..)) => "xml_parse",
+            Error::ImageDiff(ImageDiffError::InvalidLength(..)) => "invalid_image",
+            Error::ImageDiff(ImageDiffError::SizeMismatch(..)) => "image_size_mismatch",
+            #[cfg(feature = "write-fonts")]
+            Error::BuildFont(BuildFontError::IoError(..)) => "io",
+            #[cfg(feature = "write-fonts")]
+            Error::BuildFont(_) => "build_font",
+            #[cfg(feature = "write-fonts")]
+            Error::Subset(SubsetError::ResolutionError(_, e) | SubsetError::IconsError(e)) => {
+                resolution_code(e)
+            }
+            #[cfg(feature = "write-fonts")]
+            Error::Subset(SubsetError::ReadError(..)) => "read",
+            #[cfg(feature = "write-fonts")]
+            Error::Subset(SubsetError::CompileError(..)) => "build_font",
+            #[cfg(feature = "write-fonts")]
+            Error::Instance(InstanceError::DrawSvgError(e)) => draw_code(e),
+            #[cfg(feature = "write-fonts")]
+            Error::Instance(InstanceError::ReadError(..)) => "read",
+            #[cfg(feature = "write-fonts")]
+            Error::Instance(InstanceError::CompileError(..)) => "build_font",
+            #[cfg(feature = "zip")]
+            Error::Archive(ArchiveError::DrawSvgError(e)) => draw_code(e),
+            #[cfg(feature = "zip")]
+            Error::Archive(ArchiveError::IoError(..)) => "io",
+            #[cfg(feature = "zip")]
+            Error::Archive(ArchiveError::ZipError(..)) => "zip",
+        }
+    }
+}
+
+fn draw_code(error: &DrawSvgError) -> &'static str {
+    match error {
+        DrawSvgError::ResolutionError(_, e) | DrawSvgError::IconsError(e) => resolution_code(e),
+        DrawSvgError::NoOutline(..) => "no_outline",
+        DrawSvgError::DrawError(..) => "draw",
+        DrawSvgError::ReadError(..) => "read",
+        DrawSvgError::NoAxis(..) => "no_axis",
+        DrawSvgError::PaintError(..) => "paint",
+    }
+}
+
+fn resolution_code(error: &IconResolutionError) -> &'static str {
+    match error {
+        IconResolutionError::ReadError(..) => "read",
+        IconResolutionError::UnmappedCharError(..) => "unmapped_char",
+        IconResolutionError::NoGlyphIds(..) => "no_glyph_ids",
+        IconResolutionError::NoLigature(..) | IconResolutionError::NoSequenceLigature(..) => {
+            "no_ligature"
+        }
+        IconResolutionError::NoCmapEntry(..) => "no_cmap_entry",
+        IconResolutionError::NoCmapEntryForGid(..) => "no_cmap_entry_for_gid",
+        IconResolutionError::InvalidCharacter(..) => "invalid_character",
+        IconResolutionError::Invalid(..) => "invalid",
+    }
+}
+
+macro_rules! impl_from_for_error {
+    ($($(#[$attr:meta])* $variant:ident($error:ty)),*) => {
+        $(
+            $(#[$attr])*
+            impl From<$error> for Error {
+                fn from(obj: $error) -> Self {
+                    Self::$variant(obj)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_error!(
+    Resolution(IconResolutionError),
+    Draw(DrawSvgError),
+    Location(LocationError),
+    Batch(BatchError),
+    Lottie(LottieError),
+    AnimatedVector(AnimatedVectorError),
+    Xml(XmlError),
+    ImageDiff(ImageDiffError),
+    #[cfg(feature = "write-fonts")]
+    BuildFont(BuildFontError),
+    #[cfg(feature = "write-fonts")]
+    Subset(SubsetError),
+    #[cfg(feature = "write-fonts")]
+    Instance(InstanceError),
+    #[cfg(feature = "zip")]
+    Archive(ArchiveError)
+);
+
+/// New variants may be added without a breaking release, see [Error::code]
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum DrawSvgError {
     #[error("Unable to determine glyph id for {0:?}: {1}")]
-    ResolutionError(IconIdentifier, IconResolutionError),
+    ResolutionError(IconIdentifier, #[source] IconResolutionError),
     #[error("{0:?} ({1}) has no outline")]
     NoOutline(IconIdentifier, GlyphId),
     #[error("{0:?} ({1}) failed to draw: {2}")]
     DrawError(IconIdentifier, GlyphId, DrawError),
     #[error("Unable to read {0}: {1}")]
-    ReadError(&'static str, skrifa::raw::ReadError),
+    ReadError(&'static str, #[source] skrifa::raw::ReadError),
+    #[error("Unable to list icons: {0}")]
+    IconsError(#[source] IconResolutionError),
+    #[error("The font has no '{0}' axis")]
+    NoAxis(String),
+    #[error("{0:?} ({1}) failed to paint: {2}")]
+    PaintError(IconIdentifier, GlyphId, PaintError),
 }
 
+/// New variants may be added without a breaking release, see [Error::code]
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum IconResolutionError {
     #[error("{0}")]
-    ReadError(ReadError),
+    ReadError(#[source] ReadError),
     #[error("No character mapping for '{0}'")]
     UnmappedCharError(char),
     #[error("The icon name '{0}' resolved to 0 glyph ids")]
     NoGlyphIds(String),
     #[error("The icon name '{0}' has no ligature")]
     NoLigature(String),
+    #[error("The codepoint sequence {0:04x?} has no ligature")]
+    NoSequenceLigature(Vec<u32>),
     #[error("The codepoint 0x{0:04x} has no cmap entry")]
     NoCmapEntry(u32),
     #[error("The gid '{0}' has no cmap entry.")]
@@ -40,3 +202,238 @@ impl From<ReadError> for IconResolutionError {
         Self::ReadError(obj)
     }
 }
+
+#[derive(Debug, Error)]
+pub enum LocationError {
+    #[error("The font has no '{0}' axis")]
+    NoAxis(Tag),
+    #[error("{1} is outside the '{0}' axis range of {2} to {3}")]
+    OutOfRange(Tag, f32, f32, f32),
+}
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error(transparent)]
+    DrawSvgError(DrawSvgError),
+    #[error("Unable to write {0}: {1}")]
+    SinkError(String, #[source] io::Error),
+    #[error("Unable to start rendering threads: {0}")]
+    ThreadPoolError(#[source] rayon::ThreadPoolBuildError),
+}
+
+impl From<DrawSvgError> for BatchError {
+    fn from(obj: DrawSvgError) -> Self {
+        Self::DrawSvgError(obj)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LottieError {
+    #[error(transparent)]
+    DrawSvgError(DrawSvgError),
+    #[error("At least two locations are needed to animate, got {0}")]
+    TooFewLocations(usize),
+    #[error("{0:?} at location {1} is not interpolation compatible with the first location")]
+    IncompatibleOutlines(IconIdentifier, usize),
+}
+
+impl From<DrawSvgError> for LottieError {
+    fn from(obj: DrawSvgError) -> Self {
+        Self::DrawSvgError(obj)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnimatedVectorError {
+    #[error(transparent)]
+    DrawSvgError(DrawSvgError),
+    #[error("{0:?} is not interpolation compatible between the locations")]
+    IncompatibleOutlines(IconIdentifier),
+}
+
+impl From<DrawSvgError> for AnimatedVectorError {
+    fn from(obj: DrawSvgError) -> Self {
+        Self::DrawSvgError(obj)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum XmlError {
+    #[error("Unable to parse xml: {0}")]
+    ParseError(#[source] roxmltree::Error),
+}
+
+impl From<roxmltree::Error> for XmlError {
+    fn from(obj: roxmltree::Error) -> Self {
+        Self::ParseError(obj)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ImageDiffError {
+    #[error("A {0}x{1} RGBA image needs {} bytes, got {2}", .0 * .1 * 4)]
+    InvalidLength(usize, usize, usize),
+    #[error("Images must be the same size, expected {0:?} but got {1:?}")]
+    SizeMismatch((usize, usize), (usize, usize)),
+}
+
+#[cfg(feature = "write-fonts")]
+#[derive(Debug, Error)]
+pub enum BuildFontError {
+    #[error("Unable to read {0}: {1}")]
+    IoError(PathBuf, #[source] io::Error),
+    #[error("Unable to load svg for '{0}': {1}")]
+    SvgError(String, String),
+    #[error("Icon names must be non-empty ascii alphanumerics or '_', got '{0}'")]
+    InvalidName(String),
+    #[error("The icon name '{0}' is used more than once")]
+    DuplicateName(String),
+    #[error("The codepoint 0x{0:04x} is already assigned to '{1}'")]
+    DuplicateCodepoint(u32, String),
+    #[error("The codepoint 0x{0:04x} is not a valid character")]
+    InvalidCodepoint(u32),
+    #[error("No private use codepoints are left to assign to '{0}'")]
+    OutOfCodepoints(String),
+    #[error("Expected 'name hex_codepoint', got '{0}'")]
+    InvalidCodepointsLine(String),
+    #[error("{0} glyphs exceeds the limit of 65535")]
+    TooManyGlyphs(usize),
+    #[error("Unable to compile font: {0}")]
+    CompileError(String),
+}
+
+#[cfg(feature = "write-fonts")]
+#[derive(Debug, Error)]
+pub enum SubsetError {
+    #[error("Unable to determine glyph id for {0:?}: {1}")]
+    ResolutionError(IconIdentifier, #[source] IconResolutionError),
+    #[error("Unable to list icons: {0}")]
+    IconsError(#[source] IconResolutionError),
+    #[error("Unable to read {0}: {1}")]
+    ReadError(&'static str, #[source] ReadError),
+    #[error("Unable to compile font: {0}")]
+    CompileError(String),
+}
+
+#[cfg(feature = "write-fonts")]
+#[derive(Debug, Error)]
+pub enum InstanceError {
+    #[error(transparent)]
+    DrawSvgError(DrawSvgError),
+    #[error("Unable to read {0}: {1}")]
+    ReadError(&'static str, #[source] ReadError),
+    #[error("Unable to compile font: {0}")]
+    CompileError(String),
+}
+
+#[cfg(feature = "write-fonts")]
+impl From<DrawSvgError> for InstanceError {
+    fn from(obj: DrawSvgError) -> Self {
+        Self::DrawSvgError(obj)
+    }
+}
+
+#[cfg(feature = "zip")]
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    DrawSvgError(DrawSvgError),
+    #[error("Unable to write {0}: {1}")]
+    IoError(String, #[source] io::Error),
+    #[error("Unable to write zip: {0}")]
+    ZipError(#[source] zip::result::ZipError),
+}
+
+#[cfg(feature = "zip")]
+impl From<DrawSvgError> for ArchiveError {
+    fn from(obj: DrawSvgError) -> Self {
+        Self::DrawSvgError(obj)
+    }
+}
+
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(obj: zip::result::ZipError) -> Self {
+        Self::ZipError(obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use skrifa::GlyphId;
+
+    use crate::{
+        error::{
+            AnimatedVectorError, BatchError, DrawSvgError, Error, IconResolutionError, LottieError,
+        },
+        iconid,
+    };
+
+    #[test]
+    fn codes_see_through_wrappers() {
+        let draw = DrawSvgError::ResolutionError(
+            iconid::MAIL.clone(),
+            IconResolutionError::NoCmapEntry(0xe158),
+        );
+        assert_eq!("no_cmap_entry", Error::from(draw).code());
+        let lottie = LottieError::from(DrawSvgError::NoOutline(
+            iconid::MAIL.clone(),
+            GlyphId::new(3),
+        ));
+        assert_eq!("no_outline", Error::from(lottie).code());
+        assert_eq!(
+            "too_few_locations",
+            Error::from(LottieError::TooFewLocations(1)).code()
+        );
+        assert_eq!(
+            "incompatible_outlines",
+            Error::from(AnimatedVectorError::IncompatibleOutlines(
+                iconid::MAIL.clone()
+            ))
+            .code()
+        );
+    }
+
+    #[test]
+    fn display_and_source_chain() {
+        let error = Error::from(DrawSvgError::ResolutionError(
+            iconid::MAIL.clone(),
+            IconResolutionError::NoCmapEntry(0xe158),
+        ));
+        assert_eq!(
+            "Unable to determine glyph id for Codepoint(57688): The codepoint 0xe158 has no cmap entry",
+            error.to_string()
+        );
+        assert_eq!(
+            "The codepoint 0xe158 has no cmap entry",
+            error.source().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn wrappers_keep_the_source_chain() {
+        let draw = DrawSvgError::ResolutionError(
+            iconid::MAIL.clone(),
+            IconResolutionError::NoCmapEntry(0xe158),
+        );
+        let error = Error::from(BatchError::from(draw));
+
+        let mut chain = vec![error.to_string()];
+        let mut source = error.source();
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+
+        assert_eq!(
+            vec![
+                "Unable to determine glyph id for Codepoint(57688): The codepoint 0xe158 has no cmap entry"
+                    .to_string(),
+                "The codepoint 0xe158 has no cmap entry".to_string(),
+            ],
+            chain
+        );
+    }
+}
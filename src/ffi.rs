@@ -0,0 +1,239 @@
+//! Bindings for use from C, and so C++ or Java through JNI.
+//!
+//! Functions return [SLEIPNIR_OK] or one of the other `SLEIPNIR_` status codes. Strings returned
+//! through out parameters are owned by the caller and must be released with [sleipnir_free].
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic, slice,
+};
+
+use skrifa::{setting::VariationSetting, FontRef, Tag};
+
+use crate::{
+    error::{DrawSvgError, IconResolutionError},
+    icon2svg::{self, DrawOptions},
+    iconid::IconIdentifier,
+    pathstyle::PathStyle,
+};
+
+pub const SLEIPNIR_OK: i32 = 0;
+/// A required pointer was null or a string wasn't UTF-8
+pub const SLEIPNIR_INVALID_ARGUMENT: i32 = 1;
+/// The font couldn't be read
+pub const SLEIPNIR_INVALID_FONT: i32 = 2;
+/// No icon in the font matches the name
+pub const SLEIPNIR_ICON_NOT_FOUND: i32 = 3;
+/// The icon couldn't be drawn, or the font couldn't be searched for it
+pub const SLEIPNIR_DRAW_FAILED: i32 = 4;
+
+/// An axis position in user coordinates, e.g. `{ {'w', 'g', 'h', 't'}, 700.0 }`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SleipnirAxisValue {
+    pub tag: [u8; 4],
+    pub value: f32,
+}
+
+/// Draws the icon named `name` as an svg, `width_height` units square, into `*svg`.
+///
+/// Axes not in `coords` are at default. `coords` may be null when `coords_len` is 0.
+///
+/// # Safety
+///
+/// `font` must point to `font_len` readable bytes, `name` to a nul-terminated string, `coords`
+/// to `coords_len` values and `svg` to writable storage for a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sleipnir_draw_icon_svg(
+    font: *const u8,
+    font_len: usize,
+    name: *const c_char,
+    width_height: f32,
+    coords: *const SleipnirAxisValue,
+    coords_len: usize,
+    svg: *mut *mut c_char,
+) -> i32 {
+    // Unwinding into C is undefined behavior
+    panic::catch_unwind(|| {
+        draw_icon_svg(font, font_len, name, width_height, coords, coords_len, svg)
+    })
+    .unwrap_or(SLEIPNIR_DRAW_FAILED)
+}
+
+unsafe fn draw_icon_svg(
+    font: *const u8,
+    font_len: usize,
+    name: *const c_char,
+    width_height: f32,
+    coords: *const SleipnirAxisValue,
+    coords_len: usize,
+    svg: *mut *mut c_char,
+) -> i32 {
+    if font.is_null() || name.is_null() || svg.is_null() || (coords.is_null() && coords_len > 0) {
+        return SLEIPNIR_INVALID_ARGUMENT;
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return SLEIPNIR_INVALID_ARGUMENT;
+    };
+    let Ok(font) = FontRef::new(slice::from_raw_parts(font, font_len)) else {
+        return SLEIPNIR_INVALID_FONT;
+    };
+    let coords = if coords_len > 0 {
+        slice::from_raw_parts(coords, coords_len)
+    } else {
+        &[]
+    };
+    let options = DrawOptions::new(
+        IconIdentifier::Name(name.into()),
+        width_height,
+        Default::default(),
+        PathStyle::Unchanged,
+    )
+    .with_user_coords(
+        coords
+            .iter()
+            .map(|coord| VariationSetting::new(Tag::new(&coord.tag), coord.value)),
+    );
+    match icon2svg::draw_icon(&font, &options) {
+        Ok(drawn) => {
+            // Svgs we write never contain nul
+            *svg = CString::new(drawn).unwrap_or_default().into_raw();
+            SLEIPNIR_OK
+        }
+        Err(DrawSvgError::ResolutionError(
+            _,
+            IconResolutionError::UnmappedCharError(..)
+            | IconResolutionError::NoGlyphIds(..)
+            | IconResolutionError::NoLigature(..),
+        )) => SLEIPNIR_ICON_NOT_FOUND,
+        Err(_) => SLEIPNIR_DRAW_FAILED,
+    }
+}
+
+/// Releases a string returned by this library, null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or have come from this library and not yet been freed.
+#[no_mangle]
+pub unsafe extern "C" fn sleipnir_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{c_char, CStr},
+        ptr,
+    };
+
+    use skrifa::{FontRef, MetadataProvider, Tag};
+    use write_fonts::FontBuilder;
+
+    use crate::{
+        ffi::{
+            sleipnir_draw_icon_svg, sleipnir_free, SleipnirAxisValue, SLEIPNIR_DRAW_FAILED,
+            SLEIPNIR_ICON_NOT_FOUND, SLEIPNIR_INVALID_ARGUMENT, SLEIPNIR_INVALID_FONT, SLEIPNIR_OK,
+        },
+        icon2svg::{draw_icon, DrawOptions},
+        iconid,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn draw(font: &[u8], name: &CStr, coords: &[SleipnirAxisValue]) -> (i32, Option<String>) {
+        let mut svg: *mut c_char = ptr::null_mut();
+        let status = unsafe {
+            sleipnir_draw_icon_svg(
+                font.as_ptr(),
+                font.len(),
+                name.as_ptr(),
+                24.0,
+                coords.as_ptr(),
+                coords.len(),
+                &mut svg,
+            )
+        };
+        if svg.is_null() {
+            return (status, None);
+        }
+        let drawn = unsafe { CStr::from_ptr(svg) }.to_str().unwrap().to_string();
+        unsafe { sleipnir_free(svg) };
+        (status, Some(drawn))
+    }
+
+    #[test]
+    fn draw_by_name() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("wght", 700.0)]);
+        let expected = draw_icon(
+            &font,
+            &DrawOptions::new(
+                iconid::LAN.clone(),
+                24.0,
+                (&loc).into(),
+                PathStyle::Unchanged,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (SLEIPNIR_OK, Some(expected)),
+            draw(
+                testdata::ICON_FONT,
+                c"lan",
+                &[SleipnirAxisValue {
+                    tag: *b"wght",
+                    value: 700.0
+                }]
+            )
+        );
+    }
+
+    #[test]
+    fn failures() {
+        assert_eq!(
+            (SLEIPNIR_ICON_NOT_FOUND, None),
+            draw(testdata::ICON_FONT, c"nope", &[])
+        );
+        assert_eq!((SLEIPNIR_INVALID_FONT, None), draw(b"nope", c"lan", &[]));
+        assert_eq!(
+            (SLEIPNIR_INVALID_ARGUMENT, None),
+            draw(testdata::ICON_FONT, c"\xff", &[])
+        );
+        let status = unsafe {
+            sleipnir_draw_icon_svg(
+                ptr::null(),
+                0,
+                c"lan".as_ptr(),
+                24.0,
+                ptr::null(),
+                0,
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(SLEIPNIR_INVALID_ARGUMENT, status);
+        unsafe { sleipnir_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn unreadable_gsub_is_not_a_missing_icon() {
+        // The ligature is found, the feature variations then point past the end of GSUB
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let mut gsub = font
+            .table_data(Tag::new(b"GSUB"))
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        assert_eq!([0, 1, 0, 1], gsub[..4]);
+        gsub[10..14].copy_from_slice(&u32::MAX.to_be_bytes());
+        let font_data = FontBuilder::new()
+            .add_raw(Tag::new(b"GSUB"), gsub)
+            .copy_missing_tables(font)
+            .build();
+
+        assert_eq!((SLEIPNIR_DRAW_FAILED, None), draw(&font_data, c"lan", &[]));
+    }
+}
@@ -0,0 +1,166 @@
+//! Produces a self-contained html page previewing every icon in a font
+
+use skrifa::{instance::LocationRef, FontRef};
+
+use crate::{
+    error::DrawSvgError,
+    icon2svg::{draw_icon, DrawOptions},
+    iconid::{IconIdentifier, Icons},
+    pathstyle::PathStyle,
+};
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+#search{font-size:1.2em;padding:.4em;width:20em}\
+#icons{display:flex;flex-wrap:wrap;gap:1em;margin-top:1em}\
+figure{margin:0;width:8em;text-align:center}\
+figcaption{font-size:.8em;overflow-wrap:anywhere}\
+.codepoint{display:block;color:#666;font-family:monospace}";
+
+const SCRIPT: &str = "document.getElementById('search').addEventListener('input',e=>{\
+const q=e.target.value.trim().toLowerCase();\
+for(const f of document.querySelectorAll('#icons figure')){\
+f.hidden=q.length>0&&!f.dataset.search.includes(q);}});";
+
+pub struct GalleryOptions<'a> {
+    title: String,
+    icon_size: f32,
+    location: LocationRef<'a>,
+    style: PathStyle,
+}
+
+impl<'a> GalleryOptions<'a> {
+    pub fn new(
+        title: &str,
+        icon_size: f32,
+        location: LocationRef<'a>,
+        style: PathStyle,
+    ) -> GalleryOptions<'a> {
+        GalleryOptions {
+            title: title.to_string(),
+            icon_size,
+            location,
+            style,
+        }
+    }
+}
+
+/// Renders every icon in `font`, sorted by name, with its names and codepoints.
+///
+/// The page has no external dependencies; svgs are inline and a small script filters
+/// icons by name or codepoint as you type in the search box.
+pub fn icon_gallery(font: &FontRef, options: &GalleryOptions) -> Result<String, DrawSvgError> {
+    let mut icons = font.icons().map_err(DrawSvgError::IconsError)?;
+    icons.sort_by(|a, b| a.names.cmp(&b.names));
+
+    let title = escape_html(&options.title);
+    let mut html = String::with_capacity(1024 * icons.len());
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{title}</title>\n<style>{STYLE}</style>\n"));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{title}</h1>\n"));
+    html.push_str(&format!(
+        "<input id=\"search\" type=\"search\" placeholder=\"Search {} icons\" autofocus>\n",
+        icons.len()
+    ));
+    html.push_str("<div id=\"icons\">\n");
+
+    for icon in icons.iter() {
+        let draw_options = DrawOptions::new(
+            IconIdentifier::GlyphId(icon.gid),
+            options.icon_size,
+            options.location,
+            options.style,
+        );
+        let svg = draw_icon(font, &draw_options)?;
+        let codepoints: Vec<_> = icon
+            .codepoints
+            .iter()
+            .map(|cp| format!("{cp:04x}"))
+            .collect();
+        let search = escape_html(
+            &icon
+                .names
+                .iter()
+                .chain(codepoints.iter())
+                .map(|s| s.to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+
+        html.push_str(&format!(
+            "<figure data-search=\"{search}\">{svg}<figcaption>"
+        ));
+        for name in icon.names.iter() {
+            html.push_str(&format!("<div>{}</div>", escape_html(name)));
+        }
+        html.push_str(&format!(
+            "<span class=\"codepoint\">{}</span>",
+            codepoints.join(" ")
+        ));
+        html.push_str("</figcaption></figure>\n");
+    }
+
+    html.push_str("</div>\n");
+    html.push_str(&format!("<script>{SCRIPT}</script>\n"));
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{instance::Location, FontRef};
+
+    use crate::{
+        gallery::{escape_html, icon_gallery, GalleryOptions},
+        iconid::Icons,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn gallery_has_every_icon() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let loc = Location::default();
+        let options = GalleryOptions::new("Popular", 24.0, (&loc).into(), PathStyle::Compact);
+
+        let html = icon_gallery(&font, &options).unwrap();
+
+        assert_eq!(font.icons().unwrap().len(), html.matches("<figure").count());
+        assert!(html.contains(testdata::INFO_COMPACT_SVG.trim()));
+        assert!(html.contains("<figure data-search=\"mic_none mic e029 e02a e31d\">"));
+    }
+
+    #[test]
+    fn gallery_escapes_title() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let loc = Location::default();
+        let options = GalleryOptions::new("<b>&", 24.0, (&loc).into(), PathStyle::Compact);
+
+        let html = icon_gallery(&font, &options).unwrap();
+
+        assert!(html.contains("<title>&lt;b&gt;&amp;</title>"));
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            "a&amp;b &lt;c&gt; &quot;d&quot; &#39;e&#39;",
+            escape_html("a&b <c> \"d\" 'e'")
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Produces Android AnimatedVectorDrawables of icons morphing between locations in designspace
+
+use kurbo::Affine;
+use skrifa::{instance::LocationRef, FontRef};
+
+use crate::{
+    document::IconDocument,
+    error::AnimatedVectorError,
+    icon2svg::DrawOptions,
+    icon2xml::{declare_namespaces, write_vector},
+    iconid::IconIdentifier,
+    pathstyle::PathStyle,
+    xml::{Namespace, XmlElement},
+};
+
+pub struct AnimatedVectorOptions<'a> {
+    identifier: IconIdentifier,
+    width_height: f32,
+    from: LocationRef<'a>,
+    to: LocationRef<'a>,
+    duration_ms: u32,
+    transform: Affine,
+}
+
+impl<'a> AnimatedVectorOptions<'a> {
+    /// Morphs from one location to the other, e.g. FILL 0 to FILL 1.
+    ///
+    /// Defaults to a 300ms animation.
+    pub fn new(
+        identifier: IconIdentifier,
+        width_height: f32,
+        from: LocationRef<'a>,
+        to: LocationRef<'a>,
+    ) -> AnimatedVectorOptions<'a> {
+        AnimatedVectorOptions {
+            identifier,
+            width_height,
+            from,
+            to,
+            duration_ms: 300,
+            transform: Affine::IDENTITY,
+        }
+    }
+
+    pub fn with_duration_ms(mut self, duration_ms: u32) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    /// As [DrawOptions::with_transform], applied at both locations
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+/// Draws the icon at both locations as one AnimatedVectorDrawable whose paths morph between them.
+///
+/// Outlines are drawn as-is, as with [PathStyle::Unchanged], so Android can interpolate the
+/// path data. If the commands differ, for example because a location substitutes a different
+/// glyph, [AnimatedVectorError::IncompatibleOutlines] results.
+pub fn draw_animated_vector(
+    font: &FontRef,
+    options: &AnimatedVectorOptions,
+) -> Result<String, AnimatedVectorError> {
+    let draw = |location| -> Result<XmlElement, AnimatedVectorError> {
+        let draw_options = DrawOptions::new(
+            options.identifier.clone(),
+            options.width_height,
+            location,
+            PathStyle::Unchanged,
+        )
+        .with_transform(options.transform);
+        let document = IconDocument::draw(font, &draw_options)?;
+        Ok(write_vector(&document, &draw_options))
+    };
+    let mut vector = draw(options.from)?;
+    let to = draw(options.to)?;
+
+    let from_paths = path_data(&vector);
+    let to_paths = path_data(&to);
+    let compatible = from_paths.len() == to_paths.len()
+        && from_paths
+            .iter()
+            .zip(to_paths.iter())
+            .all(|(from, to)| commands(from).eq(commands(to)));
+    if !compatible {
+        return Err(AnimatedVectorError::IncompatibleOutlines(
+            options.identifier.clone(),
+        ));
+    }
+
+    let android = &Namespace::ANDROID;
+    let mut targets = Vec::with_capacity(to_paths.len());
+    for (i, (path, value_to)) in vector
+        .children_mut()
+        .iter_mut()
+        .filter_map(|node| node.as_element_mut())
+        .zip(to_paths)
+        .enumerate()
+    {
+        let name = format!("p{i}");
+        path.set_ns_attribute(android, "name", &name);
+        let value_from = path.ns_attribute(android, "pathData").unwrap_or_default();
+        let animator = XmlElement::new("objectAnimator")
+            .with_ns_attribute(android, "propertyName", "pathData")
+            .with_ns_attribute(android, "duration", options.duration_ms)
+            .with_ns_attribute(android, "valueFrom", value_from)
+            .with_ns_attribute(android, "valueTo", value_to)
+            .with_ns_attribute(android, "valueType", "pathType");
+        targets.push(
+            XmlElement::new("target")
+                .with_ns_attribute(android, "name", name)
+                .with_child(aapt_attr("animation", animator)),
+        );
+    }
+
+    let mut animated_vector =
+        XmlElement::new("animated-vector").with_child(aapt_attr("drawable", vector));
+    for target in targets {
+        animated_vector.add_child(target);
+    }
+    declare_namespaces(&mut animated_vector);
+    Ok(animated_vector.to_string())
+}
+
+/// An inline resource for an android attribute, e.g. the drawable of an animated-vector
+fn aapt_attr(name: &str, value: XmlElement) -> XmlElement {
+    XmlElement::new_ns(&Namespace::AAPT, "attr")
+        .with_attribute("name", Namespace::ANDROID.qualify(name))
+        .with_child(value)
+}
+
+fn path_data(vector: &XmlElement) -> Vec<String> {
+    vector
+        .child_elements()
+        .filter_map(|path| path.ns_attribute(&Namespace::ANDROID, "pathData"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn commands(path_data: &str) -> impl Iterator<Item = char> + '_ {
+    path_data.chars().filter(char::is_ascii_alphabetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        error::AnimatedVectorError,
+        icon2avd::{draw_animated_vector, AnimatedVectorOptions},
+        icon2svg::DrawOptions,
+        icon2xml::draw_icon,
+        iconid,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    /// The path data of a VectorDrawable
+    fn path_data(xml: &str) -> &str {
+        let start = xml.find("android:pathData=\"").unwrap() + 18;
+        &xml[start..start + xml[start..].find('"').unwrap()]
+    }
+
+    #[test]
+    fn morph_weight() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let thin = font.axes().location(&[("wght", 100.0)]);
+        let bold = font.axes().location(&[("wght", 700.0)]);
+        let vector = |loc| {
+            draw_icon(
+                &font,
+                &DrawOptions::new(iconid::LAN.clone(), 24.0, loc, PathStyle::Unchanged),
+            )
+            .unwrap()
+        };
+        let (from, to) = (vector((&thin).into()), vector((&bold).into()));
+        let (from, to) = (path_data(&from), path_data(&to));
+
+        let avd = draw_animated_vector(
+            &font,
+            &AnimatedVectorOptions::new(iconid::LAN.clone(), 24.0, (&thin).into(), (&bold).into())
+                .with_duration_ms(250),
+        )
+        .unwrap();
+
+        assert_eq!(
+            format!(
+                concat!(
+                    "<animated-vector xmlns:android=\"http://schemas.android.com/apk/res/android\" ",
+                    "xmlns:aapt=\"http://schemas.android.com/aapt\">",
+                    "<aapt:attr name=\"android:drawable\">",
+                    "<vector android:width=\"24dp\" android:height=\"24dp\" ",
+                    "android:viewportWidth=\"960\" android:viewportHeight=\"960\">",
+                    "<path android:pathData=\"{from}\" android:fillColor=\"@android:color/white\" android:name=\"p0\"/>",
+                    "</vector></aapt:attr>",
+                    "<target android:name=\"p0\"><aapt:attr name=\"android:animation\">",
+                    "<objectAnimator android:propertyName=\"pathData\" android:duration=\"250\" ",
+                    "android:valueFrom=\"{from}\" android:valueTo=\"{to}\" android:valueType=\"pathType\"/>",
+                    "</aapt:attr></target></animated-vector>"
+                ),
+                from = from,
+                to = to
+            ),
+            avd
+        );
+        assert_ne!(from, to);
+    }
+
+    #[test]
+    fn substituted_glyph_is_incompatible() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let outlined = font.axes().location(&[("FILL", 0.0)]);
+        let filled = font.axes().location(&[("FILL", 1.0)]);
+
+        let result = draw_animated_vector(
+            &font,
+            &AnimatedVectorOptions::new(
+                iconid::MAIL.clone(),
+                24.0,
+                (&outlined).into(),
+                (&filled).into(),
+            ),
+        );
+
+        assert!(
+            matches!(result, Err(AnimatedVectorError::IncompatibleOutlines(..))),
+            "{result:?}"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Produces css rules that draw icons as masks, so the web can use icons without a bundler
+
+use skrifa::FontRef;
+
+use crate::{
+    document::IconDocument,
+    error::DrawSvgError,
+    icon2svg::{write_svg, DrawOptions},
+    iconid::IconIdentifier,
+};
+
+/// Draws the icon as a css rule for the class `class_prefix` then the icon's name, e.g.
+/// `.icon-mail` for a prefix of `icon-`.
+///
+/// The svg is inlined as a `mask-image` data URI over `currentColor`, so the icon takes the
+/// color of the text around it. Use [crate::pathstyle::PathStyle::Compact] for the smallest
+/// rules.
+pub fn draw_rule(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+    class_prefix: &str,
+) -> Result<String, DrawSvgError> {
+    let document = IconDocument::draw(font, options)?;
+    Ok(write_rule(
+        &document,
+        options,
+        &class_name(class_prefix, &options.identifier),
+    ))
+}
+
+/// Draws each of `icons` as a rule, as [draw_rule] does, with the same drawing `options`, whose
+/// own identifier is ignored. Repeated icons are drawn once.
+pub fn draw_stylesheet(
+    font: &FontRef,
+    icons: &[IconIdentifier],
+    options: &DrawOptions<'_>,
+    class_prefix: &str,
+) -> Result<String, DrawSvgError> {
+    let mut css = String::with_capacity(1024 * icons.len());
+    let mut classes: Vec<String> = Vec::with_capacity(icons.len());
+    for identifier in icons {
+        let class = class_name(class_prefix, identifier);
+        if classes.contains(&class) {
+            continue;
+        }
+        let mut icon_options = options.clone();
+        icon_options.identifier = identifier.clone();
+        let document = IconDocument::draw(font, &icon_options)?;
+        css.push_str(&write_rule(&document, &icon_options, &class));
+        classes.push(class);
+    }
+    Ok(css)
+}
+
+fn write_rule(document: &IconDocument, options: &DrawOptions<'_>, class: &str) -> String {
+    let (width, height) = document.output_size();
+    let url = format!(
+        "url(\"data:image/svg+xml,{}\")",
+        data_uri_escape(&write_svg(document, options))
+    );
+    format!(
+        concat!(
+            ".{class} {{\n",
+            "  display: inline-block;\n",
+            "  width: {width}px;\n",
+            "  height: {height}px;\n",
+            "  background-color: currentColor;\n",
+            "  -webkit-mask-image: {url};\n",
+            "  mask-image: {url};\n",
+            "  -webkit-mask-size: 100% 100%;\n",
+            "  mask-size: 100% 100%;\n",
+            "}}\n"
+        ),
+        class = class,
+        width = width,
+        height = height,
+        url = url
+    )
+}
+
+/// The class for an icon, escaped as css requires, e.g. `10k` becomes `\31 0k`
+fn class_name(prefix: &str, identifier: &IconIdentifier) -> String {
+    let mut class = String::new();
+    for (i, c) in format!("{prefix}{}", identifier.file_stem())
+        .chars()
+        .enumerate()
+    {
+        match c {
+            '0'..='9' if i == 0 => class.push_str(&format!("\\{:x} ", c as u32)),
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => class.push(c),
+            c if !c.is_ascii() => class.push(c),
+            c => {
+                class.push('\\');
+                class.push(c);
+            }
+        }
+    }
+    class
+}
+
+/// Percent-encodes what can't appear in a double quoted data URI, the rest of an svg can
+fn data_uri_escape(svg: &str) -> String {
+    let mut escaped = String::with_capacity(svg.len() + svg.len() / 4);
+    for c in svg.chars() {
+        match c {
+            '%' | '#' | '<' | '>' | '"' | '\\' | '{' | '}' | '|' | '^' | '`' => {
+                escaped.push_str(&format!("%{:02X}", c as u32))
+            }
+            c if c.is_ascii_control() || !c.is_ascii() => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    escaped.push_str(&format!("%{byte:02X}"));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        icon2css::{class_name, data_uri_escape, draw_rule, draw_stylesheet},
+        icon2svg::{draw_icon, DrawOptions},
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn rule_inlines_svg() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Compact,
+        );
+        let url = format!(
+            "url(\"data:image/svg+xml,{}\")",
+            data_uri_escape(&draw_icon(&font, &options).unwrap())
+        );
+
+        assert_eq!(
+            format!(
+                concat!(
+                    ".icon-ue158 {{\n",
+                    "  display: inline-block;\n",
+                    "  width: 24px;\n",
+                    "  height: 24px;\n",
+                    "  background-color: currentColor;\n",
+                    "  -webkit-mask-image: {url};\n",
+                    "  mask-image: {url};\n",
+                    "  -webkit-mask-size: 100% 100%;\n",
+                    "  mask-size: 100% 100%;\n",
+                    "}}\n"
+                ),
+                url = url
+            ),
+            draw_rule(&font, &options, "icon-").unwrap()
+        );
+        assert!(url.starts_with(
+            "url(\"data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22 viewBox=%220 -960 960 960%22"
+        ));
+    }
+
+    #[test]
+    fn stylesheet_draws_repeats_once() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        );
+
+        let css = draw_stylesheet(
+            &font,
+            &[
+                iconid::LAN.clone(),
+                iconid::MAIL.clone(),
+                iconid::LAN.clone(),
+            ],
+            &options,
+            "",
+        )
+        .unwrap();
+
+        assert_eq!(2, css.matches(" {\n").count());
+        assert!(css.starts_with(".lan {\n"), "{css}");
+        assert!(css.contains("\n.ue158 {\n"), "{css}");
+    }
+
+    #[test]
+    fn class_names_are_escaped() {
+        assert_eq!(
+            "\\31 0k",
+            class_name("", &IconIdentifier::Name("10k".into()))
+        );
+        assert_eq!(
+            "i-10k\\.x",
+            class_name("i-", &IconIdentifier::Name("10k.x".into()))
+        );
+    }
+
+    #[test]
+    fn data_uri_escapes() {
+        assert_eq!(
+            "%3Cpath d=%22M0,0%22 fill=%22%23fff%22/%3E %25 %C3%A9",
+            data_uri_escape("<path d=\"M0,0\" fill=\"#fff\"/> % é")
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Produces Encapsulated PostScript of icons in Google-style icon fonts
+
+use kurbo::{Affine, BezPath, PathEl, Point, Rect, Shape};
+use skrifa::FontRef;
+
+use crate::{
+    document::{title, IconDocument, Paint},
+    error::DrawSvgError,
+    icon2svg::DrawOptions,
+    iconid::IconIdentifier,
+};
+
+/// Draws an icon as an EPS file of `width_height` points square.
+///
+/// The bounding box comments cover the view box, the em box unless [DrawOptions::with_framing]
+/// says otherwise, grown to fit any ink that spills outside it.
+/// The path style in `options` is ignored, PostScript has no compact form.
+///
+/// With [DrawOptions::with_color] each layer is filled in its color, a gradient in its first
+/// stop's color, without transparency or blending. The foreground color is black,
+/// [DrawOptions::with_fill] is ignored.
+pub fn draw_icon(font: &FontRef, options: &DrawOptions<'_>) -> Result<String, DrawSvgError> {
+    Ok(write_eps(
+        &IconDocument::draw(font, options)?,
+        &options.identifier,
+    ))
+}
+
+/// Serializes a document, each layer is filled in turn
+pub(crate) fn write_eps(document: &IconDocument, identifier: &IconIdentifier) -> String {
+    // Our paths are Y-down, PostScript is Y-up with the origin at bottom left of the view box
+    let scale = document.width_height as f64 / document.upem as f64;
+    let view_box = document.view_box;
+    let transform =
+        Affine::scale_non_uniform(scale, -scale) * Affine::translate((-view_box.x0, -view_box.y1));
+    let paths: Vec<_> = document
+        .paths()
+        .map(|mut path| {
+            path.apply_affine(transform);
+            path
+        })
+        .collect();
+
+    let (width, height) = document.output_size();
+    let bbox = paths
+        .iter()
+        .filter(|path| !path.elements().is_empty())
+        .fold(
+            Rect::new(0.0, 0.0, width as f64, height as f64),
+            |bbox, path| bbox.union(path.bounding_box()),
+        );
+
+    let mut eps = String::with_capacity(1024);
+    eps.push_str("%!PS-Adobe-3.0 EPSF-3.0\n");
+    eps.push_str(&format!(
+        "%%BoundingBox: {} {} {} {}\n",
+        bbox.x0.floor(),
+        bbox.y0.floor(),
+        bbox.x1.ceil(),
+        bbox.y1.ceil()
+    ));
+    eps.push_str(&format!(
+        "%%HiResBoundingBox: {} {} {} {}\n",
+        number(bbox.x0),
+        number(bbox.y0),
+        number(bbox.x1),
+        number(bbox.y1)
+    ));
+    eps.push_str(&format!("%%Title: {}\n", title(identifier)));
+    eps.push_str("%%Creator: sleipnir\n");
+    eps.push_str("%%EndComments\n");
+    eps.push_str("gsave\n");
+    let colors = layer_colors(document);
+    for (i, path) in paths.iter().enumerate() {
+        if let Some(colors) = &colors {
+            eps.push_str(&format!("{} setrgbcolor\n", colors[i]));
+        }
+        eps.push_str("newpath\n");
+        write_path(&mut eps, path);
+        // fill is nonzero winding, matching font outlines
+        eps.push_str("fill\n");
+    }
+    eps.push_str("grestore\n");
+    eps.push_str("%%EOF\n");
+    eps
+}
+
+fn write_path(eps: &mut String, path: &BezPath) {
+    let mut current = Point::ZERO;
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                eps.push_str(&format!("{} moveto\n", coords(&[p])));
+                current = p;
+            }
+            PathEl::LineTo(p) => {
+                eps.push_str(&format!("{} lineto\n", coords(&[p])));
+                current = p;
+            }
+            PathEl::QuadTo(c, p) => {
+                // PostScript only has cubics
+                let c0 = current + (c - current) * (2.0 / 3.0);
+                let c1 = p + (c - p) * (2.0 / 3.0);
+                eps.push_str(&format!("{} curveto\n", coords(&[c0, c1, p])));
+                current = p;
+            }
+            PathEl::CurveTo(c0, c1, p) => {
+                eps.push_str(&format!("{} curveto\n", coords(&[c0, c1, p])));
+                current = p;
+            }
+            PathEl::ClosePath => eps.push_str("closepath\n"),
+        }
+    }
+}
+
+/// The color of each layer as `r g b` operands from 0 to 1, for `setrgbcolor` or PDF's `rg`.
+///
+/// None when every layer is in the foreground color, left as the default black. Gradients are
+/// given their first stop's color and alpha is dropped, plain fills can do no more.
+pub(crate) fn layer_colors(document: &IconDocument) -> Option<Vec<String>> {
+    let colors: Vec<_> = document
+        .layers
+        .iter()
+        .map(|layer| match &layer.paint {
+            Paint::Solid(color) => color.rgb,
+            Paint::LinearGradient { stops, .. } | Paint::RadialGradient { stops, .. } => {
+                stops.first().and_then(|(_, color)| color.rgb)
+            }
+        })
+        .collect();
+    if colors.iter().all(Option::is_none) {
+        return None;
+    }
+    Some(
+        colors
+            .into_iter()
+            .map(|rgb| {
+                rgb.unwrap_or_default()
+                    .map(|c| number(c as f64 / 255.0))
+                    .join(" ")
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn coords(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{} {}", number(p.x), number(p.y)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn number(value: f64) -> String {
+    let value = format!("{value:.3}");
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    match value {
+        "-0" => "0".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{instance::Location, FontRef, MetadataProvider};
+
+    use crate::{
+        icon2eps::{draw_icon, number},
+        icon2svg::{
+            tests::{colr_v0, font_with_colr},
+            DrawOptions, Framing,
+        },
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn draw_mail_eps() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let eps = draw_icon(&font, &options).unwrap();
+
+        assert!(eps.starts_with(
+            "%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 24 24\n%%HiResBoundingBox: 0 0 24 24\n%%Title: U+E158\n"
+        ));
+        assert!(eps.ends_with("closepath\nfill\ngrestore\n%%EOF\n"));
+        assert_eq!(
+            eps.matches(" moveto\n").count(),
+            eps.matches("closepath\n").count()
+        );
+    }
+
+    #[test]
+    fn frame_on_metrics() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_framing(Framing::Metrics);
+
+        let eps = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            eps.contains("%%BoundingBox: 0 0 24 29\n%%HiResBoundingBox: 0 0 24 28.8\n"),
+            "{eps}"
+        );
+    }
+
+    #[test]
+    fn draw_quads_as_curves() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let loc = Location::default();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x2e),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let eps = draw_icon(&font, &options).unwrap();
+
+        assert!(eps.contains(" curveto\n"));
+        assert!(!eps.contains("quad"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!("1.5", number(1.5));
+        assert_eq!("2", number(2.0001));
+        assert_eq!("0", number(-0.0001));
+    }
+
+    #[test]
+    fn color_layers() {
+        let font_data = font_with_colr(colr_v0());
+        let font = FontRef::new(&font_data).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Unchanged,
+        );
+
+        let mono = draw_icon(&font, &options).unwrap();
+        let color = draw_icon(&font, &options.with_color(true)).unwrap();
+
+        assert!(!mono.contains("setrgbcolor"), "{mono}");
+        assert_eq!(
+            vec!["1 0 0", "0 0 1", "0 0 0"],
+            color
+                .lines()
+                .filter_map(|line| line.strip_suffix(" setrgbcolor"))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Produces Lottie animations of icons transitioning between locations in designspace
+
+use kurbo::{Affine, BezPath, PathEl, Point, Vec2};
+use skrifa::{instance::LocationRef, FontRef};
+
+use crate::{
+    document::IconDocument, error::LottieError, icon2eps::number, icon2svg::DrawOptions,
+    iconid::IconIdentifier, json, pathstyle::PathStyle,
+};
+
+// ease-in-out, the same curve as css ease-in-out
+const EASE_OUT: (f64, f64) = (0.42, 0.0);
+const EASE_IN: (f64, f64) = (0.58, 1.0);
+
+pub struct LottieOptions<'a> {
+    identifier: IconIdentifier,
+    width_height: f32,
+    locations: Vec<LocationRef<'a>>,
+    frame_rate: f32,
+    transition_frames: u32,
+    transform: Affine,
+}
+
+impl<'a> LottieOptions<'a> {
+    /// Animates through `locations` in order, e.g. FILL 0 then FILL 1.
+    ///
+    /// Defaults to 60 frames per second with 30 frames per transition.
+    pub fn new(
+        identifier: IconIdentifier,
+        width_height: f32,
+        locations: Vec<LocationRef<'a>>,
+    ) -> LottieOptions<'a> {
+        LottieOptions {
+            identifier,
+            width_height,
+            locations,
+            frame_rate: 60.0,
+            transition_frames: 30,
+            transform: Affine::IDENTITY,
+        }
+    }
+
+    pub fn with_frame_rate(mut self, frame_rate: f32) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+
+    /// How many frames it takes to move from one location to the next
+    pub fn with_transition_frames(mut self, transition_frames: u32) -> Self {
+        self.transition_frames = transition_frames;
+        self
+    }
+
+    /// As [DrawOptions::with_transform], applied at every location
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+/// A contour as Lottie wants it, cubic segments with tangents relative to their vertex
+#[derive(Debug, PartialEq)]
+struct Contour {
+    closed: bool,
+    vertices: Vec<Point>,
+    in_tangents: Vec<Vec2>,
+    out_tangents: Vec<Vec2>,
+}
+
+impl Contour {
+    fn new(start: Point) -> Contour {
+        Contour {
+            closed: false,
+            vertices: vec![start],
+            in_tangents: vec![Vec2::ZERO],
+            out_tangents: vec![Vec2::ZERO],
+        }
+    }
+
+    fn last(&self) -> Point {
+        *self.vertices.last().unwrap()
+    }
+
+    fn push(&mut self, out_control: Point, in_control: Point, end: Point) {
+        *self.out_tangents.last_mut().unwrap() = out_control - self.last();
+        self.vertices.push(end);
+        self.in_tangents.push(in_control - end);
+        self.out_tangents.push(Vec2::ZERO);
+    }
+}
+
+/// Draws the icon at each location and animates between them.
+///
+/// Outlines are drawn as-is, as with [crate::pathstyle::PathStyle::Unchanged], so for a
+/// variable font the command structure matches at every location. If it doesn't, for example
+/// because a location substitutes a different glyph, [LottieError::IncompatibleOutlines] results.
+pub fn draw_lottie(font: &FontRef, options: &LottieOptions) -> Result<String, LottieError> {
+    if options.locations.len() < 2 {
+        return Err(LottieError::TooFewLocations(options.locations.len()));
+    }
+    let size = options.width_height as f64;
+
+    let mut keyframes: Vec<Vec<Contour>> = Vec::with_capacity(options.locations.len());
+    for (i, location) in options.locations.iter().enumerate() {
+        let document = IconDocument::draw(
+            font,
+            &DrawOptions::new(
+                options.identifier.clone(),
+                options.width_height,
+                *location,
+                PathStyle::Unchanged,
+            )
+            .with_transform(options.transform),
+        )?;
+        // Our paths are Y-down with the em box at (0, -upem), Lottie wants (0, 0) to (size, size)
+        let upem = document.upem as f64;
+        let transform = Affine::scale(size / upem) * Affine::translate((0.0, upem));
+        let mut path = BezPath::new();
+        for layer_path in document.paths() {
+            path.extend(layer_path);
+        }
+        path.apply_affine(transform);
+        let contours = to_contours(&path);
+        if let Some(first) = keyframes.first() {
+            if !compatible(first, &contours) {
+                return Err(LottieError::IncompatibleOutlines(
+                    options.identifier.clone(),
+                    i,
+                ));
+            }
+        }
+        keyframes.push(contours);
+    }
+
+    let name = json::string(&options.identifier.file_stem());
+    let last_frame = options.transition_frames as usize * (keyframes.len() - 1);
+    let size = number(size);
+
+    let shapes: Vec<_> = (0..keyframes[0].len())
+        .map(|contour| {
+            let frames: Vec<_> = keyframes
+                .iter()
+                .enumerate()
+                .map(|(i, contours)| {
+                    let t = i * options.transition_frames as usize;
+                    let shape = contour_json(&contours[contour]);
+                    if i == keyframes.len() - 1 {
+                        format!("{{\"t\":{t},\"s\":[{shape}]}}")
+                    } else {
+                        format!(
+                            "{{\"t\":{t},\"s\":[{shape}],\"o\":{{\"x\":[{}],\"y\":[{}]}},\"i\":{{\"x\":[{}],\"y\":[{}]}}}}",
+                            EASE_OUT.0, EASE_OUT.1, EASE_IN.0, EASE_IN.1
+                        )
+                    }
+                })
+                .collect();
+            format!(
+                "{{\"ty\":\"sh\",\"ind\":{contour},\"ks\":{{\"a\":1,\"k\":[{}]}}}}",
+                frames.join(",")
+            )
+        })
+        .collect();
+
+    let mut json = String::with_capacity(4096);
+    json.push_str(&format!(
+        "{{\"v\":\"5.7.0\",\"fr\":{},\"ip\":0,\"op\":{last_frame},\"w\":{size},\"h\":{size},\"nm\":{name},\"ddd\":0,\"assets\":[],",
+        number(options.frame_rate as f64)
+    ));
+    json.push_str(&format!(
+        "\"layers\":[{{\"ddd\":0,\"ind\":1,\"ty\":4,\"nm\":{name},\"sr\":1,\"ao\":0,\"ip\":0,\"op\":{last_frame},\"st\":0,\"bm\":0,"
+    ));
+    json.push_str(
+        "\"ks\":{\"o\":{\"a\":0,\"k\":100},\"r\":{\"a\":0,\"k\":0},\"p\":{\"a\":0,\"k\":[0,0,0]},\"a\":{\"a\":0,\"k\":[0,0,0]},\"s\":{\"a\":0,\"k\":[100,100,100]}},",
+    );
+    json.push_str(&format!(
+        "\"shapes\":[{{\"ty\":\"gr\",\"nm\":{name},\"it\":["
+    ));
+    for shape in shapes {
+        json.push_str(&shape);
+        json.push(',');
+    }
+    // Font outlines are nonzero winding
+    json.push_str(
+        "{\"ty\":\"fl\",\"c\":{\"a\":0,\"k\":[0,0,0,1]},\"o\":{\"a\":0,\"k\":100},\"r\":1},",
+    );
+    json.push_str("{\"ty\":\"tr\",\"p\":{\"a\":0,\"k\":[0,0]},\"a\":{\"a\":0,\"k\":[0,0]},\"s\":{\"a\":0,\"k\":[100,100]},\"r\":{\"a\":0,\"k\":0},\"o\":{\"a\":0,\"k\":100}}");
+    json.push_str("]}]}]}");
+    Ok(json)
+}
+
+/// Splits a path into contours of cubic segments.
+///
+/// Vertices are kept exactly as drawn, e.g. a contour that ends with a curve back to its start
+/// keeps the duplicate vertex, so that the structure never depends on coordinates.
+fn to_contours(path: &BezPath) -> Vec<Contour> {
+    let mut contours: Vec<Contour> = Vec::new();
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => contours.push(Contour::new(p)),
+            PathEl::LineTo(p) => {
+                let contour = contours.last_mut().unwrap();
+                let start = contour.last();
+                contour.push(start, p, p);
+            }
+            PathEl::QuadTo(c, p) => {
+                let contour = contours.last_mut().unwrap();
+                let start = contour.last();
+                contour.push(
+                    start + (c - start) * (2.0 / 3.0),
+                    p + (c - p) * (2.0 / 3.0),
+                    p,
+                );
+            }
+            PathEl::CurveTo(c0, c1, p) => contours.last_mut().unwrap().push(c0, c1, p),
+            PathEl::ClosePath => contours.last_mut().unwrap().closed = true,
+        }
+    }
+    contours
+}
+
+fn compatible(a: &[Contour], b: &[Contour]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.closed == b.closed && a.vertices.len() == b.vertices.len())
+}
+
+fn contour_json(contour: &Contour) -> String {
+    fn points(values: impl Iterator<Item = (f64, f64)>) -> String {
+        values
+            .map(|(x, y)| format!("[{},{}]", number(x), number(y)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+    format!(
+        "{{\"c\":{},\"v\":[{}],\"i\":[{}],\"o\":[{}]}}",
+        contour.closed,
+        points(contour.vertices.iter().map(|p| (p.x, p.y))),
+        points(contour.in_tangents.iter().map(|v| (v.x, v.y))),
+        points(contour.out_tangents.iter().map(|v| (v.x, v.y))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{BezPath, Point, Vec2};
+    use skrifa::{instance::Location, FontRef, MetadataProvider};
+
+    use crate::{
+        error::LottieError,
+        icon2lottie::{compatible, draw_lottie, to_contours, Contour, LottieOptions},
+        iconid, testdata,
+    };
+
+    #[test]
+    fn animate_weight() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let thin = font.axes().location(&[("wght", 100.0)]);
+        let bold = font.axes().location(&[("wght", 700.0)]);
+        let options = LottieOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            vec![(&thin).into(), (&bold).into()],
+        )
+        .with_transition_frames(12);
+
+        let lottie: serde_json::Value =
+            serde_json::from_str(&draw_lottie(&font, &options).unwrap()).unwrap();
+
+        assert_eq!(12, lottie["op"]);
+        assert_eq!(24, lottie["w"]);
+        let group = &lottie["layers"][0]["shapes"][0]["it"];
+        let shape = &group[0]["ks"];
+        assert_eq!(1, shape["a"]);
+        assert_eq!(2, shape["k"].as_array().unwrap().len());
+        assert_eq!(12, shape["k"][1]["t"]);
+        // the fill and transform follow the shapes
+        let items = group.as_array().unwrap();
+        assert_eq!("fl", items[items.len() - 2]["ty"]);
+        assert_eq!("tr", items[items.len() - 1]["ty"]);
+    }
+
+    // mail swaps to a different glyph when filled so can't morph
+    #[test]
+    fn animate_substituted_glyph_fails() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let unfilled = font.axes().location(&[("FILL", 0.0)]);
+        let filled = font.axes().location(&[("FILL", 1.0)]);
+        let options = LottieOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            vec![(&unfilled).into(), (&filled).into()],
+        );
+
+        assert!(matches!(
+            draw_lottie(&font, &options),
+            Err(LottieError::IncompatibleOutlines(_, 1))
+        ));
+    }
+
+    #[test]
+    fn animate_requires_two_locations() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = Location::default();
+        let options = LottieOptions::new(iconid::MAIL.clone(), 24.0, vec![(&loc).into()]);
+
+        assert!(matches!(
+            draw_lottie(&font, &options),
+            Err(LottieError::TooFewLocations(1))
+        ));
+    }
+
+    #[test]
+    fn quads_become_cubics() {
+        let path = BezPath::from_svg("M0,0 L3,0 Q3,3 0,3 Z").unwrap();
+        assert_eq!(
+            vec![Contour {
+                closed: true,
+                vertices: vec![(0.0, 0.0).into(), (3.0, 0.0).into(), (0.0, 3.0).into()],
+                in_tangents: vec![Vec2::ZERO, Vec2::ZERO, Vec2::new(2.0, 0.0)],
+                out_tangents: vec![Vec2::ZERO, Vec2::new(0.0, 2.0), Vec2::ZERO],
+            }],
+            to_contours(&path)
+        );
+    }
+
+    #[test]
+    fn incompatible_contours() {
+        let triangle = to_contours(&BezPath::from_svg("M0,0 L3,0 L0,3 Z").unwrap());
+        let square = to_contours(&BezPath::from_svg("M0,0 L3,0 L3,3 L0,3 Z").unwrap());
+        let curved = to_contours(&BezPath::from_svg("M0,0 L3,0 Q3,3 0,3 Z").unwrap());
+        assert!(!compatible(&triangle, &square));
+        assert!(compatible(&triangle, &curved));
+        assert_eq!(Point::new(0.0, 3.0), curved[0].vertices[2]);
+    }
+}
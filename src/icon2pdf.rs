@@ -0,0 +1,310 @@
+//! Produces single page PDFs of icons, e.g. for iOS asset catalogs
+
+use kurbo::{Affine, BezPath, PathEl, Point};
+use skrifa::FontRef;
+
+use crate::{
+    document::{title, IconDocument},
+    error::DrawSvgError,
+    icon2eps::{coords, layer_colors, number},
+    icon2svg::DrawOptions,
+    iconid::IconIdentifier,
+};
+
+/// Draws an icon as a PDF whose one page is `width_height` points square, or the shape of the
+/// view box if [DrawOptions::with_framing] says otherwise.
+///
+/// Outlines stay vector. The path style in `options` is ignored, PDF has no compact form.
+///
+/// With [DrawOptions::with_color] each layer is filled in its color, a gradient in its first
+/// stop's color, without transparency or blending. The foreground color is black,
+/// [DrawOptions::with_fill] is ignored.
+pub fn draw_icon(font: &FontRef, options: &DrawOptions<'_>) -> Result<Vec<u8>, DrawSvgError> {
+    Ok(write_pdf(
+        &IconDocument::draw(font, options)?,
+        &options.identifier,
+    ))
+}
+
+/// Serializes a document, each layer is filled in turn
+pub(crate) fn write_pdf(document: &IconDocument, identifier: &IconIdentifier) -> Vec<u8> {
+    // As for EPS, PDF is Y-up with the origin at bottom left of the page
+    let scale = document.width_height as f64 / document.upem as f64;
+    let view_box = document.view_box;
+    let transform =
+        Affine::scale_non_uniform(scale, -scale) * Affine::translate((-view_box.x0, -view_box.y1));
+    let mut content = String::with_capacity(1024);
+    let colors = layer_colors(document);
+    for (i, mut path) in document.paths().enumerate() {
+        if let Some(colors) = &colors {
+            content.push_str(&format!("{} rg\n", colors[i]));
+        }
+        path.apply_affine(transform);
+        write_path(&mut content, &path);
+        // f is nonzero winding, matching font outlines
+        content.push_str("f\n");
+    }
+
+    let (width, height) = document.output_size();
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << >> /Contents 4 0 R >>",
+            number(width as f64),
+            number(height as f64)
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ),
+        format!(
+            "<< /Title {} /Producer (sleipnir) >>",
+            string(&title(identifier))
+        ),
+    ];
+
+    let mut pdf = String::with_capacity(content.len() + 1024);
+    pdf.push_str("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{object}\nendobj\n", i + 1));
+    }
+    let xref = pdf.len();
+    // Entries are exactly 20 bytes, hence the space before each newline
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf.into_bytes()
+}
+
+fn write_path(pdf: &mut String, path: &BezPath) {
+    let mut current = Point::ZERO;
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                pdf.push_str(&format!("{} m\n", coords(&[p])));
+                current = p;
+            }
+            PathEl::LineTo(p) => {
+                pdf.push_str(&format!("{} l\n", coords(&[p])));
+                current = p;
+            }
+            PathEl::QuadTo(c, p) => {
+                // PDF only has cubics
+                let c0 = current + (c - current) * (2.0 / 3.0);
+                let c1 = p + (c - p) * (2.0 / 3.0);
+                pdf.push_str(&format!("{} c\n", coords(&[c0, c1, p])));
+                current = p;
+            }
+            PathEl::CurveTo(c0, c1, p) => {
+                pdf.push_str(&format!("{} c\n", coords(&[c0, c1, p])));
+                current = p;
+            }
+            PathEl::ClosePath => pdf.push_str("h\n"),
+        }
+    }
+}
+
+/// A PDF text string: a literal string if `value` is ASCII, else a UTF-16BE hex string with a
+/// byte order mark as PDF readers only decode text strings as PDFDocEncoding or UTF-16
+fn string(value: &str) -> String {
+    if !value.is_ascii() {
+        let mut string = String::from("<FEFF");
+        for unit in value.encode_utf16() {
+            string.push_str(&format!("{unit:04X}"));
+        }
+        string.push('>');
+        return string;
+    }
+    let mut string = String::with_capacity(value.len() + 2);
+    string.push('(');
+    for byte in value.bytes() {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                string.push('\\');
+                string.push(byte as char);
+            }
+            b' '..=b'~' => string.push(byte as char),
+            _ => string.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    string.push(')');
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{instance::Location, FontRef, MetadataProvider};
+
+    use crate::{
+        icon2pdf::{draw_icon, string},
+        icon2svg::{
+            tests::{colr_v0, font_with_colr},
+            DrawOptions, Fallback, Framing,
+        },
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn draw(options: &DrawOptions, font: &[u8]) -> String {
+        let font = FontRef::new(font).unwrap();
+        String::from_utf8(draw_icon(&font, options).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn draw_mail_pdf() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let pdf = draw(&options, testdata::ICON_FONT);
+
+        assert!(pdf.starts_with("%PDF-1.4\n1 0 obj\n"), "{pdf}");
+        assert!(pdf.contains("/MediaBox [0 0 24 24]"), "{pdf}");
+        assert!(pdf.contains("/Title (U+E158)"), "{pdf}");
+        assert!(pdf.contains("h\nf\nendstream\n"), "{pdf}");
+        assert!(pdf.ends_with("%%EOF\n"), "{pdf}");
+        assert_eq!(pdf.matches(" m\n").count(), pdf.matches("h\n").count());
+    }
+
+    #[test]
+    fn xref_points_at_objects() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::LAN.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let pdf = draw(&options, testdata::ICON_FONT);
+
+        let startxref: usize = pdf
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n0 6\n"));
+        let entries: Vec<usize> = pdf[startxref..]
+            .lines()
+            .skip(3)
+            .take(5)
+            .map(|entry| entry[..10].parse().unwrap())
+            .collect();
+        for (i, offset) in entries.into_iter().enumerate() {
+            assert!(
+                pdf[offset..].starts_with(&format!("{} 0 obj\n", i + 1)),
+                "object {}",
+                i + 1
+            );
+        }
+        let length: usize = pdf
+            .split("/Length ")
+            .nth(1)
+            .and_then(|tail| tail.split(' ').next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        let stream = pdf.find("stream\n").unwrap() + "stream\n".len();
+        assert!(pdf[stream + length..].starts_with("endstream"));
+    }
+
+    #[test]
+    fn frame_on_metrics() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_framing(Framing::Metrics);
+
+        let pdf = draw(&options, testdata::ICON_FONT);
+
+        assert!(pdf.contains("/MediaBox [0 0 24 28.8]"), "{pdf}");
+    }
+
+    #[test]
+    fn draw_quads_as_curves() {
+        let loc = Location::default();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x2e),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let pdf = draw(&options, testdata::MOSTLY_OFF_CURVE_FONT);
+
+        assert!(pdf.contains(" c\n"));
+    }
+
+    #[test]
+    fn escape_strings() {
+        assert_eq!("(a\\(b\\)\\\\ \\012)", string("a(b)\\ \n"));
+    }
+
+    #[test]
+    fn non_ascii_strings_are_utf16() {
+        assert_eq!("<FEFF00E9D83DDCE7>", string("é📧"));
+    }
+
+    #[test]
+    fn non_ascii_title() {
+        let options = DrawOptions::new(
+            IconIdentifier::Name("café".into()),
+            24.0,
+            Default::default(),
+            PathStyle::Unchanged,
+        )
+        .with_fallback(Fallback::Notdef);
+
+        let pdf = draw(&options, testdata::ICON_FONT);
+
+        assert!(pdf.contains("/Title <FEFF00630061006600E9>"), "{pdf}");
+    }
+
+    #[test]
+    fn color_layers() {
+        let font_data = font_with_colr(colr_v0());
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Unchanged,
+        );
+
+        let mono = draw(&options, &font_data);
+        let color = draw(&options.with_color(true), &font_data);
+
+        assert!(!mono.contains(" rg\n"), "{mono}");
+        assert_eq!(
+            vec!["1 0 0", "0 0 1", "0 0 0"],
+            color
+                .lines()
+                .filter_map(|line| line.strip_suffix(" rg"))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,73 +1,422 @@
 //! Produces svgs of icons in Google-style icon fonts
 
-use crate::{error::DrawSvgError, iconid::IconIdentifier, pathstyle::PathStyle, pens::SvgPathPen};
-use skrifa::{
-    instance::{LocationRef, Size},
-    outline::DrawSettings,
-    raw::{tables::glyf::ToPathStyle, TableProvider},
-    FontRef, MetadataProvider,
+use crate::{
+    document::{title, Color, Group, IconDocument, Layer, Paint},
+    error::DrawSvgError,
+    iconid::{IconIdentifier, IconIndex},
+    pathstyle::PathStyle,
+    xml::{Namespace, XmlElement},
 };
+use kurbo::{Affine, BezPath, Rect};
+use skrifa::{color::Extend, instance::LocationRef, setting::VariationSetting, FontRef, Tag};
 
 pub fn draw_icon(font: &FontRef, options: &DrawOptions<'_>) -> Result<String, DrawSvgError> {
-    let upem = font
-        .head()
-        .map_err(|e| DrawSvgError::ReadError("head", e))?
-        .units_per_em();
-    let gid = options
-        .identifier
-        .resolve(font, &options.location)
-        .map_err(|e| DrawSvgError::ResolutionError(options.identifier.clone(), e))?;
-
-    let glyph = font
-        .outline_glyphs()
-        .get(gid)
-        .ok_or(DrawSvgError::NoOutline(options.identifier.clone(), gid))?;
-
-    // Draw the glyph. Fonts are Y-up, svg Y-down so flip-y.
-    let mut svg_path_pen = SvgPathPen::new();
-
-    glyph
-        .draw(
-            DrawSettings::unhinted(Size::unscaled(), options.location)
-                .with_path_style(ToPathStyle::HarfBuzz),
-            &mut svg_path_pen,
-        )
-        .map_err(|e| DrawSvgError::DrawError(options.identifier.clone(), gid, e))?;
-
-    let upem_str = upem.to_string();
-    let width_height = options.width_height.to_string();
-    let mut svg = String::with_capacity(1024);
-    // svg preamble
-    // This viewBox matches existing code we are moving to Rust
-    svg.push_str("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 -");
-    svg.push_str(&upem_str);
-    svg.push(' ');
-    svg.push_str(&upem_str);
-    svg.push(' ');
-    svg.push_str(upem_str.as_str());
-    svg.push_str("\" height=\"");
-    svg.push_str(&width_height);
-    svg.push_str("\" width=\"");
-    svg.push_str(&width_height);
-    svg.push_str("\">");
-
-    // the actual path
-    svg.push_str("<path d=\"");
-    svg.push_str(&options.style.write_svg_path(&svg_path_pen.into_inner()));
-    //svg.push_str(&path_pen.into_inner().to_svg());
-    svg.push_str("\"/>");
-
-    // svg ending
-    svg.push_str("</svg>");
-
-    Ok(svg)
+    Ok(write_svg(&IconDocument::draw(font, options)?, options))
 }
 
+/// As [draw_icon] but also returns the error a [Fallback] was drawn in place of, if any
+pub fn draw_icon_with_warning(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+) -> Result<(String, Option<DrawSvgError>), DrawSvgError> {
+    let mut document = IconDocument::draw(font, options)?;
+    let warning = document.fallback_for.take();
+    Ok((write_svg(&document, options), warning))
+}
+
+/// Draws the outline of an icon for rendering by other means, e.g. lyon or vello, with the
+/// viewBox the svg would frame it in.
+///
+/// The path is in font units with Y down, as written into an svg. The layers of a color icon are
+/// joined into one path and their paint dropped.
+pub fn draw_icon_path(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+) -> Result<(BezPath, Rect), DrawSvgError> {
+    let document = IconDocument::draw(font, options)?;
+    let mut path = BezPath::new();
+    for layer in document.paths() {
+        path.extend(layer.iter());
+    }
+    Ok((path, document.view_box))
+}
+
+/// Serializes a document, each layer becomes a path
+pub(crate) fn write_svg(document: &IconDocument, options: &DrawOptions<'_>) -> String {
+    let hints = &options.hints;
+    let view_box = document.view_box;
+    let (width, height) = document.output_size();
+    // The default em box viewBox matches existing code we are moving to Rust
+    let mut svg = XmlElement::new("svg")
+        .with_namespace(&Namespace::SVG)
+        .with_attribute(
+            "viewBox",
+            format!(
+                "{} {} {} {}",
+                view_box.x0,
+                view_box.y0,
+                view_box.width(),
+                view_box.height()
+            ),
+        )
+        .with_attribute("height", height)
+        .with_attribute("width", width);
+    if let Some(preserve_aspect_ratio) = &hints.preserve_aspect_ratio {
+        svg.set_attribute("preserveAspectRatio", preserve_aspect_ratio);
+    }
+    if let Some(shape_rendering) = hints.shape_rendering {
+        svg.set_attribute("shape-rendering", shape_rendering.as_str());
+    }
+    if let Some(accessibility) = &options.accessibility {
+        let label = accessibility
+            .title
+            .clone()
+            .or_else(|| document.name.as_ref().map(|name| name.replace('_', " ")))
+            .unwrap_or_else(|| title(&options.identifier));
+        svg.set_attribute("role", "img");
+        svg.set_attribute("aria-label", &label);
+        svg.add_child(XmlElement::new("title").with_text(label));
+        if let Some(description) = &accessibility.description {
+            svg.add_child(XmlElement::new("desc").with_text(description));
+        }
+    }
+    write_svg_content(&mut svg, document, options, "");
+    svg.to_string()
+}
+
+/// Adds the gradients and paths of a document to `parent`, gradient ids are `id_prefix` then `g`
+/// and the layer index so documents sharing a file can be told apart
+pub(crate) fn write_svg_content(
+    parent: &mut XmlElement,
+    document: &IconDocument,
+    options: &DrawOptions<'_>,
+    id_prefix: &str,
+) {
+    let hints = &options.hints;
+    let foreground = options.fill.attribute();
+    // gradients are referenced by layer index
+    let gradients: Vec<_> = document
+        .layers
+        .iter()
+        .enumerate()
+        .filter(|(_, layer)| {
+            options.stroke_width.is_none() && !matches!(layer.paint, Paint::Solid(..))
+        })
+        .collect();
+    if !gradients.is_empty() {
+        let mut defs = XmlElement::new("defs");
+        for (i, layer) in gradients {
+            defs.add_child(gradient(
+                &format!("{id_prefix}g{i}"),
+                layer,
+                foreground.as_deref(),
+            ));
+        }
+        parent.add_child(defs);
+    }
+
+    // the actual paths, in groups for blending
+    let mut open_groups: &[Group] = &[];
+    let mut open_elements: Vec<XmlElement> = Vec::new();
+    for (i, (path, layer)) in document.paths().zip(document.layers.iter()).enumerate() {
+        let shared = open_groups
+            .iter()
+            .zip(layer.groups.iter())
+            .take_while(|(open, group)| open == group)
+            .count();
+        for _ in shared..open_groups.len() {
+            close_group(parent, &mut open_elements);
+        }
+        for group in &layer.groups[shared..] {
+            open_elements.push(
+                XmlElement::new("g")
+                    .with_attribute("style", format!("mix-blend-mode:{}", group.blend)),
+            );
+        }
+        open_groups = &layer.groups;
+
+        let mut element =
+            XmlElement::new("path").with_attribute("d", options.style.write_svg_path(&path));
+        match (options.stroke_width, &layer.paint) {
+            (Some(width), _) => {
+                let width = if hints.non_scaling_stroke {
+                    width
+                } else {
+                    width * document.upem as f32 / document.width_height
+                };
+                let color = match options.stroke_color {
+                    Some(rgb) => rgb_string(rgb),
+                    None => "currentColor".to_string(),
+                };
+                element.set_attribute("fill", "none");
+                element.set_attribute("stroke", color);
+                element.set_attribute("stroke-width", number(width as f64));
+            }
+            (None, Paint::Solid(color)) => write_color(
+                &mut element,
+                ("fill", "fill-opacity"),
+                color,
+                foreground.as_deref(),
+            ),
+            (None, _) => element.set_attribute("fill", format!("url(#{id_prefix}g{i})")),
+        }
+        if hints.non_scaling_stroke {
+            element.set_attribute("vector-effect", "non-scaling-stroke");
+        }
+        match open_elements.last_mut() {
+            Some(group) => group.add_child(element),
+            None => parent.add_child(element),
+        }
+    }
+    while !open_elements.is_empty() {
+        close_group(parent, &mut open_elements);
+    }
+}
+
+/// Moves the innermost open group into its parent
+fn close_group(root: &mut XmlElement, open: &mut Vec<XmlElement>) {
+    let Some(group) = open.pop() else {
+        return;
+    };
+    match open.last_mut() {
+        Some(parent) => parent.add_child(group),
+        None => root.add_child(group),
+    }
+}
+
+/// Sets the color and opacity attributes, the foreground color is `foreground` if there is one
+fn write_color(
+    element: &mut XmlElement,
+    (color_attr, opacity_attr): (&str, &str),
+    color: &Color,
+    foreground: Option<&str>,
+) {
+    match (color.rgb, foreground) {
+        (Some(rgb), _) => element.set_attribute(color_attr, rgb_string(rgb)),
+        (None, Some(foreground)) => element.set_attribute(color_attr, foreground),
+        (None, None) => (),
+    }
+    if color.alpha < 1.0 {
+        element.set_attribute(opacity_attr, number(color.alpha as f64));
+    }
+}
+
+fn gradient(id: &str, layer: &Layer, foreground: Option<&str>) -> XmlElement {
+    // Gradients are in user space, which has the layer transform applied to the path
+    let t = layer.transform;
+    let scale = t.determinant().abs().sqrt();
+    let (mut element, stops, extend) = match &layer.paint {
+        Paint::Solid(..) => unreachable!("solid paints are written as fill colors"),
+        Paint::LinearGradient {
+            p0,
+            p1,
+            stops,
+            extend,
+        } => {
+            let (p0, p1) = (t * *p0, t * *p1);
+            let element = XmlElement::new("linearGradient")
+                .with_attribute("id", id)
+                .with_attribute("gradientUnits", "userSpaceOnUse")
+                .with_attribute("x1", number(p0.x))
+                .with_attribute("y1", number(p0.y))
+                .with_attribute("x2", number(p1.x))
+                .with_attribute("y2", number(p1.y));
+            (element, stops, extend)
+        }
+        Paint::RadialGradient {
+            c0,
+            r0,
+            c1,
+            r1,
+            stops,
+            extend,
+        } => {
+            // svg's focal circle is c0/r0, the end circle c1/r1
+            let (c0, c1) = (t * *c0, t * *c1);
+            let element = XmlElement::new("radialGradient")
+                .with_attribute("id", id)
+                .with_attribute("gradientUnits", "userSpaceOnUse")
+                .with_attribute("fx", number(c0.x))
+                .with_attribute("fy", number(c0.y))
+                .with_attribute("fr", number(r0 * scale))
+                .with_attribute("cx", number(c1.x))
+                .with_attribute("cy", number(c1.y))
+                .with_attribute("r", number(r1 * scale));
+            (element, stops, extend)
+        }
+    };
+    match extend {
+        Extend::Repeat => element.set_attribute("spreadMethod", "repeat"),
+        Extend::Reflect => element.set_attribute("spreadMethod", "reflect"),
+        // pad is the default
+        _ => (),
+    }
+    for (offset, color) in stops {
+        let mut stop = XmlElement::new("stop").with_attribute("offset", number(*offset as f64));
+        // Stops are black by default, not the foreground
+        write_color(
+            &mut stop,
+            ("stop-color", "stop-opacity"),
+            color,
+            Some(foreground.unwrap_or("currentColor")),
+        );
+        element.add_child(stop);
+    }
+    element
+}
+
+fn rgb_string([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(crate) fn number(value: f64) -> String {
+    // Adding 0.0 turns -0.0 into 0.0
+    format!("{}", (value * 100.0).round() / 100.0 + 0.0)
+}
+
+/// Values of the svg `shape-rendering` attribute
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum ShapeRendering {
+    Auto,
+    OptimizeSpeed,
+    CrispEdges,
+    GeometricPrecision,
+}
+
+impl ShapeRendering {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ShapeRendering::Auto => "auto",
+            ShapeRendering::OptimizeSpeed => "optimizeSpeed",
+            ShapeRendering::CrispEdges => "crispEdges",
+            ShapeRendering::GeometricPrecision => "geometricPrecision",
+        }
+    }
+}
+
+/// Optional rendering hints written into svg output, by default none are
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RenderHints {
+    /// `shape-rendering` on the root element
+    pub shape_rendering: Option<ShapeRendering>,
+    /// `vector-effect="non-scaling-stroke"` on each path, for renderers that stroke icons
+    pub non_scaling_stroke: bool,
+    /// `preserveAspectRatio` on the root element, e.g. `xMidYMid meet`
+    pub preserve_aspect_ratio: Option<String>,
+}
+
+/// How svg output fills with the foreground color, see [DrawOptions::with_fill]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum Fill {
+    /// No fill attribute, so black unless css says otherwise
+    #[default]
+    Unset,
+    /// `fill="currentColor"`, the color of the surrounding text
+    CurrentColor,
+    /// `fill="var(--name, #rrggbb)"`, a css custom property with a fallback, e.g. `icon-color`
+    CustomProperty { name: String, fallback: [u8; 3] },
+    /// A literal `fill="#rrggbb"`
+    Rgb([u8; 3]),
+}
+
+impl Fill {
+    /// The value of the fill attribute, if there is one
+    fn attribute(&self) -> Option<String> {
+        match self {
+            Fill::Unset => None,
+            Fill::CurrentColor => Some("currentColor".to_string()),
+            Fill::CustomProperty { name, fallback } => {
+                Some(format!("var(--{name}, {})", rgb_string(*fallback)))
+            }
+            Fill::Rgb(rgb) => Some(rgb_string(*rgb)),
+        }
+    }
+}
+
+/// Accessibility metadata written into svg output, see [DrawOptions::with_accessibility]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Accessibility {
+    /// The `<title>` and `aria-label`, by default the icon's name with spaces for underscores
+    pub title: Option<String>,
+    /// A `<desc>`, none by default
+    pub description: Option<String>,
+}
+
+/// What to draw when an icon can't be resolved or has no outline
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum Fallback {
+    /// Fail with the error
+    #[default]
+    Error,
+    /// Draw the font's .notdef glyph, failing with the original error if it has no outline
+    Notdef,
+    /// Draw this path, in font units with Y down like the svg viewBox
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::bez_path"))]
+    Path(BezPath),
+}
+
+/// The area of the font's coordinate space that is output
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum Framing {
+    /// A square em, every icon is the same size
+    #[default]
+    EmBox,
+    /// The glyph's advance by the font's ascent to descent, as the icon would sit in text
+    Metrics,
+}
+
+#[derive(Clone)]
 pub struct DrawOptions<'a> {
-    identifier: IconIdentifier,
-    width_height: f32,
-    location: LocationRef<'a>,
-    style: PathStyle,
+    pub(crate) identifier: IconIdentifier,
+    pub(crate) width_height: f32,
+    pub(crate) location: LocationRef<'a>,
+    pub(crate) style: PathStyle,
+    pub(crate) transform: Affine,
+    pub(crate) fallback: Fallback,
+    pub(crate) framing: Framing,
+    pub(crate) canonical: bool,
+    pub(crate) hints: RenderHints,
+    pub(crate) optical_size: bool,
+    pub(crate) features: Vec<Tag>,
+    pub(crate) color: bool,
+    pub(crate) stroke_width: Option<f32>,
+    pub(crate) stroke_color: Option<[u8; 3]>,
+    pub(crate) auto_mirror: Option<bool>,
+    pub(crate) user_coords: Vec<VariationSetting>,
+    pub(crate) remove_overlaps: bool,
+    pub(crate) accessibility: Option<Accessibility>,
+    pub(crate) fill: Fill,
+    pub(crate) icon_index: Option<&'a IconIndex>,
 }
 
 impl<'a> DrawOptions<'a> {
@@ -82,20 +431,186 @@ impl<'a> DrawOptions<'a> {
             width_height,
             location,
             style,
+            transform: Affine::IDENTITY,
+            fallback: Fallback::Error,
+            framing: Framing::EmBox,
+            canonical: false,
+            hints: RenderHints::default(),
+            optical_size: false,
+            features: Vec::new(),
+            color: false,
+            stroke_width: None,
+            stroke_color: None,
+            auto_mirror: None,
+            user_coords: Vec::new(),
+            remove_overlaps: false,
+            accessibility: None,
+            fill: Fill::Unset,
+            icon_index: None,
         }
     }
+
+    /// Applies `transform` to the outline before it is written, in any format.
+    ///
+    /// The transform is in font units with Y down, the coordinate space of the svg viewBox, so
+    /// `Affine::translate((0.0, -20.0))` nudges an icon up by 20 units.
+    pub fn with_transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Draws `fallback` instead of failing when the icon is missing, see [draw_icon_with_warning]
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Frames the output on font metrics rather than the em box, width is then scaled to match
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Produces byte-identical output for equivalent outlines, e.g. to avoid churn when
+    /// re-exporting into version control.
+    ///
+    /// Coordinates are rounded to hundredths of a unit and subpaths are sorted by position, so
+    /// paths no longer follow the font's drawing order.
+    pub fn with_canonical_output(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Adds rendering hint attributes to svg output, other formats ignore them
+    pub fn with_render_hints(mut self, hints: RenderHints) -> Self {
+        self.hints = hints;
+        self
+    }
+
+    /// Sets opsz to `width_height`, clamped to the axis range, as platforms apply optical sizing.
+    ///
+    /// Only applies when the font has an opsz axis that the caller doesn't pin. opsz set by
+    /// [DrawOptions::with_user_coords] is drawn as is, even at its default. A location can't
+    /// tell opsz left at the default from opsz pinned there, so only a location that pins it
+    /// elsewhere is drawn as is.
+    pub fn with_automatic_optical_size(mut self, optical_size: bool) -> Self {
+        self.optical_size = optical_size;
+        self
+    }
+
+    /// Applies the single substitutions of `feature`, e.g. `ss01`, when resolving the icon.
+    ///
+    /// Use to draw alternates, such as the sharp or rounded form of an icon, see
+    /// [IconIdentifier::resolve_with_features].
+    pub fn with_feature(mut self, feature: Tag) -> Self {
+        self.features.push(feature);
+        self
+    }
+
+    /// Draws COLR v0 and v1 color glyphs in their colors, from the first palette, rather than
+    /// as a single outline.
+    ///
+    /// Solid fills and linear and radial gradients are supported, sweep gradients are drawn in
+    /// their first color. Each fill is clipped to the innermost glyph clip only. Composite
+    /// modes become groups with a css mix-blend-mode, Porter-Duff modes other than source over
+    /// and plus are drawn as source over. VectorDrawable output draws colors without blending,
+    /// pdf and eps fill each layer in a single color.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Outlines the icon with a stroke `width` wide, in the same units as `width_height`, rather
+    /// than filling it. Only svg and VectorDrawable output stroke.
+    ///
+    /// With [RenderHints::non_scaling_stroke] the width is in screen pixels instead.
+    pub fn with_stroke_width(mut self, width: f32) -> Self {
+        self.stroke_width = Some(width);
+        self
+    }
+
+    /// The stroke color, `currentColor` by default, see [DrawOptions::with_stroke_width]
+    pub fn with_stroke_color(mut self, rgb: [u8; 3]) -> Self {
+        self.stroke_color = Some(rgb);
+        self
+    }
+
+    /// Whether VectorDrawable output is `autoMirrored`, flipped in right-to-left layouts.
+    ///
+    /// By default directional icons, such as `arrow_back` or `send`, are detected by name.
+    pub fn with_auto_mirror(mut self, auto_mirror: bool) -> Self {
+        self.auto_mirror = Some(auto_mirror);
+        self
+    }
+
+    /// Sets axes by user coordinates, e.g. `[("wght", 700.0), ("FILL", 1.0)]`, on top of the
+    /// location.
+    ///
+    /// Coordinates are normalized, including any avar mapping, against the font drawn from.
+    /// Axes not listed keep their value from the location, axes the font lacks are ignored.
+    pub fn with_user_coords<I>(mut self, coords: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<VariationSetting>,
+    {
+        self.user_coords.extend(coords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Whether to merge overlapping contours, off by default.
+    ///
+    /// Filled icons often overlap contours, which is fine under nonzero filling but leaves
+    /// holes where a consumer forces even-odd. Merged contours are flattened to lines.
+    pub fn with_remove_overlaps(mut self, remove_overlaps: bool) -> Self {
+        self.remove_overlaps = remove_overlaps;
+        self
+    }
+
+    /// Makes svg output accessible, adding `role="img"`, an `aria-label` and a `<title>`, and a
+    /// `<desc>` if there's a description. Other formats ignore it.
+    ///
+    /// Icons drawn by codepoint or glyph id are labelled by the name the font gives them, if any.
+    pub fn with_accessibility(mut self, accessibility: Accessibility) -> Self {
+        self.accessibility = Some(accessibility);
+        self
+    }
+
+    /// How svg output writes the foreground color, unset by default. Other formats ignore it.
+    ///
+    /// Applies to the whole icon and to the foreground layers of a color icon, colored layers
+    /// keep their color. Strokes are colored by [DrawOptions::with_stroke_color].
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Looks up the names of icons drawn by codepoint or glyph id in `index`, built from the
+    /// font drawn from, rather than indexing the font on each draw.
+    ///
+    /// Names label accessible svg output and mark directional icons in VectorDrawable output.
+    pub fn with_icon_index(mut self, index: &'a IconIndex) -> Self {
+        self.icon_index = Some(index);
+        self
+    }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::{
-        icon2svg::draw_icon,
+        error::DrawSvgError,
+        icon2svg::{
+            draw_icon, draw_icon_path, draw_icon_with_warning, Accessibility, Fallback, Fill,
+            Framing, RenderHints, ShapeRendering,
+        },
         iconid::{self, IconIdentifier},
         pathstyle::PathStyle,
         testdata,
+        xml::XmlElement,
     };
+    use kurbo::{Affine, BezPath, Rect};
     use regex::Regex;
-    use skrifa::{instance::Location, FontRef, MetadataProvider};
+    use skrifa::{instance::Location, FontRef, GlyphId, MetadataProvider, Tag};
+    use write_fonts::FontBuilder;
 
     use pretty_assertions::assert_eq;
 
@@ -136,6 +651,102 @@ mod tests {
         assert_draw_icon(testdata::MAIL_SVG, iconid::MAIL.clone());
     }
 
+    #[test]
+    fn automatic_optical_size() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font
+            .axes()
+            .location(&[("wght", 700.0), ("GRAD", 200.0), ("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            48.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_automatic_optical_size(true);
+
+        assert_icon_svg_equal(
+            testdata::MAIL_OPSZ48_SVG,
+            &draw_icon(&font, &options).unwrap(),
+        );
+    }
+
+    #[test]
+    fn user_coords() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font
+            .axes()
+            .location(&[("wght", 700.0), ("GRAD", 200.0), ("FILL", 1.0)]);
+        let grad = font.axes().location(&[("GRAD", 200.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            48.0,
+            (&grad).into(),
+            PathStyle::Unchanged,
+        )
+        .with_user_coords([("wght", 700.0), ("FILL", 1.0), ("ZZZZ", 1.0)]);
+
+        assert_eq!(
+            draw_icon(
+                &font,
+                &DrawOptions::new(
+                    iconid::MAIL.clone(),
+                    48.0,
+                    (&loc).into(),
+                    PathStyle::Unchanged
+                )
+            )
+            .unwrap(),
+            draw_icon(&font, &options).unwrap()
+        );
+        assert_icon_svg_equal(
+            testdata::MAIL_OPSZ48_SVG,
+            &draw_icon(&font, &options.with_automatic_optical_size(true)).unwrap(),
+        );
+    }
+
+    #[test]
+    fn pinned_optical_size_wins() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("opsz", 20.0), ("FILL", 1.0)]);
+        let options = |optical_size| {
+            DrawOptions::new(
+                iconid::MAIL.clone(),
+                48.0,
+                (&loc).into(),
+                PathStyle::Compact,
+            )
+            .with_automatic_optical_size(optical_size)
+        };
+
+        assert_eq!(
+            draw_icon(&font, &options(false)).unwrap(),
+            draw_icon(&font, &options(true)).unwrap()
+        );
+    }
+
+    #[test]
+    fn pinned_default_optical_size_wins() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let opsz = font.axes().get_by_tag(Tag::new(b"opsz")).unwrap();
+        assert_ne!(48.0, opsz.default_value());
+        let options = |optical_size| {
+            DrawOptions::new(
+                iconid::MAIL.clone(),
+                48.0,
+                Default::default(),
+                PathStyle::Compact,
+            )
+            .with_user_coords([("opsz", opsz.default_value())])
+            .with_automatic_optical_size(optical_size)
+        };
+
+        assert_eq!(
+            draw_icon(&font, &options(false)).unwrap(),
+            draw_icon(&font, &options(true)).unwrap()
+        );
+    }
+
     #[test]
     fn draw_mail_icon_at_opsz48() {
         let font = FontRef::new(testdata::ICON_FONT).unwrap();
@@ -158,6 +769,318 @@ mod tests {
         );
     }
 
+    #[test]
+    fn draw_with_transform() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let loc = Location::default();
+        let identifier = IconIdentifier::Codepoint(0x2e);
+        let plain = DrawOptions::new(
+            identifier.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+        let nudged = DrawOptions::new(identifier, 24.0, (&loc).into(), PathStyle::Unchanged)
+            .with_transform(Affine::translate((0.0, -20.0)));
+
+        let plain = draw_icon(&font, &plain).unwrap();
+        let nudged = draw_icon(&font, &nudged).unwrap();
+
+        let y = |svg: &str| {
+            let re = Regex::new(r#"d="M[\d.-]+,([\d.-]+)"#).unwrap();
+            re.captures(svg).unwrap()[1].parse::<f64>().unwrap()
+        };
+        assert_eq!(y(&plain) - 20.0, y(&nudged));
+    }
+
+    #[test]
+    fn missing_icon_falls_back_to_notdef() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let missing = IconIdentifier::Name("no_such_icon".into());
+        let options = DrawOptions::new(missing.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+        assert!(draw_icon(&font, &options).is_err());
+
+        let (svg, warning) =
+            draw_icon_with_warning(&font, &options.with_fallback(Fallback::Notdef)).unwrap();
+        let notdef = DrawOptions::new(
+            IconIdentifier::GlyphId(GlyphId::NOTDEF),
+            24.0,
+            (&loc).into(),
+            PathStyle::Compact,
+        );
+
+        assert_eq!(draw_icon(&font, &notdef).unwrap(), svg);
+        assert!(
+            matches!(&warning, Some(DrawSvgError::ResolutionError(id, _)) if *id == missing),
+            "{warning:?}"
+        );
+    }
+
+    #[test]
+    fn missing_icon_falls_back_to_path() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = Location::default();
+        let placeholder = BezPath::from_svg("M0,0 L960,0 L960,-960 Z").unwrap();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x41),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_fallback(Fallback::Path(placeholder));
+
+        let (svg, warning) = draw_icon_with_warning(&font, &options).unwrap();
+
+        assert!(
+            svg.contains("<path d=\"M0,0L960,0L960,-960L0,0Z\"/>"),
+            "{svg}"
+        );
+        assert!(warning.is_some());
+    }
+
+    #[test]
+    fn icon_path() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let (path, view_box) = draw_icon_path(&font, &options).unwrap();
+
+        assert_eq!(Rect::new(0.0, -960.0, 960.0, 0.0), view_box);
+        assert!(
+            draw_icon(&font, &options).unwrap().contains(&format!(
+                "<path d=\"{}\"/>",
+                PathStyle::Unchanged.write_svg_path(&path)
+            )),
+            "{path:?}"
+        );
+    }
+
+    #[test]
+    fn remove_overlaps() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = Location::default();
+        let overlapping = BezPath::from_svg(
+            "M0,-960 L600,-960 L600,-360 L0,-360 Z M360,-600 L960,-600 L960,0 L360,0 Z",
+        )
+        .unwrap();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x41),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_fallback(Fallback::Path(overlapping));
+
+        let svg = draw_icon(&font, &options.clone().with_remove_overlaps(true)).unwrap();
+
+        assert!(
+            svg.contains(
+                "<path d=\"M0,-960L600,-960L600,-600L960,-600L960,0L360,0L360,-360L0,-360L0,-960Z\"/>"
+            ),
+            "{svg}"
+        );
+        assert!(draw_icon(&font, &options)
+            .unwrap()
+            .contains("L0,-360L0,-960ZM360,-600"));
+    }
+
+    #[test]
+    fn frame_on_metrics() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Compact,
+        )
+        .with_framing(Framing::Metrics);
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        // ascent 1056, descent -96
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 -1056 960 1152\" height=\"28.8\" width=\"24\">"
+            ),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn canonical_output_ignores_noise() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = |transform| {
+            DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact)
+                .with_transform(transform)
+                .with_canonical_output(true)
+        };
+
+        assert_eq!(
+            draw_icon(&font, &options(Affine::IDENTITY)).unwrap(),
+            draw_icon(&font, &options(Affine::translate((0.001, -0.001)))).unwrap()
+        );
+    }
+
+    #[test]
+    fn render_hints() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let loc = Location::default();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x2e),
+            24.0,
+            (&loc).into(),
+            PathStyle::Compact,
+        )
+        .with_render_hints(RenderHints {
+            shape_rendering: Some(ShapeRendering::GeometricPrecision),
+            non_scaling_stroke: true,
+            preserve_aspect_ratio: Some("xMidYMid meet".to_string()),
+        });
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 -1024 1024 1024\" height=\"24\" width=\"24\" preserveAspectRatio=\"xMidYMid meet\" shape-rendering=\"geometricPrecision\"><path d=\""
+            ),
+            "{svg}"
+        );
+        assert!(
+            svg.ends_with("\" vector-effect=\"non-scaling-stroke\"/></svg>"),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn escapes_attributes() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_render_hints(RenderHints {
+            preserve_aspect_ratio: Some("\"/><script/>".to_string()),
+            ..Default::default()
+        });
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            svg.contains(" preserveAspectRatio=\"&quot;/&gt;&lt;script/&gt;\">"),
+            "{svg}"
+        );
+        assert!(XmlElement::parse(&svg).is_ok());
+    }
+
+    #[test]
+    fn fill_modes() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let draw = |fill| {
+            let options = DrawOptions::new(
+                iconid::MAIL.clone(),
+                24.0,
+                Default::default(),
+                PathStyle::Compact,
+            )
+            .with_fill(fill);
+            let svg = draw_icon(&font, &options).unwrap();
+            // The attributes after d
+            let path = &svg[svg.find("<path d=\"").unwrap() + 9..];
+            path[path.find('"').unwrap() + 1..path.find("/>").unwrap()].to_string()
+        };
+
+        assert_eq!("", draw(Fill::Unset));
+        assert_eq!(" fill=\"currentColor\"", draw(Fill::CurrentColor));
+        assert_eq!(
+            " fill=\"var(--icon-color, #000000)\"",
+            draw(Fill::CustomProperty {
+                name: "icon-color".to_string(),
+                fallback: [0, 0, 0],
+            })
+        );
+        assert_eq!(" fill=\"#1a73e8\"", draw(Fill::Rgb([0x1a, 0x73, 0xe8])));
+    }
+
+    #[test]
+    fn accessible_by_name() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            IconIdentifier::Name("lan".into()),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_accessibility(Accessibility::default());
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 -960 960 960\" height=\"24\" width=\"24\" role=\"img\" aria-label=\"lan\"><title>lan</title><path d=\""
+            ),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn accessible_by_codepoint_uses_font_name() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_accessibility(Accessibility {
+            title: None,
+            description: Some("Opens your <inbox> & more".to_string()),
+        });
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            svg.contains(
+                " role=\"img\" aria-label=\"mail\"><title>mail</title><desc>Opens your &lt;inbox&gt; &amp; more</desc><path"
+            ),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn accessible_with_title() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            iconid::LAN.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_accessibility(Accessibility {
+            title: Some("Tom & \"Jerry\"".to_string()),
+            description: None,
+        });
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(
+            svg.contains(
+                " role=\"img\" aria-label=\"Tom &amp; &quot;Jerry&quot;\"><title>Tom &amp; \"Jerry\"</title>"
+            ),
+            "{svg}"
+        );
+    }
+
     #[test]
     fn draw_lan_icon() {
         assert_draw_icon(testdata::LAN_SVG, iconid::LAN.clone());
@@ -201,4 +1124,239 @@ mod tests {
     fn draw_info_icon_compact() {
         assert_draw_mat_symbol(testdata::INFO_COMPACT_SVG, "info", PathStyle::Compact);
     }
+
+    #[test]
+    fn stroke() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options =
+            || DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+
+        let filled = draw_icon(&font, &options()).unwrap();
+        let stroked = draw_icon(&font, &options().with_stroke_width(1.5)).unwrap();
+        let colored = draw_icon(
+            &font,
+            &options()
+                .with_stroke_width(2.0)
+                .with_stroke_color([0x12, 0xab, 0xff])
+                .with_render_hints(RenderHints {
+                    non_scaling_stroke: true,
+                    ..Default::default()
+                }),
+        )
+        .unwrap();
+
+        // 1.5px at 24px per 960 unit em is 60 units
+        assert_eq!(
+            filled.replace(
+                "\"/>",
+                "\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"60\"/>"
+            ),
+            stroked
+        );
+        assert!(
+            colored.contains(
+                " fill=\"none\" stroke=\"#12abff\" stroke-width=\"2\" vector-effect=\"non-scaling-stroke\"/>"
+            ),
+            "{colored}"
+        );
+    }
+
+    /// One palette of opaque red then half transparent blue
+    pub(crate) fn cpal() -> Vec<u8> {
+        let mut cpal = Vec::new();
+        // version, numPaletteEntries, numPalettes, numColorRecords
+        for value in [0u16, 2, 1, 2] {
+            cpal.extend(value.to_be_bytes());
+        }
+        cpal.extend(14u32.to_be_bytes()); // colorRecordsArrayOffset
+        cpal.extend(0u16.to_be_bytes()); // colorRecordIndices
+        cpal.extend([0, 0, 255, 255]); // BGRA
+        cpal.extend([255, 0, 0, 128]);
+        cpal
+    }
+
+    /// gid 1, mail, as gid 1 in red, gid 3 in half blue and gid 5 in the foreground color
+    pub(crate) fn colr_v0() -> Vec<u8> {
+        let mut colr = Vec::new();
+        colr.extend(0u16.to_be_bytes()); // version
+        colr.extend(1u16.to_be_bytes()); // numBaseGlyphRecords
+        colr.extend(14u32.to_be_bytes()); // baseGlyphRecordsOffset
+        colr.extend(20u32.to_be_bytes()); // layerRecordsOffset
+        colr.extend(3u16.to_be_bytes()); // numLayerRecords
+        for value in [1u16, 0, 3] {
+            colr.extend(value.to_be_bytes());
+        }
+        for value in [1u16, 0, 3, 1, 5, 0xFFFF] {
+            colr.extend(value.to_be_bytes());
+        }
+        colr
+    }
+
+    /// gid 1, mail, painted by `paint`
+    pub(crate) fn colr_v1(paint: &[u8]) -> Vec<u8> {
+        let mut colr = Vec::new();
+        colr.extend(1u16.to_be_bytes()); // version
+        colr.extend(0u16.to_be_bytes()); // numBaseGlyphRecords
+        colr.extend(0u32.to_be_bytes()); // baseGlyphRecordsOffset
+        colr.extend(0u32.to_be_bytes()); // layerRecordsOffset
+        colr.extend(0u16.to_be_bytes()); // numLayerRecords
+        colr.extend(34u32.to_be_bytes()); // baseGlyphListOffset
+        colr.extend([0u8; 16]); // no layer list, clip list or variations
+                                // BaseGlyphList of one record for gid 1, the paint follows
+        colr.extend(1u32.to_be_bytes());
+        colr.extend(1u16.to_be_bytes());
+        colr.extend(10u32.to_be_bytes());
+        colr.extend(paint);
+        colr
+    }
+
+    /// A PaintGlyph of `gid` with the paint that follows it
+    pub(crate) fn paint_glyph(gid: u16) -> Vec<u8> {
+        let mut paint = vec![10, 0, 0, 6];
+        paint.extend(gid.to_be_bytes());
+        paint
+    }
+
+    /// A PaintSolid of a palette entry at full alpha
+    pub(crate) fn paint_solid(palette_index: u16) -> Vec<u8> {
+        let mut paint = vec![2];
+        paint.extend(palette_index.to_be_bytes());
+        paint.extend(0x4000u16.to_be_bytes());
+        paint
+    }
+
+    pub(crate) fn font_with_colr(colr: Vec<u8>) -> Vec<u8> {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        FontBuilder::new()
+            .add_raw(Tag::new(b"COLR"), colr)
+            .add_raw(Tag::new(b"CPAL"), cpal())
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    fn path_data(font: &FontRef, gid: u16) -> String {
+        let options = DrawOptions::new(
+            IconIdentifier::GlyphId(GlyphId::new(gid)),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        );
+        let svg = draw_icon(font, &options).unwrap();
+        let start = svg.find(" d=\"").unwrap() + 4;
+        svg[start..start + svg[start..].find('"').unwrap()].to_string()
+    }
+
+    #[test]
+    fn colr_v0_layers() {
+        let font_data = font_with_colr(colr_v0());
+        let font = FontRef::new(&font_data).unwrap();
+        let options = |color| {
+            DrawOptions::new(
+                iconid::MAIL.clone(),
+                24.0,
+                Default::default(),
+                PathStyle::Compact,
+            )
+            .with_color(color)
+        };
+
+        let monochrome = draw_icon(&font, &options(false)).unwrap();
+        let color = draw_icon(&font, &options(true)).unwrap();
+
+        assert_eq!(1, monochrome.matches("<path").count());
+        assert_eq!(
+            format!(
+                "<path d=\"{}\" fill=\"#ff0000\"/><path d=\"{}\" fill=\"#0000ff\" fill-opacity=\"0.5\"/><path d=\"{}\"/></svg>",
+                path_data(&font, 1),
+                path_data(&font, 3),
+                path_data(&font, 5)
+            ),
+            color[color.find("<path").unwrap()..]
+        );
+    }
+
+    #[test]
+    fn fill_colors_foreground_layers() {
+        let font_data = font_with_colr(colr_v0());
+        let font = FontRef::new(&font_data).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_color(true)
+        .with_fill(Fill::CurrentColor);
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert!(svg.contains("\" fill=\"#ff0000\"/>"), "{svg}");
+        assert!(svg.ends_with("\" fill=\"currentColor\"/></svg>"), "{svg}");
+    }
+
+    #[test]
+    fn colr_v1_linear_gradient() {
+        // A red to blue PaintLinearGradient from (0, 0) to (960, 0)
+        let mut paint = paint_glyph(1);
+        paint.extend([4, 0, 0, 16]);
+        for value in [0i16, 0, 960, 0, 0, 960] {
+            paint.extend(value.to_be_bytes());
+        }
+        // ColorLine, pad, of 2 stops
+        paint.push(0);
+        paint.extend(2u16.to_be_bytes());
+        for value in [0u16, 0, 0x4000, 0x4000, 1, 0x4000] {
+            paint.extend(value.to_be_bytes());
+        }
+        let font_data = font_with_colr(colr_v1(&paint));
+        let font = FontRef::new(&font_data).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_color(true);
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert_eq!(
+            format!(
+                "<defs><linearGradient id=\"g0\" gradientUnits=\"userSpaceOnUse\" x1=\"0\" y1=\"0\" x2=\"960\" y2=\"0\"><stop offset=\"0\" stop-color=\"#ff0000\"/><stop offset=\"1\" stop-color=\"#0000ff\" stop-opacity=\"0.5\"/></linearGradient></defs><path d=\"{}\" fill=\"url(#g0)\"/></svg>",
+                path_data(&font, 1)
+            ),
+            svg[svg.find("<defs>").unwrap()..]
+        );
+    }
+
+    #[test]
+    fn colr_v1_composite() {
+        // PaintComposite, multiplying lan in red onto mail in half blue
+        let source = [paint_glyph(3), paint_solid(0)].concat();
+        let backdrop = [paint_glyph(1), paint_solid(1)].concat();
+        let mut paint = vec![32, 0, 0, 8, 23, 0, 0, 8 + source.len() as u8];
+        paint.extend(source);
+        paint.extend(backdrop);
+        let font_data = font_with_colr(colr_v1(&paint));
+        let font = FontRef::new(&font_data).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_color(true);
+
+        let svg = draw_icon(&font, &options).unwrap();
+
+        assert_eq!(
+            format!(
+                "<path d=\"{}\" fill=\"#0000ff\" fill-opacity=\"0.5\"/><g style=\"mix-blend-mode:multiply\"><path d=\"{}\" fill=\"#ff0000\"/></g></svg>",
+                path_data(&font, 1),
+                path_data(&font, 3)
+            ),
+            svg[svg.find("<path").unwrap()..]
+        );
+    }
 }
@@ -0,0 +1,151 @@
+//! Generates SwiftUI shapes of icons, so iOS code can draw them without the font
+
+use kurbo::{Affine, PathEl, Point};
+use skrifa::FontRef;
+
+use crate::{document::IconDocument, error::DrawSvgError, icon2eps::number, icon2svg::DrawOptions};
+
+/// Generates a SwiftUI `Shape` named `struct_name` that draws the icon scaled to the rect it's
+/// given, e.g. `Mail().frame(width: 24, height: 24)`.
+///
+/// The layers of a color icon are joined and their paint dropped, a shape is filled in one
+/// color. The path style and size in `options` are ignored.
+pub fn draw_shape(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+    struct_name: &str,
+) -> Result<String, DrawSvgError> {
+    let document = IconDocument::draw(font, options)?;
+    let view_box = document.view_box;
+    // SwiftUI is Y-down as we are, only the origin moves
+    let transform = Affine::translate((-view_box.x0, -view_box.y0));
+
+    let mut swift = String::with_capacity(4096);
+    swift.push_str("// Generated by sleipnir, do not edit.\n\n");
+    swift.push_str("import SwiftUI\n\n");
+    swift.push_str(&format!("struct {struct_name}: Shape {{\n"));
+    swift.push_str("    func path(in rect: CGRect) -> Path {\n");
+    swift.push_str("        var path = Path()\n");
+    for mut path in document.paths() {
+        path.apply_affine(transform);
+        for el in path.elements() {
+            let call = match *el {
+                PathEl::MoveTo(p) => format!("move(to: {})", point(p)),
+                PathEl::LineTo(p) => format!("addLine(to: {})", point(p)),
+                PathEl::QuadTo(c, p) => {
+                    format!("addQuadCurve(to: {}, control: {})", point(p), point(c))
+                }
+                PathEl::CurveTo(c0, c1, p) => format!(
+                    "addCurve(to: {}, control1: {}, control2: {})",
+                    point(p),
+                    point(c0),
+                    point(c1)
+                ),
+                PathEl::ClosePath => "closeSubpath()".to_string(),
+            };
+            swift.push_str(&format!("        path.{call}\n"));
+        }
+    }
+    swift.push_str("        return path.applying(\n");
+    swift.push_str("            CGAffineTransform(translationX: rect.minX, y: rect.minY)\n");
+    swift.push_str(&format!(
+        "                .scaledBy(x: rect.width / {}, y: rect.height / {})\n",
+        number(view_box.width()),
+        number(view_box.height())
+    ));
+    swift.push_str("        )\n");
+    swift.push_str("    }\n");
+    swift.push_str("}\n");
+    Ok(swift)
+}
+
+fn point(p: Point) -> String {
+    format!("CGPoint(x: {}, y: {})", number(p.x), number(p.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{instance::Location, FontRef, MetadataProvider};
+
+    use crate::{
+        icon2svg::{DrawOptions, Framing},
+        icon2swift::draw_shape,
+        iconid::{self, IconIdentifier},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn draw_mail_shape() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let swift = draw_shape(&font, &options, "Mail").unwrap();
+
+        assert!(swift.starts_with(concat!(
+            "// Generated by sleipnir, do not edit.\n\n",
+            "import SwiftUI\n\n",
+            "struct Mail: Shape {\n",
+            "    func path(in rect: CGRect) -> Path {\n",
+            "        var path = Path()\n",
+            "        path.move(to: CGPoint(x: "
+        )));
+        assert!(swift.ends_with(concat!(
+            "        path.closeSubpath()\n",
+            "        return path.applying(\n",
+            "            CGAffineTransform(translationX: rect.minX, y: rect.minY)\n",
+            "                .scaledBy(x: rect.width / 960, y: rect.height / 960)\n",
+            "        )\n",
+            "    }\n",
+            "}\n"
+        )));
+        assert_eq!(
+            swift.matches("path.move(").count(),
+            swift.matches("path.closeSubpath()").count()
+        );
+        // Coordinates are relative to the top left of the view box
+        assert!(!swift.contains("y: -"), "{swift}");
+    }
+
+    #[test]
+    fn frame_on_metrics() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        )
+        .with_framing(Framing::Metrics);
+
+        let swift = draw_shape(&font, &options, "Mail").unwrap();
+
+        assert!(
+            swift.contains(".scaledBy(x: rect.width / 960, y: rect.height / 1152)\n"),
+            "{swift}"
+        );
+    }
+
+    #[test]
+    fn draw_quads() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let loc = Location::default();
+        let options = DrawOptions::new(
+            IconIdentifier::Codepoint(0x2e),
+            24.0,
+            (&loc).into(),
+            PathStyle::Unchanged,
+        );
+
+        let swift = draw_shape(&font, &options, "Dot").unwrap();
+
+        assert!(swift.contains("path.addQuadCurve(to: CGPoint("), "{swift}");
+    }
+}
@@ -0,0 +1,401 @@
+//! Produces Android VectorDrawable xml of icons in Google-style icon fonts
+
+use kurbo::Affine;
+use skrifa::{color::Extend, FontRef};
+
+use crate::{
+    document::{icon_names, Color, IconDocument, Layer, Paint},
+    error::DrawSvgError,
+    icon2svg::{number, DrawOptions},
+    iconid::{IconIdentifier, IconIndex},
+    xml::{Namespace, XmlElement},
+};
+
+/// Draws an icon as a VectorDrawable of `width_height` dp square.
+///
+/// Each layer becomes a path. The foreground color is `@android:color/white`, for tinting, and
+/// with [DrawOptions::with_color] palette colors and gradients are written as fills, the
+/// gradients in `aapt:attr` elements.
+pub fn draw_icon(font: &FontRef, options: &DrawOptions<'_>) -> Result<String, DrawSvgError> {
+    let mut vector = write_vector(&IconDocument::draw(font, options)?, options);
+    let auto_mirror = options
+        .auto_mirror
+        .unwrap_or_else(|| is_directional(font, &options.identifier, options.icon_index));
+    if auto_mirror {
+        vector.set_ns_attribute(&Namespace::ANDROID, "autoMirrored", true);
+    }
+    declare_namespaces(&mut vector);
+    Ok(vector.to_string())
+}
+
+// Icons that point along the reading direction, per Material's list of icons to mirror
+const DIRECTIONAL_ICONS: &[&str] = &[
+    "arrow_left_alt",
+    "arrow_right_alt",
+    "call_made",
+    "call_missed",
+    "call_missed_outgoing",
+    "call_received",
+    "call_split",
+    "chevron_left",
+    "chevron_right",
+    "exit_to_app",
+    "first_page",
+    "format_indent_decrease",
+    "format_indent_increase",
+    "forward",
+    "keyboard_arrow_left",
+    "keyboard_arrow_right",
+    "keyboard_backspace",
+    "keyboard_tab",
+    "last_page",
+    "launch",
+    "login",
+    "logout",
+    "navigate_before",
+    "navigate_next",
+    "open_in_new",
+    "read_more",
+    "redo",
+    "reply",
+    "reply_all",
+    "send",
+    "subdirectory_arrow_left",
+    "subdirectory_arrow_right",
+    "trending_flat",
+    "undo",
+];
+
+/// Whether an icon named `name` should be mirrored in right-to-left layouts
+fn is_directional_name(name: &str) -> bool {
+    DIRECTIONAL_ICONS.contains(&name)
+        || name.starts_with("arrow_back")
+        || name.starts_with("arrow_forward")
+}
+
+/// Whether any name of the icon is directional, icons identified otherwise are looked up by
+/// glyph, in `index` if given
+pub(crate) fn is_directional(
+    font: &FontRef,
+    identifier: &IconIdentifier,
+    index: Option<&IconIndex>,
+) -> bool {
+    icon_names(font, identifier, index)
+        .iter()
+        .any(|name| is_directional_name(name))
+}
+
+/// Declares the android namespace, and aapt if it's used, on the root of a drawable
+pub(crate) fn declare_namespaces(root: &mut XmlElement) {
+    root.declare_namespace(&Namespace::ANDROID);
+    if !root.undeclared_prefixes().is_empty() {
+        root.declare_namespace(&Namespace::AAPT);
+    }
+}
+
+/// Serializes a document as a `vector` element, leaving namespaces to be declared by the root
+pub(crate) fn write_vector(document: &IconDocument, options: &DrawOptions<'_>) -> XmlElement {
+    let android = &Namespace::ANDROID;
+    let view_box = document.view_box;
+    let (width, height) = document.output_size();
+    let mut vector = XmlElement::new("vector")
+        .with_ns_attribute(android, "width", format!("{}dp", number(width as f64)))
+        .with_ns_attribute(android, "height", format!("{}dp", number(height as f64)))
+        .with_ns_attribute(android, "viewportWidth", number(view_box.width()))
+        .with_ns_attribute(android, "viewportHeight", number(view_box.height()));
+
+    // The viewport starts at the origin where the view box may not
+    let to_viewport = Affine::translate((-view_box.x0, -view_box.y0));
+    for (mut path, layer) in document.paths().zip(document.layers.iter()) {
+        path.apply_affine(to_viewport);
+        let mut element = XmlElement::new("path").with_ns_attribute(
+            android,
+            "pathData",
+            options.style.write_svg_path(&path),
+        );
+        match (options.stroke_width, &layer.paint) {
+            (Some(stroke_width), _) => {
+                let stroke_width = stroke_width * document.upem as f32 / document.width_height;
+                let color = match options.stroke_color {
+                    Some(rgb) => rgb_string(rgb),
+                    None => FOREGROUND.to_string(),
+                };
+                element.set_ns_attribute(android, "strokeColor", color);
+                element.set_ns_attribute(android, "strokeWidth", number(stroke_width as f64));
+            }
+            (None, Paint::Solid(color)) => {
+                let fill = color.rgb.map(rgb_string);
+                element.set_ns_attribute(
+                    android,
+                    "fillColor",
+                    fill.as_deref().unwrap_or(FOREGROUND),
+                );
+                if color.alpha < 1.0 {
+                    element.set_ns_attribute(android, "fillAlpha", number(color.alpha as f64));
+                }
+            }
+            (None, _) => {
+                element.add_child(
+                    XmlElement::new_ns(&Namespace::AAPT, "attr")
+                        .with_attribute("name", android.qualify("fillColor"))
+                        .with_child(gradient(layer, to_viewport)),
+                );
+            }
+        }
+        vector.add_child(element);
+    }
+    vector
+}
+
+// Icons are tinted so the foreground is drawn in white
+const FOREGROUND: &str = "@android:color/white";
+
+fn rgb_string([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// `#aarrggbb` with the foreground as white
+fn argb_string(color: &Color) -> String {
+    let [r, g, b] = color.rgb.unwrap_or([255, 255, 255]);
+    let a = (color.alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{a:02x}{r:02x}{g:02x}{b:02x}")
+}
+
+/// A gradient element for a layer's non-solid paint.
+///
+/// VectorDrawable radial gradients have no focal circle, the end circle is used.
+fn gradient(layer: &Layer, to_viewport: Affine) -> XmlElement {
+    let android = &Namespace::ANDROID;
+    // Gradients are in the coordinates of the path, which has the layer transform applied
+    let t = to_viewport * layer.transform;
+    let mut element = XmlElement::new("gradient");
+    let (stops, extend) = match &layer.paint {
+        Paint::Solid(..) => unreachable!("solid paints are written as fill colors"),
+        Paint::LinearGradient {
+            p0,
+            p1,
+            stops,
+            extend,
+        } => {
+            let (p0, p1) = (t * *p0, t * *p1);
+            element.set_ns_attribute(android, "type", "linear");
+            element.set_ns_attribute(android, "startX", number(p0.x));
+            element.set_ns_attribute(android, "startY", number(p0.y));
+            element.set_ns_attribute(android, "endX", number(p1.x));
+            element.set_ns_attribute(android, "endY", number(p1.y));
+            (stops, extend)
+        }
+        Paint::RadialGradient {
+            c1,
+            r1,
+            stops,
+            extend,
+            ..
+        } => {
+            let c1 = t * *c1;
+            element.set_ns_attribute(android, "type", "radial");
+            element.set_ns_attribute(android, "centerX", number(c1.x));
+            element.set_ns_attribute(android, "centerY", number(c1.y));
+            element.set_ns_attribute(
+                android,
+                "gradientRadius",
+                number(r1 * t.determinant().abs().sqrt()),
+            );
+            (stops, extend)
+        }
+    };
+    let tile_mode = match extend {
+        Extend::Repeat => "repeat",
+        Extend::Reflect => "mirror",
+        _ => "clamp",
+    };
+    element.set_ns_attribute(android, "tileMode", tile_mode);
+    for (offset, color) in stops {
+        element.add_child(
+            XmlElement::new("item")
+                .with_ns_attribute(android, "offset", number(*offset as f64))
+                .with_ns_attribute(android, "color", argb_string(color)),
+        );
+    }
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::{
+        icon2svg::{
+            tests::{colr_v0, colr_v1, font_with_colr, paint_glyph},
+            DrawOptions,
+        },
+        icon2xml::{draw_icon, is_directional_name},
+        iconid::{self, IconIdentifier, IconIndex, Icons},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn options() -> DrawOptions<'static> {
+        DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+    }
+
+    #[test]
+    fn monochrome() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let xml = draw_icon(&font, &options()).unwrap();
+
+        assert_eq!(
+            concat!(
+                "<vector xmlns:android=\"http://schemas.android.com/apk/res/android\" ",
+                "android:width=\"24dp\" android:height=\"24dp\" ",
+                "android:viewportWidth=\"960\" android:viewportHeight=\"960\">",
+                "<path android:pathData=\"M160,800q-33,0-56.5-23.5T80,720V240q0-33 23.5-56.5T160,160H800q33,0 56.5,23.5T880,240V720q0,33-23.5,56.5T800,800H160ZM480,520L160,320V720H800V320L480,520Zm0-80L800,240H160L480,440ZM160,320V240V720V320Z\" ",
+                "android:fillColor=\"@android:color/white\"/>",
+                "</vector>"
+            ),
+            xml
+        );
+    }
+
+    /// The fill attributes of each path
+    fn fills(xml: &str) -> Vec<&str> {
+        xml.split("<path ")
+            .skip(1)
+            .map(|path| {
+                let fill = path.split_once("\" android:").unwrap().1;
+                let end = fill.find("\"/>").or_else(|| fill.find("\">")).unwrap();
+                &fill[..end + 1]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colr_v0_fill_colors() {
+        let font_data = font_with_colr(colr_v0());
+        let font = FontRef::new(&font_data).unwrap();
+
+        let xml = draw_icon(&font, &options().with_color(true)).unwrap();
+
+        assert_eq!(
+            vec![
+                "fillColor=\"#ff0000\"",
+                "fillColor=\"#0000ff\" android:fillAlpha=\"0.5\"",
+                "fillColor=\"@android:color/white\"",
+            ],
+            fills(&xml)
+        );
+    }
+
+    #[test]
+    fn colr_v1_linear_gradient() {
+        // A red to blue PaintLinearGradient from (0, 0) to (960, 0), reflected
+        let mut paint = paint_glyph(1);
+        paint.extend([4, 0, 0, 16]);
+        for value in [0i16, 0, 960, 0, 0, 960] {
+            paint.extend(value.to_be_bytes());
+        }
+        paint.push(2);
+        paint.extend(2u16.to_be_bytes());
+        for value in [0u16, 0, 0x4000, 0x4000, 1, 0x4000] {
+            paint.extend(value.to_be_bytes());
+        }
+        let font_data = font_with_colr(colr_v1(&paint));
+        let font = FontRef::new(&font_data).unwrap();
+
+        let xml = draw_icon(&font, &options().with_color(true)).unwrap();
+
+        assert!(xml.starts_with(concat!(
+            "<vector xmlns:android=\"http://schemas.android.com/apk/res/android\" ",
+            "xmlns:aapt=\"http://schemas.android.com/aapt\" "
+        )));
+        // Font Y-up (0, 0) is at the bottom of the viewport
+        assert_eq!(
+            concat!(
+                "<aapt:attr name=\"android:fillColor\">",
+                "<gradient android:type=\"linear\" android:startX=\"0\" android:startY=\"960\" ",
+                "android:endX=\"960\" android:endY=\"960\" android:tileMode=\"mirror\">",
+                "<item android:offset=\"0\" android:color=\"#ffff0000\"/>",
+                "<item android:offset=\"1\" android:color=\"#800000ff\"/>",
+                "</gradient></aapt:attr></path></vector>"
+            ),
+            &xml[xml.find("<aapt:attr").unwrap()..]
+        );
+    }
+
+    #[test]
+    fn stroke() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let xml = draw_icon(
+            &font,
+            &options()
+                .with_stroke_width(1.5)
+                .with_stroke_color([0x12, 0xab, 0xff]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec!["strokeColor=\"#12abff\" android:strokeWidth=\"60\""],
+            fills(&xml)
+        );
+    }
+
+    #[test]
+    fn auto_mirror() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let draw = |options: DrawOptions| {
+            draw_icon(&font, &options)
+                .unwrap()
+                .contains(" android:autoMirrored=\"true\"")
+        };
+
+        assert!(!draw(options()));
+        assert!(draw(options().with_auto_mirror(true)));
+        assert!(is_directional_name("arrow_back_ios_new"));
+        assert!(is_directional_name("send"));
+        assert!(!is_directional_name("format_align_left"));
+    }
+
+    #[test]
+    fn auto_mirror_by_glyph() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let name = |directional| {
+            font.icons()
+                .unwrap()
+                .into_iter()
+                .flat_map(|icon| icon.names)
+                .find(|name| is_directional_name(name) == directional)
+                .unwrap()
+        };
+        let index = IconIndex::new(&font).unwrap();
+        let draw = |name: String, index: Option<&IconIndex>| {
+            let gid = IconIdentifier::Name(name.into())
+                .resolve_unsubstituted(&font)
+                .unwrap();
+            let options = DrawOptions::new(
+                IconIdentifier::GlyphId(gid),
+                24.0,
+                Default::default(),
+                PathStyle::Compact,
+            );
+            let options = match index {
+                Some(index) => options.with_icon_index(index),
+                None => options,
+            };
+            draw_icon(&font, &options)
+                .unwrap()
+                .contains(" android:autoMirrored=\"true\"")
+        };
+
+        for index in [None, Some(&index)] {
+            assert!(draw(name(true), index));
+            assert!(!draw(name(false), index));
+        }
+    }
+}
@@ -1,32 +1,39 @@
 //! Identification of icons and resolution of glyph ids. Assumes Google style icon font input.
 //!
 use crate::error::IconResolutionError;
-use crate::ligatures::Ligatures;
+use crate::ligatures::{gid_to_char, reverse_non_pua_cmap, single_substitute, Ligatures};
 use skrifa::{
     instance::LocationRef,
     raw::{
         tables::{
-            gsub::{Gsub, SingleSubst, SubstitutionSubtables},
+            gsub::{Gsub, SubstitutionSubtables},
             layout::ConditionSet,
         },
-        types::BigEndian,
         FontRef, ReadError, TableProvider, TopLevelTable,
     },
-    GlyphId, MetadataProvider,
+    GlyphId, MetadataProvider, Tag,
 };
 use smallvec::SmallVec;
 use smol_str::SmolStr;
-use std::{collections::HashMap, iter::once, ops::RangeInclusive};
+use std::{collections::HashMap, ops::RangeInclusive};
 
 // https://en.wikipedia.org/wiki/Private_Use_Areas
 const _PUA_CODEPOINTS: [RangeInclusive<u32>; 3] =
     [0xE000..=0xF8FF, 0xF0000..=0xFFFFD, 0x100000..=0x10FFFD];
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum IconIdentifier {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::glyph_id"))]
     GlyphId(GlyphId),
     Codepoint(u32),
     Name(SmolStr),
+    /// Several codepoints drawn as one glyph by a ligature, e.g. a flag or a ZWJ emoji sequence
+    Sequence(Vec<u32>),
 }
 
 impl IconIdentifier {
@@ -40,7 +47,28 @@ impl IconIdentifier {
         font: &FontRef,
         location: &LocationRef,
     ) -> Result<GlyphId, IconResolutionError> {
-        let gid = match self {
+        self.resolve_with_features(font, location, &[])
+    }
+
+    /// As [IconIdentifier::resolve] also applying the single substitutions of `features`, e.g.
+    /// `ss01` or `salt` to select an alternate. Other lookup types are ignored.
+    pub fn resolve_with_features(
+        &self,
+        font: &FontRef,
+        location: &LocationRef,
+        features: &[Tag],
+    ) -> Result<GlyphId, IconResolutionError> {
+        let gid = self.resolve_unsubstituted(font)?;
+        apply_location_based_substitution(font, location, features, gid)
+            .map_err(IconResolutionError::ReadError)
+    }
+
+    /// The glyph id before location based substitution, the same at every location
+    pub(crate) fn resolve_unsubstituted(
+        &self,
+        font: &FontRef,
+    ) -> Result<GlyphId, IconResolutionError> {
+        match self {
             IconIdentifier::GlyphId(gid) => Ok(*gid),
             IconIdentifier::Codepoint(cp) => font
                 .cmap()
@@ -54,16 +82,64 @@ impl IconIdentifier {
                         None => Err(IconResolutionError::NoLigature(name.to_string())),
                     })
             }
-        }?;
+            IconIdentifier::Sequence(codepoints) => {
+                let cmap = font.cmap().map_err(IconResolutionError::ReadError)?;
+                let gids = codepoints
+                    .iter()
+                    .map(|cp| {
+                        cmap.map_codepoint(*cp)
+                            .ok_or(IconResolutionError::NoCmapEntry(*cp))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                match gids.as_slice() {
+                    [gid] => Ok(*gid),
+                    _ => font
+                        .resolve_ligature_glyphs(&gids)
+                        .ok_or_else(|| IconResolutionError::NoSequenceLigature(codepoints.clone())),
+                }
+            }
+        }
+    }
 
-        apply_location_based_substitution(font, location, gid)
-            .map_err(IconResolutionError::ReadError)
+    /// A name for output derived from this identifier, e.g. `mail`, `ue158`, `gid5` or
+    /// `u1f1fa_1f1f8`
+    pub(crate) fn file_stem(&self) -> String {
+        match self {
+            IconIdentifier::Name(name) => name.to_string(),
+            IconIdentifier::Codepoint(cp) => format!("u{cp:04x}"),
+            IconIdentifier::GlyphId(gid) => format!("gid{}", gid.to_u32()),
+            IconIdentifier::Sequence(codepoints) => format!(
+                "u{}",
+                codepoints
+                    .iter()
+                    .map(|cp| format!("{cp:04x}"))
+                    .collect::<Vec<_>>()
+                    .join("_")
+            ),
+        }
+    }
+
+    /// [IconIdentifier::file_stem] limited to ASCII letters, digits, `_` and `-`, for file
+    /// paths, so names read from a font can't reach outside the directory output is written to.
+    ///
+    /// Other characters are escaped as `-u` and their hex codepoint, e.g. `a/b` becomes
+    /// `a-u002fb`.
+    pub(crate) fn path_stem(&self) -> String {
+        self.file_stem()
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c.to_string(),
+                c => format!("-u{:04x}", c as u32),
+            })
+            .collect()
     }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Icon {
     // Icon's glyph.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::glyph_id"))]
     pub gid: GlyphId,
     // Names of the icons pointing at the same `gid`.
     pub names: Vec<String>,
@@ -111,85 +187,82 @@ fn matches(
 
 /// Pending availability of memory safe shaping apply single substitutions manually because the FILL
 /// axis uses them to prevent seams that occur when shapes grow to be adjacent.
+///
+/// Every lookup of every feature substituted by the first matching record, and of every
+/// requested feature, is applied in lookup list order, each to the output of the last, as a
+/// shaper would. A requested feature the record substitutes uses the substitute.
 fn apply_location_based_substitution(
     font: &FontRef,
     location: &LocationRef,
+    features: &[Tag],
     gid: GlyphId,
 ) -> Result<GlyphId, ReadError> {
     if font.table_data(Gsub::TAG).is_none() {
         return Ok(gid);
     }
     let gsub = font.gsub()?;
-    let Some(feature_variations) = gsub.feature_variations() else {
-        return Ok(gid);
-    };
-
-    let feature_variations = feature_variations?;
     let lookups = gsub.lookup_list()?;
 
     // For small sets of lookup indices avoid heap allocation
     let mut lookup_indices = SmallVec::<[u16; 32]>::new();
-
-    for record in feature_variations.feature_variation_records() {
-        if !matches(
-            record.condition_set(feature_variations.offset_data()),
-            location,
-        )? {
-            continue;
+    let mut substituted_features = SmallVec::<[u16; 8]>::new();
+    if let Some(feature_variations) = gsub.feature_variations() {
+        let feature_variations = feature_variations?;
+        // Only the first record whose conditions match applies
+        let mut live_record = None;
+        for record in feature_variations.feature_variation_records() {
+            if matches(
+                record.condition_set(feature_variations.offset_data()),
+                location,
+            )? {
+                live_record = Some(record);
+                break;
+            }
         }
-
-        let Some(feature_table_substitution) =
-            record.feature_table_substitution(feature_variations.offset_data())
-        else {
-            // We found a live sub, it's a nop. Done.
-            return Ok(gid);
-        };
-        let feature_table_substitution = feature_table_substitution?;
-
-        for sub in feature_table_substitution.substitutions() {
-            let alt = sub.alternate_feature(feature_table_substitution.offset_data())?;
-            // <https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2#feature-table>
-            // "the client arranges the indices numerically into their LookupList order"
-            lookup_indices.clear();
-            for lookup_idx in alt.lookup_list_indices() {
-                lookup_indices.push(lookup_idx.get());
+        // A live record without a substitution is a nop
+        if let Some(feature_table_substitution) = live_record
+            .and_then(|record| record.feature_table_substitution(feature_variations.offset_data()))
+        {
+            let feature_table_substitution = feature_table_substitution?;
+            for sub in feature_table_substitution.substitutions() {
+                let alt = sub.alternate_feature(feature_table_substitution.offset_data())?;
+                lookup_indices.extend(alt.lookup_list_indices().iter().map(|idx| idx.get()));
+                substituted_features.push(sub.feature_index());
             }
-            lookup_indices.sort_unstable();
-
-            for lookup_idx in lookup_indices.iter() {
-                let lookup = lookups.lookups().get(*lookup_idx as usize)?;
-                let SubstitutionSubtables::Single(table) = lookup.subtables()? else {
-                    continue;
-                };
-                for single in table.iter() {
-                    let single = &single?;
-                    let coverage = match single {
-                        SingleSubst::Format1(single) => single.coverage()?,
-                        SingleSubst::Format2(single) => single.coverage()?,
-                    };
-                    let Some(coverage_idx) = coverage.get(gid) else {
-                        continue;
-                    };
-                    // This one is live
-                    let new_gid = match single {
-                        SingleSubst::Format1(single) => GlyphId::new(
-                            (gid.to_u16() as i32 + single.delta_glyph_id() as i32) as u16,
-                        ),
-                        SingleSubst::Format2(single) => single
-                            .substitute_glyph_ids()
-                            .get(coverage_idx as usize)
-                            .map(|be| be.get())
-                            .unwrap_or(gid),
-                    };
-                    return Ok(new_gid);
-                }
+        }
+    }
+    if !features.is_empty() {
+        let feature_list = gsub.feature_list()?;
+        for (idx, record) in feature_list.feature_records().iter().enumerate() {
+            if !features.contains(&record.feature_tag())
+                || substituted_features.contains(&(idx as u16))
+            {
+                continue;
+            }
+            let feature = record.feature(feature_list.offset_data())?;
+            lookup_indices.extend(feature.lookup_list_indices().iter().map(|idx| idx.get()));
+        }
+    }
+    // <https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2#feature-table>
+    // "the client arranges the indices numerically into their LookupList order"
+    lookup_indices.sort_unstable();
+    lookup_indices.dedup();
+
+    let mut gid = gid;
+    for lookup_idx in lookup_indices.iter() {
+        let lookup = lookups.lookups().get(*lookup_idx as usize)?;
+        let SubstitutionSubtables::Single(table) = lookup.subtables()? else {
+            continue;
+        };
+        // The first subtable that covers the glyph applies, then we move to the next lookup
+        for single in table.iter() {
+            if let Some(substitute) = single_substitute(&single?, gid)? {
+                // This one is live
+                gid = substitute;
+                break;
             }
         }
-        // We need only apply the first live, supported, substitution
-        break;
     }
-
-    // If we got here there is no change
     Ok(gid)
 }
 
@@ -205,43 +278,35 @@ pub trait Icons {
 impl Icons for FontRef<'_> {
     fn icons(&self) -> Result<Vec<Icon>, IconResolutionError> {
         let charmap = self.charmap();
-        let mut rev_non_pua_cmap: HashMap<GlyphId, u32> = HashMap::new();
+        let rev_non_pua_cmap = reverse_non_pua_cmap(self);
         let mut rev_pua_cmap: HashMap<GlyphId, Vec<u32>> = HashMap::new();
         for (codepoint, gid) in charmap.mappings() {
             if is_pua(codepoint) {
                 rev_pua_cmap.entry(gid).or_default().push(codepoint);
-            } else {
-                rev_non_pua_cmap.insert(gid, codepoint);
             }
         }
 
         // A glyph having both non-PUA and PUA codepoint is considered a single character ligature.
         let single_charc_icons = rev_non_pua_cmap
-            .iter()
-            .filter(|(k, _)| rev_pua_cmap.contains_key(k))
-            .map(|(k, c)| {
+            .keys()
+            .filter(|k| rev_pua_cmap.contains_key(k))
+            .map(|k| {
                 Ok::<(GlyphId, String), IconResolutionError>((
                     *k,
-                    String::from(
-                        char::from_u32(*c).ok_or(IconResolutionError::InvalidCharacter(*c))?,
-                    ),
+                    String::from(gid_to_char(k, &rev_non_pua_cmap)?),
                 ))
             });
 
         let icons = self
-            .ligatures()
-            .filter(|(_, liga)| !rev_non_pua_cmap.contains_key(&liga.ligature_glyph()))
-            .map(|(liga_first, liga)| {
-                Ok::<(GlyphId, String), IconResolutionError>((
-                    liga.ligature_glyph(),
-                    build_icon_name(liga_first, liga.component_glyph_ids(), &rev_non_pua_cmap)?,
-                ))
-            });
+            .ligature_strings()?
+            .into_iter()
+            .filter(|(_, gid)| !rev_non_pua_cmap.contains_key(gid))
+            .map(|(name, gid)| Ok((gid, name)));
 
         let mut icons: Vec<(GlyphId, String)> = single_charc_icons
             .chain(icons)
             .collect::<Result<Vec<_>, _>>()?;
-        icons.sort_by(|a, b| a.0.cmp(&b.0));
+        icons.sort_by_key(|a| a.0);
         icons
             .chunk_by(|a, b| a.0 == b.0)
             .map(|group| {
@@ -258,31 +323,45 @@ impl Icons for FontRef<'_> {
     }
 }
 
-fn build_icon_name(
-    first_gid: GlyphId,
-    gids: &[BigEndian<GlyphId>],
-    rev_non_pua_cmap: &HashMap<GlyphId, u32>,
-) -> Result<String, IconResolutionError> {
-    Ok(once(first_gid)
-        .chain(gids.iter().map(|g| g.get()))
-        .map(|gid| gid_to_char(&gid, rev_non_pua_cmap))
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .collect())
+/// The icons of a font by glyph id, e.g. to name the glyphs in shaping output
+#[derive(Debug, Default)]
+pub struct IconIndex {
+    by_gid: HashMap<GlyphId, Icon>,
 }
 
-fn is_pua(codepoint: u32) -> bool {
-    _PUA_CODEPOINTS.iter().any(|r| r.contains(&codepoint))
+impl IconIndex {
+    /// Indexes the icons listed by [Icons::icons]
+    pub fn new(font: &FontRef) -> Result<IconIndex, IconResolutionError> {
+        Ok(IconIndex {
+            by_gid: font
+                .icons()?
+                .into_iter()
+                .map(|icon| (icon.gid, icon))
+                .collect(),
+        })
+    }
+
+    pub fn icon(&self, gid: GlyphId) -> Option<&Icon> {
+        self.by_gid.get(&gid)
+    }
+
+    /// The names of the icon drawn by `gid`, empty if it isn't an icon
+    pub fn names_for_gid(&self, gid: GlyphId) -> &[String] {
+        self.icon(gid)
+            .map(|icon| icon.names.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The PUA codepoints of the icon drawn by `gid`, empty if it isn't an icon
+    pub fn codepoints_for_gid(&self, gid: GlyphId) -> &[u32] {
+        self.icon(gid)
+            .map(|icon| icon.codepoints.as_slice())
+            .unwrap_or_default()
+    }
 }
 
-fn gid_to_char(
-    gid: &GlyphId,
-    rev_non_pua_cmap: &HashMap<GlyphId, u32>,
-) -> Result<char, IconResolutionError> {
-    let codepoint = *rev_non_pua_cmap
-        .get(gid)
-        .ok_or_else(|| IconResolutionError::NoCmapEntryForGid(gid.to_u32()))?;
-    char::from_u32(codepoint).ok_or(IconResolutionError::InvalidCharacter(codepoint))
+pub(crate) fn is_pua(codepoint: u32) -> bool {
+    _PUA_CODEPOINTS.iter().any(|r| r.contains(&codepoint))
 }
 
 #[cfg(test)]
@@ -296,11 +375,26 @@ pub static MAN: IconIdentifier = IconIdentifier::GlyphId(GlyphId::new(5));
 
 #[cfg(test)]
 mod tests {
-    use skrifa::{setting::VariationSetting, FontRef, GlyphId, MetadataProvider};
-    use write_fonts::{tables::cmap::Cmap, FontBuilder};
+    use skrifa::{
+        instance::LocationRef, setting::VariationSetting, FontRef, GlyphId, MetadataProvider,
+    };
+    use write_fonts::{
+        tables::{
+            cmap::Cmap,
+            gsub::{Gsub, SingleSubst, SubstitutionLookup, SubstitutionLookupList},
+            layout::{
+                ConditionFormat1, ConditionSet, CoverageTable, Feature, FeatureList, FeatureRecord,
+                FeatureTableSubstitution, FeatureTableSubstitutionRecord, FeatureVariationRecord,
+                FeatureVariations, Lookup, LookupFlag, ScriptList,
+            },
+        },
+        types::{F2Dot14, Tag},
+        FontBuilder,
+    };
 
     use crate::{
-        iconid::{Icon, Icons, LAN, MAIL, MAN, PLAY_ARROW},
+        error::IconResolutionError,
+        iconid::{Icon, IconIndex, Icons, LAN, MAIL, MAN, PLAY_ARROW},
         testdata::{self, MATERIAL_SYMBOLS_POPULAR},
     };
 
@@ -403,11 +497,171 @@ mod tests {
         assert_gid_at(&LAN, [("FILL", 0.99)], GlyphId::new(4));
     }
 
+    /// Replaces GSUB with one single substitution lookup per `(from, to)` pair in `lookups`
+    /// and one feature per `(tag, lookup indices)` in `features`. Where FILL >= 0.5 each
+    /// `(feature index, lookup indices)` in `at_fill` swaps in those lookups for the feature's.
+    fn rebuild_font_with_single_substitutions(
+        fontdata: &[u8],
+        lookups: &[(u16, u16)],
+        features: &[(&[u8; 4], Vec<u16>)],
+        at_fill: &[(u16, Vec<u16>)],
+    ) -> Vec<u8> {
+        let font = FontRef::new(fontdata).unwrap();
+        let fill = font
+            .axes()
+            .iter()
+            .position(|axis| axis.tag() == Tag::new(b"FILL"))
+            .unwrap() as u16;
+        let lookups = lookups
+            .iter()
+            .map(|&(from, to)| {
+                SubstitutionLookup::Single(Lookup::new(
+                    LookupFlag::empty(),
+                    vec![SingleSubst::format_1(
+                        CoverageTable::format_1(vec![GlyphId::new(from)]),
+                        to as i16 - from as i16,
+                    )],
+                    0,
+                ))
+            })
+            .collect();
+        let features = features
+            .iter()
+            .map(|(tag, lookups)| {
+                FeatureRecord::new(Tag::new(tag), Feature::new(None, lookups.clone()))
+            })
+            .collect();
+        let mut gsub = Gsub::new(
+            ScriptList::default(),
+            FeatureList::new(features),
+            SubstitutionLookupList::new(lookups),
+        );
+        gsub.feature_variations = FeatureVariations::new(vec![FeatureVariationRecord::new(
+            Some(ConditionSet::new(vec![ConditionFormat1::new(
+                fill,
+                F2Dot14::from_f32(0.5),
+                F2Dot14::from_f32(1.0),
+            )])),
+            Some(FeatureTableSubstitution::new(
+                at_fill
+                    .iter()
+                    .map(|(feature, lookups)| {
+                        FeatureTableSubstitutionRecord::new(
+                            *feature,
+                            Feature::new(None, lookups.clone()),
+                        )
+                    })
+                    .collect(),
+            )),
+        )])
+        .into();
+        FontBuilder::new()
+            .add_table(&gsub)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    /// At FILL >= 0.5 mail => gid 2 => gid 3 using one lookup from each of two features, the
+    /// feature listed first holding the later lookup
+    fn rebuild_font_with_chained_substitutions(fontdata: &[u8]) -> Vec<u8> {
+        rebuild_font_with_single_substitutions(
+            fontdata,
+            &[(1, 2), (2, 3)],
+            &[(b"rlig", vec![]), (b"rvrn", vec![])],
+            &[(0, vec![1]), (1, vec![0])],
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn resolve_chained_substitution_at_FILL_1() {
+        let font_data = rebuild_font_with_chained_substitutions(testdata::ICON_FONT);
+        assert_gid_at_with_font(&font_data, &MAIL, [("FILL", 1.0)], GlyphId::new(3));
+        assert_gid_at_with_font(&font_data, &MAIL, [("FILL", 0.0)], GlyphId::new(1));
+    }
+
+    /// ss01 swaps gid 1 for 4, which at FILL 1 a variation swaps for 2
+    fn rebuild_font_with_stylistic_set(fontdata: &[u8]) -> Vec<u8> {
+        rebuild_font_with_single_substitutions(
+            fontdata,
+            &[(1, 4), (4, 2)],
+            &[(b"rvrn", vec![]), (b"ss01", vec![0])],
+            &[(0, vec![1])],
+        )
+    }
+
+    #[test]
+    fn resolve_stylistic_set() {
+        let font_data = rebuild_font_with_stylistic_set(testdata::ICON_FONT);
+        let font = FontRef::new(&font_data).unwrap();
+        let resolve = |fill: f32, features: &[Tag]| {
+            let location = font.axes().location([("FILL", fill)]);
+            MAIL.resolve_with_features(&font, &(&location).into(), features)
+                .unwrap()
+        };
+        let ss01 = [Tag::new(b"ss01")];
+
+        assert_eq!(GlyphId::new(1), resolve(0.0, &[]));
+        assert_eq!(GlyphId::new(4), resolve(0.0, &ss01));
+        assert_eq!(GlyphId::new(1), resolve(1.0, &[]));
+        assert_eq!(GlyphId::new(2), resolve(1.0, &ss01));
+        // Unknown features are ignored
+        assert_eq!(GlyphId::new(1), resolve(0.0, &[Tag::new(b"ss20")]));
+    }
+
     #[test]
     fn resolve_man_icon_at_default() {
         assert_gid_at::<[(&str, f32); 0]>(&MAN, [], GlyphId::new(5));
     }
 
+    #[test]
+    fn resolve_sequence() {
+        let font = FontRef::new(testdata::LIGA_TESTS_FONT).unwrap();
+        let news = IconIdentifier::Sequence("news".chars().map(|c| c as u32).collect());
+        let loc = LocationRef::default();
+
+        assert_eq!(
+            IconIdentifier::Name("news".into())
+                .resolve(&font, &loc)
+                .unwrap(),
+            news.resolve(&font, &loc).unwrap()
+        );
+        assert_eq!(
+            IconIdentifier::Codepoint('x' as u32)
+                .resolve(&font, &loc)
+                .unwrap(),
+            IconIdentifier::Sequence(vec!['x' as u32])
+                .resolve(&font, &loc)
+                .unwrap()
+        );
+        assert_eq!("u006e_0065_0077_0073", news.file_stem());
+        assert_eq!(
+            "-u002e-u002e-u002fnews",
+            IconIdentifier::Name("../news".into()).path_stem()
+        );
+    }
+
+    #[test]
+    fn sequence_without_ligature() {
+        let font = FontRef::new(testdata::LIGA_TESTS_FONT).unwrap();
+        let sequence = IconIdentifier::Sequence(vec!['x' as u32, 'x' as u32]);
+
+        assert!(matches!(
+            sequence.resolve(&font, &LocationRef::default()),
+            Err(IconResolutionError::NoSequenceLigature(codepoints)) if codepoints == ['x' as u32; 2]
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn icon_json() {
+        let icon = Icon::new("mail", [0xe158], 5);
+        let json = serde_json::to_string(&icon).unwrap();
+        assert_eq!(r#"{"gid":5,"names":["mail"],"codepoints":[57688]}"#, json);
+        assert_eq!(icon, serde_json::from_str(&json).unwrap());
+    }
+
     #[test]
     fn icons_default() {
         let font_data = rebuild_font_with_cmap(
@@ -415,7 +669,7 @@ mod tests {
             |(_, _)| true,
             vec![('\u{E358}', GlyphId::new(3))],
         );
-        let expected = vec![
+        let expected = [
             Icon::new("x", [58180], 6),
             Icon::new("box_check", [58199, 58200], 3),
             Icon::new("news", [57394], 4),
@@ -441,6 +695,26 @@ mod tests {
             names: vec![String::from("mic_none"), String::from("mic")]
         }))
     }
+
+    #[test]
+    fn index_by_gid() {
+        let font = FontRef::new(MATERIAL_SYMBOLS_POPULAR).unwrap();
+
+        let index = IconIndex::new(&font).unwrap();
+
+        assert_eq!(
+            &[String::from("mic_none"), String::from("mic")],
+            index.names_for_gid(GlyphId::new(31))
+        );
+        assert_eq!(
+            &[57385, 57386, 58141],
+            index.codepoints_for_gid(GlyphId::new(31))
+        );
+        // .notdef isn't an icon
+        assert!(index.names_for_gid(GlyphId::new(0)).is_empty());
+        assert!(index.codepoints_for_gid(GlyphId::new(0)).is_empty());
+    }
+
     #[test]
     fn icons_missing_component_cmap() {
         let font_data = rebuild_font_with_cmap(
@@ -0,0 +1,176 @@
+//! Produces a single svg sprite sheet of many icons, so the web can fetch one file per icon set
+
+use skrifa::FontRef;
+
+use crate::{
+    document::IconDocument,
+    error::DrawSvgError,
+    icon2svg::{number, write_svg_content, DrawOptions},
+    iconid::IconIdentifier,
+    xml::{Namespace, XmlElement},
+};
+
+/// Draws each of `icons` into a `<symbol>` with the same drawing `options`, whose own identifier
+/// is ignored.
+///
+/// Symbols are identified as icons are named in file names, e.g. `mail`, `ue158` or `gid5`, and
+/// are referenced as `sprite.svg#mail`. The sheet also `<use>`s each symbol once, left to right,
+/// so it can be previewed as is. Repeated icons are drawn once.
+pub fn draw_sprite(
+    font: &FontRef,
+    icons: &[IconIdentifier],
+    options: &DrawOptions<'_>,
+) -> Result<String, DrawSvgError> {
+    let mut defs = XmlElement::new("defs");
+    let mut uses = Vec::with_capacity(icons.len());
+    let mut ids: Vec<String> = Vec::with_capacity(icons.len());
+    let (mut width, mut height) = (0.0f32, 0.0f32);
+    for identifier in icons {
+        let id = identifier.file_stem();
+        if ids.contains(&id) {
+            continue;
+        }
+        let mut icon_options = options.clone();
+        icon_options.identifier = identifier.clone();
+        let document = IconDocument::draw(font, &icon_options)?;
+
+        let view_box = document.view_box;
+        let mut symbol = XmlElement::new("symbol")
+            .with_attribute("id", &id)
+            .with_attribute(
+                "viewBox",
+                format!(
+                    "{} {} {} {}",
+                    view_box.x0,
+                    view_box.y0,
+                    view_box.width(),
+                    view_box.height()
+                ),
+            );
+        write_svg_content(&mut symbol, &document, &icon_options, &format!("{id}-"));
+        defs.add_child(symbol);
+
+        let (icon_width, icon_height) = document.output_size();
+        uses.push(
+            XmlElement::new("use")
+                .with_attribute("href", format!("#{id}"))
+                .with_attribute("x", number(width as f64))
+                .with_attribute("width", icon_width)
+                .with_attribute("height", icon_height),
+        );
+        width += icon_width;
+        height = height.max(icon_height);
+        ids.push(id);
+    }
+
+    let width = number(width as f64);
+    let mut svg = XmlElement::new("svg")
+        .with_namespace(&Namespace::SVG)
+        .with_attribute("viewBox", format!("0 0 {width} {height}"))
+        .with_attribute("height", height)
+        .with_attribute("width", width);
+    if !defs.children().is_empty() {
+        svg.add_child(defs);
+    }
+    for element in uses {
+        svg.add_child(element);
+    }
+    Ok(svg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, GlyphId};
+
+    use crate::{
+        error::DrawSvgError,
+        icon2svg::{draw_icon, DrawOptions},
+        iconid::{self, IconIdentifier},
+        icons2sprite::draw_sprite,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn options() -> DrawOptions<'static> {
+        DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+    }
+
+    /// The svg without its root element
+    fn content(svg: &str) -> &str {
+        &svg[svg.find('>').unwrap() + 1..svg.len() - "</svg>".len()]
+    }
+
+    #[test]
+    fn symbols_match_individual_svgs() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let icons = [
+            iconid::LAN.clone(),
+            iconid::MAIL.clone(),
+            IconIdentifier::GlyphId(GlyphId::new(5)),
+        ];
+
+        let sprite = draw_sprite(&font, &icons, &options()).unwrap();
+
+        let mut expected = String::from(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 72 24\" height=\"24\" width=\"72\"><defs>",
+        );
+        for (icon, id) in icons.iter().zip(["lan", "ue158", "gid5"]) {
+            let mut icon_options = options();
+            icon_options.identifier = icon.clone();
+            let svg = draw_icon(&font, &icon_options).unwrap();
+            expected.push_str(&format!(
+                "<symbol id=\"{id}\" viewBox=\"0 -960 960 960\">{}</symbol>",
+                content(&svg)
+            ));
+        }
+        expected.push_str(concat!(
+            "</defs>",
+            "<use href=\"#lan\" x=\"0\" width=\"24\" height=\"24\"/>",
+            "<use href=\"#ue158\" x=\"24\" width=\"24\" height=\"24\"/>",
+            "<use href=\"#gid5\" x=\"48\" width=\"24\" height=\"24\"/>",
+            "</svg>"
+        ));
+        assert_eq!(expected, sprite);
+    }
+
+    #[test]
+    fn repeats_drawn_once() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let sprite = draw_sprite(
+            &font,
+            &[iconid::MAIL.clone(), IconIdentifier::Codepoint(0xe158)],
+            &options(),
+        )
+        .unwrap();
+
+        assert_eq!(1, sprite.matches("<symbol").count());
+        assert_eq!(1, sprite.matches("<use").count());
+    }
+
+    #[test]
+    fn empty_sprite() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 0 0\" height=\"0\" width=\"0\"/>",
+            draw_sprite(&font, &[], &options()).unwrap()
+        );
+    }
+
+    #[test]
+    fn unresolved_icon() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let icons = [iconid::MAIL.clone(), IconIdentifier::Name("nope".into())];
+
+        assert!(matches!(
+            draw_sprite(&font, &icons, &options()),
+            Err(DrawSvgError::ResolutionError(..))
+        ));
+    }
+}
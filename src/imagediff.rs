@@ -0,0 +1,241 @@
+//! Compares rendered images tolerantly, for golden tests that shouldn't fail on encoder noise
+
+use crate::error::ImageDiffError;
+
+// SSIM is computed over non-overlapping windows of this many pixels square
+const WINDOW: usize = 8;
+// Stabilizing constants from the SSIM paper for 8 bit values
+const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Borrowed, non-premultiplied 8 bit RGBA pixels, row by row
+#[derive(Clone, Copy, Debug)]
+pub struct RgbaImage<'a> {
+    width: usize,
+    height: usize,
+    data: &'a [u8],
+}
+
+impl<'a> RgbaImage<'a> {
+    pub fn new(width: usize, height: usize, data: &'a [u8]) -> Result<Self, ImageDiffError> {
+        if data.len() != width * height * 4 {
+            return Err(ImageDiffError::InvalidLength(width, height, data.len()));
+        }
+        Ok(RgbaImage {
+            width,
+            height,
+            data,
+        })
+    }
+
+    fn pixels(&self) -> impl Iterator<Item = &[u8]> {
+        self.data.chunks_exact(4)
+    }
+
+    /// Luminance of the pixel composited over white
+    fn luma(&self, x: usize, y: usize) -> f64 {
+        let i = (y * self.width + x) * 4;
+        let [r, g, b, a] = [0, 1, 2, 3].map(|c| self.data[i + c] as f64);
+        let over_white = |c: f64| c * a / 255.0 + 255.0 * (1.0 - a / 255.0);
+        0.299 * over_white(r) + 0.587 * over_white(g) + 0.114 * over_white(b)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    channel_tolerance: u8,
+    min_similarity: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            channel_tolerance: 0,
+            min_similarity: 0.99,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Exact pixel counting with a similarity threshold of 0.99
+    pub fn new() -> DiffOptions {
+        Default::default()
+    }
+
+    /// Pixels whose channels all differ by at most `tolerance` don't count as different
+    pub fn with_channel_tolerance(mut self, tolerance: u8) -> Self {
+        self.channel_tolerance = tolerance;
+        self
+    }
+
+    /// The similarity, from 0 to 1, at or above which images are considered to match
+    pub fn with_min_similarity(mut self, min_similarity: f64) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageDiff {
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+    /// Mean structural similarity (SSIM) of luminance, 1 for identical images
+    pub similarity: f64,
+    /// Whether similarity reached [DiffOptions::with_min_similarity]
+    pub matches: bool,
+}
+
+/// Counts differing pixels and scores how alike two images of the same size look.
+///
+/// The score is SSIM over 8x8 windows of luminance, with transparency composited over white,
+/// so antialiasing differences between encoders or platforms score close to 1 while moved or
+/// missing shapes do not.
+pub fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    options: &DiffOptions,
+) -> Result<ImageDiff, ImageDiffError> {
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        return Err(ImageDiffError::SizeMismatch(
+            (expected.width, expected.height),
+            (actual.width, actual.height),
+        ));
+    }
+    let differing_pixels = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(e, a)| {
+            e.iter()
+                .zip(a.iter())
+                .any(|(e, a)| e.abs_diff(*a) > options.channel_tolerance)
+        })
+        .count();
+    let similarity = ssim(expected, actual);
+    Ok(ImageDiff {
+        differing_pixels,
+        total_pixels: expected.width * expected.height,
+        similarity,
+        matches: similarity >= options.min_similarity,
+    })
+}
+
+fn ssim(expected: &RgbaImage, actual: &RgbaImage) -> f64 {
+    let mut total = 0.0;
+    let mut windows = 0;
+    for wy in (0..expected.height).step_by(WINDOW) {
+        for wx in (0..expected.width).step_by(WINDOW) {
+            let coords: Vec<_> = (wy..(wy + WINDOW).min(expected.height))
+                .flat_map(|y| (wx..(wx + WINDOW).min(expected.width)).map(move |x| (x, y)))
+                .collect();
+            let e: Vec<_> = coords.iter().map(|(x, y)| expected.luma(*x, *y)).collect();
+            let a: Vec<_> = coords.iter().map(|(x, y)| actual.luma(*x, *y)).collect();
+            total += window_ssim(&e, &a);
+            windows += 1;
+        }
+    }
+    if windows == 0 {
+        return 1.0;
+    }
+    total / windows as f64
+}
+
+fn window_ssim(e: &[f64], a: &[f64]) -> f64 {
+    let n = e.len() as f64;
+    let mean_e = e.iter().sum::<f64>() / n;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let (mut var_e, mut var_a, mut covar) = (0.0, 0.0, 0.0);
+    for (e, a) in e.iter().zip(a) {
+        var_e += (e - mean_e) * (e - mean_e);
+        var_a += (a - mean_a) * (a - mean_a);
+        covar += (e - mean_e) * (a - mean_a);
+    }
+    let (var_e, var_a, covar) = (var_e / n, var_a / n, covar / n);
+    ((2.0 * mean_e * mean_a + C1) * (2.0 * covar + C2))
+        / ((mean_e * mean_e + mean_a * mean_a + C1) * (var_e + var_a + C2))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::ImageDiffError,
+        imagediff::{compare_images, DiffOptions, RgbaImage},
+    };
+
+    /// A `shade` of grey square on transparent, offset by `dx`
+    fn square(size: usize, dx: usize, shade: u8) -> Vec<u8> {
+        let mut data = vec![0u8; size * size * 4];
+        for y in size / 4..size * 3 / 4 {
+            for x in size / 4 + dx..size * 3 / 4 + dx {
+                let i = (y * size + x) * 4;
+                data[i..i + 4].copy_from_slice(&[shade, shade, shade, 255]);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn identical() {
+        let data = square(32, 0, 0);
+        let image = RgbaImage::new(32, 32, &data).unwrap();
+
+        let diff = compare_images(&image, &image, &DiffOptions::new()).unwrap();
+
+        assert_eq!(0, diff.differing_pixels);
+        assert_eq!(1024, diff.total_pixels);
+        assert_eq!(1.0, diff.similarity);
+        assert!(diff.matches);
+    }
+
+    #[test]
+    fn slight_shade_change_matches() {
+        let (e, a) = (square(32, 0, 128), square(32, 0, 131));
+        let (e, a) = (
+            RgbaImage::new(32, 32, &e).unwrap(),
+            RgbaImage::new(32, 32, &a).unwrap(),
+        );
+
+        let strict = compare_images(&e, &a, &DiffOptions::new()).unwrap();
+        let tolerant =
+            compare_images(&e, &a, &DiffOptions::new().with_channel_tolerance(4)).unwrap();
+
+        assert_eq!(256, strict.differing_pixels);
+        assert!(strict.matches, "{strict:?}");
+        assert_eq!(0, tolerant.differing_pixels);
+    }
+
+    #[test]
+    fn moved_shape_does_not_match() {
+        let (e, a) = (square(32, 0, 0), square(32, 4, 0));
+        let (e, a) = (
+            RgbaImage::new(32, 32, &e).unwrap(),
+            RgbaImage::new(32, 32, &a).unwrap(),
+        );
+
+        let diff = compare_images(&e, &a, &DiffOptions::new()).unwrap();
+
+        assert_eq!(128, diff.differing_pixels);
+        assert!(!diff.matches, "{diff:?}");
+    }
+
+    #[test]
+    fn sizes_must_match() {
+        let (e, a) = (square(32, 0, 0), square(16, 0, 0));
+        let (e, a) = (
+            RgbaImage::new(32, 32, &e).unwrap(),
+            RgbaImage::new(16, 16, &a).unwrap(),
+        );
+
+        assert!(matches!(
+            compare_images(&e, &a, &DiffOptions::new()),
+            Err(ImageDiffError::SizeMismatch((32, 32), (16, 16)))
+        ));
+    }
+
+    #[test]
+    fn length_must_match_size() {
+        assert!(matches!(
+            RgbaImage::new(4, 4, &[0; 60]),
+            Err(ImageDiffError::InvalidLength(4, 4, 60))
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+//! Produces Xcode asset catalog imagesets of icons
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use skrifa::FontRef;
+
+use crate::{
+    error::DrawSvgError,
+    icon2svg::{self, DrawOptions},
+    json,
+};
+
+/// The files of a `<name>.imageset` directory
+#[derive(Debug)]
+pub struct ImageSet {
+    name: String,
+    files: Vec<(String, String)>,
+}
+
+impl ImageSet {
+    /// The directory name Xcode expects, `<name>.imageset`
+    pub fn dir_name(&self) -> String {
+        format!("{}.imageset", self.name)
+    }
+
+    /// File name and content pairs
+    pub fn files(&self) -> &[(String, String)] {
+        &self.files
+    }
+
+    /// Writes the imageset into `parent`, typically an `.xcassets` directory
+    pub fn write_to(&self, parent: &Path) -> io::Result<PathBuf> {
+        let dir = parent.join(self.dir_name());
+        fs::create_dir_all(&dir)?;
+        for (file_name, content) in self.files.iter() {
+            fs::write(dir.join(file_name), content)?;
+        }
+        Ok(dir)
+    }
+}
+
+/// Draws an icon as an imageset named `name` holding a single svg.
+///
+/// The svg is marked to preserve its vector representation, so Xcode scales it for every
+/// display rather than needing 1x/2x/3x renders, and as a template image so it takes the
+/// tint color like SF Symbols do.
+pub fn draw_imageset(
+    font: &FontRef,
+    name: &str,
+    options: &DrawOptions<'_>,
+) -> Result<ImageSet, DrawSvgError> {
+    let svg = icon2svg::draw_icon(font, options)?;
+    let svg_name = format!("{name}.svg");
+    let contents = format!(
+        r#"{{
+  "images" : [
+    {{
+      "filename" : {},
+      "idiom" : "universal"
+    }}
+  ],
+  "info" : {{
+    "author" : "xcode",
+    "version" : 1
+  }},
+  "properties" : {{
+    "preserves-vector-representation" : true,
+    "template-rendering-intent" : "template"
+  }}
+}}
+"#,
+        json::string(&svg_name)
+    );
+    Ok(ImageSet {
+        name: name.to_string(),
+        files: vec![("Contents.json".to_string(), contents), (svg_name, svg)],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        icon2svg::DrawOptions, iconid, imageset::draw_imageset, pathstyle::PathStyle, testdata,
+    };
+
+    fn mail_options(loc: &skrifa::instance::Location) -> DrawOptions<'_> {
+        DrawOptions::new(iconid::MAIL.clone(), 24.0, loc.into(), PathStyle::Unchanged)
+    }
+
+    #[test]
+    fn imageset_contents() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+
+        let imageset = draw_imageset(&font, "mail", &mail_options(&loc)).unwrap();
+
+        assert_eq!("mail.imageset", imageset.dir_name());
+        let files = imageset.files();
+        assert_eq!(
+            vec!["Contents.json", "mail.svg"],
+            files.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>()
+        );
+        let contents: serde_json::Value = serde_json::from_str(&files[0].1).unwrap();
+        assert_eq!("mail.svg", contents["images"][0]["filename"]);
+        assert_eq!(
+            true,
+            contents["properties"]["preserves-vector-representation"]
+        );
+        assert_eq!(testdata::MAIL_SVG.trim(), files[1].1);
+    }
+
+    #[test]
+    fn imageset_write_to() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+        let imageset = draw_imageset(&font, "mail", &mail_options(&loc)).unwrap();
+        let parent = std::env::temp_dir().join(format!("sleipnir-imageset-{}", std::process::id()));
+
+        let dir = imageset.write_to(&parent).unwrap();
+
+        assert_eq!(parent.join("mail.imageset"), dir);
+        assert!(dir.join("Contents.json").is_file());
+        assert!(dir.join("mail.svg").is_file());
+        fs::remove_dir_all(parent).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+//! Pins a variable icon font at one location, for platforms that can't vary fonts
+
+use kurbo::Affine;
+use skrifa::{
+    instance::{LocationRef, Size},
+    raw::{types::FWord, types::UfWord, TableProvider},
+    setting::VariationSetting,
+    FontRef, GlyphId, MetadataProvider, Tag,
+};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        glyf::{Bbox, GlyfLocaBuilder, Glyph, SimpleGlyph},
+        gsub::Gsub,
+        head::Head,
+        hhea::Hhea,
+        hmtx::{Hmtx, LongMetric},
+        maxp::Maxp,
+        os2::Os2,
+    },
+    FontBuilder, NullableOffsetMarker,
+};
+
+use crate::{
+    document::draw_glyph_id,
+    error::{DrawSvgError, InstanceError},
+    iconid::IconIdentifier,
+};
+
+/// Tables that vary the font, or describe how it varies, which a static font doesn't have
+const VARIATION_TABLES: [Tag; 8] = [
+    Tag::new(b"avar"),
+    Tag::new(b"cvar"),
+    Tag::new(b"fvar"),
+    Tag::new(b"gvar"),
+    Tag::new(b"HVAR"),
+    Tag::new(b"MVAR"),
+    Tag::new(b"STAT"),
+    Tag::new(b"VVAR"),
+];
+
+/// Produces a static font drawing every glyph as `font` does at `coords`, user coordinates such
+/// as `[("FILL", 1.0), ("wght", 500.0)]`.
+///
+/// Axes not listed are pinned at their default. Glyphs the font substitutes at that location,
+/// e.g. the filled variant of an icon, are drawn in place of the glyph they substitute so no
+/// location based substitution is left to do. Outlines are rounded to whole units.
+pub fn static_instance<I>(font: &FontRef, coords: I) -> Result<Vec<u8>, InstanceError>
+where
+    I: IntoIterator,
+    I::Item: Into<VariationSetting>,
+{
+    let settings: Vec<VariationSetting> = coords.into_iter().map(Into::into).collect();
+    let location = font.axes().location(settings.iter().copied());
+    let location = LocationRef::from(&location);
+    let read_error = |table| move |e| InstanceError::ReadError(table, e);
+
+    let num_glyphs = font.maxp().map_err(read_error("maxp"))?.num_glyphs();
+    let metrics = font.glyph_metrics(Size::unscaled(), location);
+    let mut glyf_builder = GlyfLocaBuilder::new();
+    let mut bbox: Option<Bbox> = None;
+    let (mut max_points, mut max_contours) = (0u16, 0u16);
+    let mut h_metrics = Vec::with_capacity(num_glyphs as usize);
+    for gid in 0..num_glyphs {
+        let identifier = IconIdentifier::GlyphId(GlyphId::new(gid));
+        let drawn = identifier
+            .resolve(font, &location)
+            .map_err(|e| DrawSvgError::ResolutionError(identifier.clone(), e))?;
+        let glyph = match draw_glyph_id(font, &identifier, drawn, location) {
+            Ok(mut path) if !path.elements().is_empty() => {
+                // Back to Y-up
+                path.apply_affine(Affine::FLIP_Y);
+                SimpleGlyph::from_bezpath(&path)
+                    .map(Glyph::Simple)
+                    .map_err(|e| InstanceError::CompileError(format!("glyph {gid}: {e:?}")))?
+            }
+            Ok(_) | Err(DrawSvgError::NoOutline(..)) => Glyph::Empty,
+            Err(e) => return Err(e.into()),
+        };
+        glyf_builder
+            .add_glyph(&glyph)
+            .map_err(|e| InstanceError::CompileError(format!("glyph {gid}: {e}")))?;
+        let glyph_bbox = glyph.bbox();
+        if let Some(glyph_bbox) = glyph_bbox {
+            bbox = Some(bbox.map(|b| b.union(glyph_bbox)).unwrap_or(glyph_bbox));
+        }
+        if let Glyph::Simple(simple) = &glyph {
+            max_contours = max_contours.max(simple.contours().len() as u16);
+            max_points =
+                max_points.max(simple.contours().iter().map(|c| c.len()).sum::<usize>() as u16);
+        }
+        let advance = metrics
+            .advance_width(drawn)
+            .unwrap_or_default()
+            .round()
+            .max(0.0) as u16;
+        h_metrics.push((advance, glyph_bbox));
+    }
+    let (glyf, loca, loca_format) = glyf_builder.build();
+    let bbox = bbox.unwrap_or_default();
+
+    let mut head: Head = font.head().map_err(read_error("head"))?.to_owned_table();
+    head.x_min = bbox.x_min;
+    head.y_min = bbox.y_min;
+    head.x_max = bbox.x_max;
+    head.y_max = bbox.y_max;
+    head.index_to_loc_format = loca_format as i16;
+
+    // Composites are drawn as simple glyphs
+    let mut maxp: Maxp = font.maxp().map_err(read_error("maxp"))?.to_owned_table();
+    maxp.max_points = Some(max_points);
+    maxp.max_contours = Some(max_contours);
+    maxp.max_composite_points = Some(0);
+    maxp.max_composite_contours = Some(0);
+    maxp.max_component_elements = Some(0);
+    maxp.max_component_depth = Some(0);
+
+    let mut hhea: Hhea = font.hhea().map_err(read_error("hhea"))?.to_owned_table();
+    let lsb = |glyph_bbox: &Option<Bbox>| glyph_bbox.map(|b| b.x_min).unwrap_or_default();
+    let inked = || {
+        h_metrics
+            .iter()
+            .filter_map(|(advance, b)| b.map(|b| (*advance, b)))
+    };
+    hhea.advance_width_max = UfWord::new(h_metrics.iter().map(|m| m.0).max().unwrap_or_default());
+    hhea.min_left_side_bearing =
+        FWord::new(inked().map(|(_, b)| b.x_min).min().unwrap_or_default());
+    hhea.min_right_side_bearing = FWord::new(
+        inked()
+            .map(|(advance, b)| advance as i16 - b.x_max)
+            .min()
+            .unwrap_or_default(),
+    );
+    hhea.x_max_extent = FWord::new(inked().map(|(_, b)| b.x_max).max().unwrap_or_default());
+    hhea.number_of_long_metrics = num_glyphs;
+    let hmtx = Hmtx::new(
+        h_metrics
+            .iter()
+            .map(|(advance, glyph_bbox)| LongMetric::new(*advance, lsb(glyph_bbox)))
+            .collect(),
+        Vec::new(),
+    );
+
+    let mut builder = FontBuilder::new();
+    builder
+        .add_table(&glyf)
+        .and_then(|b| b.add_table(&loca))
+        .and_then(|b| b.add_table(&head))
+        .and_then(|b| b.add_table(&maxp))
+        .and_then(|b| b.add_table(&hhea))
+        .and_then(|b| b.add_table(&hmtx))
+        .map_err(|e| InstanceError::CompileError(e.to_string()))?;
+    if let Ok(os2) = font.os2() {
+        let mut os2: Os2 = os2.to_owned_table();
+        if let Some(wght) = font.axes().get_by_tag(Tag::new(b"wght")) {
+            let value = settings
+                .iter()
+                .rev()
+                .find(|setting| setting.selector == wght.tag())
+                .map(|setting| setting.value.clamp(wght.min_value(), wght.max_value()))
+                .unwrap_or(wght.default_value());
+            os2.us_weight_class = value.round().clamp(1.0, 1000.0) as u16;
+        }
+        builder
+            .add_table(&os2)
+            .map_err(|e| InstanceError::CompileError(e.to_string()))?;
+    }
+    if let Ok(gsub) = font.gsub() {
+        // Substitutions at the location are baked into the outlines
+        let mut gsub: Gsub = gsub.to_owned_table();
+        gsub.feature_variations = NullableOffsetMarker::new(None);
+        builder
+            .add_table(&gsub)
+            .map_err(|e| InstanceError::CompileError(e.to_string()))?;
+    }
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        if builder.contains(tag) || VARIATION_TABLES.contains(&tag) {
+            continue;
+        }
+        if let Some(data) = font.table_data(tag) {
+            builder.add_raw(tag, data.as_bytes().to_vec());
+        }
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{PathEl, Shape};
+    use skrifa::{
+        instance::{LocationRef, Size},
+        raw::TableProvider,
+        FontRef, GlyphId, MetadataProvider, Tag,
+    };
+
+    use crate::{
+        icon2svg::{draw_icon_path, DrawOptions},
+        iconid::{self, IconIdentifier},
+        instance::static_instance,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    fn assert_draws_like(
+        instance: &FontRef,
+        font: &FontRef,
+        location: LocationRef,
+        identifier: &IconIdentifier,
+    ) {
+        let draw = |font, location| {
+            draw_icon_path(
+                font,
+                &DrawOptions::new(identifier.clone(), 24.0, location, PathStyle::Unchanged),
+            )
+            .unwrap()
+            .0
+        };
+        let expected = draw(font, location);
+        let actual = draw(instance, LocationRef::default());
+        assert!(
+            (expected.area() - actual.area()).abs() < expected.area().abs() * 0.01,
+            "{identifier:?} {} {}",
+            expected.area(),
+            actual.area()
+        );
+        let moves = |path: &kurbo::BezPath| {
+            path.elements()
+                .iter()
+                .filter(|el| matches!(el, PathEl::MoveTo(..)))
+                .count()
+        };
+        assert_eq!(moves(&expected), moves(&actual), "{identifier:?}");
+    }
+
+    #[test]
+    fn pins_every_axis() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let coords = [("FILL", 1.0), ("wght", 700.0)];
+        let location = font.axes().location(coords);
+
+        let data = static_instance(&font, coords).unwrap();
+        let instance = FontRef::new(&data).unwrap();
+
+        assert!(instance.axes().is_empty());
+        for tag in [b"fvar", b"gvar", b"avar", b"HVAR", b"STAT"] {
+            assert!(instance.table_data(Tag::new(tag)).is_none());
+        }
+        assert_eq!(700, instance.os2().unwrap().us_weight_class());
+        // The filled mail is a different glyph, drawn in place of the outlined one
+        for identifier in [&iconid::MAIL, &iconid::LAN] {
+            assert_draws_like(&instance, &font, (&location).into(), identifier);
+        }
+        let gid = iconid::LAN
+            .resolve(&font, &(&location).into())
+            .unwrap_or(GlyphId::NOTDEF);
+        assert_eq!(
+            font.glyph_metrics(Size::unscaled(), &location)
+                .advance_width(gid)
+                .map(f32::round),
+            instance
+                .glyph_metrics(Size::unscaled(), LocationRef::default())
+                .advance_width(gid)
+        );
+    }
+
+    #[test]
+    fn unlisted_axes_at_default() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let data = static_instance(&font, [("ZZZZ", 1.0)]).unwrap();
+        let instance = FontRef::new(&data).unwrap();
+
+        assert_eq!(400, instance.os2().unwrap().us_weight_class());
+        assert_draws_like(&instance, &font, LocationRef::default(), &iconid::MAIL);
+    }
+}
@@ -0,0 +1,29 @@
+//! Helpers for writing JSON by hand, for output whose layout we want to control
+
+/// `value` as a quoted JSON string
+pub(crate) fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::string;
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(r#""a\"b\\c\u000a""#, string("a\"b\\c\n"));
+        let value: serde_json::Value = serde_json::from_str(&string("é\t")).unwrap();
+        assert_eq!("é\t", value);
+    }
+}
@@ -1,10 +1,53 @@
+#[cfg(feature = "zip")]
+pub mod archive;
+pub mod axes;
+pub mod batch;
+pub mod bitmap;
+pub mod cache;
 pub mod cmp;
+pub mod codegen;
+pub mod compose;
+mod document;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gallery;
+pub mod icon2avd;
+pub mod icon2css;
+pub mod icon2eps;
+pub mod icon2lottie;
+pub mod icon2pdf;
 pub mod icon2svg;
+pub mod icon2swift;
+pub mod icon2xml;
 pub mod iconid;
+pub mod icons2sprite;
+pub mod imagediff;
+pub mod imageset;
+#[cfg(feature = "write-fonts")]
+pub mod instance;
+mod json;
 pub mod ligatures;
+mod overlap;
+pub mod owned;
 pub mod pathstyle;
 mod pens;
+pub mod quality;
+pub mod release;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "write-fonts")]
+pub mod subset;
+#[cfg(feature = "write-fonts")]
+pub mod svg2font;
+pub mod sweep;
+pub mod text;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod webfont;
+pub mod xml;
+
+pub use error::Error;
 
 /// Setup to match fontations/font-test-data because that rig works for google3
 #[cfg(test)]
@@ -1,14 +1,26 @@
 //! Helpers for working with layout
 
+use std::{collections::HashMap, iter::once};
+
 use skrifa::{
     raw::{
-        tables::gsub::{Ligature, LigatureSubstFormat1, SubstitutionSubtables},
-        FontRef, TableProvider,
+        tables::{
+            gsub::{
+                Gsub, Ligature, LigatureSubstFormat1, SingleSubst, SubstitutionLookupList,
+                SubstitutionSubtables,
+            },
+            layout::{
+                ChainedSequenceContext, ClassDef, CoverageTable, SequenceContext,
+                SequenceLookupRecord,
+            },
+        },
+        types::BigEndian,
+        FontRef, ReadError, TableProvider, TopLevelTable,
     },
-    GlyphId, MetadataProvider,
+    GlyphId, MetadataProvider, Tag,
 };
 
-use crate::error::IconResolutionError;
+use crate::{error::IconResolutionError, iconid::is_pua};
 
 pub trait Ligatures {
     /// Exposes the complete set of ligature substitution tables in the font
@@ -21,6 +33,16 @@ pub trait Ligatures {
     ///
     /// Meant for use with icon names in contexts where speed is not essential.
     fn resolve_ligature(&self, name: &str) -> Result<Option<GlyphId>, IconResolutionError>;
+
+    /// The glyph a ligature substitutes for the sequence `gids`, if there is one
+    fn resolve_ligature_glyphs(&self, gids: &[GlyphId]) -> Option<GlyphId>;
+
+    /// Every ligature as the string that produces it and the glyph it produces, in the order
+    /// of [Ligatures::ligatures]
+    ///
+    /// Components are decoded through the non-PUA entries of cmap, a component with none is
+    /// an error.
+    fn ligature_strings(&self) -> Result<Vec<(String, GlyphId)>, IconResolutionError>;
 }
 
 impl<'a> Ligatures for FontRef<'a> {
@@ -51,27 +73,52 @@ impl<'a> Ligatures for FontRef<'a> {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let Some(first) = gids.first() else {
+        if gids.is_empty() {
             return Err(IconResolutionError::NoGlyphIds(name.to_string()));
-        };
-        let gids = &gids[1..];
+        }
+        Ok(self.resolve_ligature_glyphs(&gids))
+    }
 
+    fn resolve_ligature_glyphs(&self, gids: &[GlyphId]) -> Option<GlyphId> {
+        let (first, rest) = gids.split_first()?;
         for (liga_first, liga) in self.ligatures() {
             if liga_first != *first {
                 continue;
             }
-            if liga.component_count() as usize != gids.len() + 1 {
+            if liga.component_count() as usize != rest.len() + 1 {
                 continue;
             }
-            if gids
+            if rest
                 .iter()
                 .zip(liga.component_glyph_ids())
                 .all(|(gid, component)| *gid == component.get())
             {
-                return Ok(Some(liga.ligature_glyph())); // We found it!
+                return Some(liga.ligature_glyph()); // We found it!
             }
         }
-        Ok(None)
+        // The ligature may only be reached through contextual lookups, e.g. that substitute
+        // the components first
+        let shaped = shape_glyphs(self, gids, &[]).ok()?;
+        match shaped.as_slice() {
+            [gid] if gids.len() > 1 => Some(*gid),
+            _ => None,
+        }
+    }
+
+    fn ligature_strings(&self) -> Result<Vec<(String, GlyphId)>, IconResolutionError> {
+        let rev_non_pua_cmap = reverse_non_pua_cmap(self);
+        self.ligatures()
+            .map(|(liga_first, liga)| {
+                Ok((
+                    build_ligature_string(
+                        liga_first,
+                        liga.component_glyph_ids(),
+                        &rev_non_pua_cmap,
+                    )?,
+                    liga.ligature_glyph(),
+                ))
+            })
+            .collect()
     }
 
     fn ligatures(&self) -> impl Iterator<Item = (GlyphId, Ligature<'_>)> {
@@ -97,3 +144,518 @@ impl<'a> Ligatures for FontRef<'a> {
             })
     }
 }
+
+/// Substitutes `gids` as a shaper would with the [DEFAULT_FEATURES] and `features` on, for
+/// icon names routed through contextual lookups and for text.
+///
+/// Lookups apply in lookup list order, each across the whole run. Single, ligature and
+/// contextual substitutions of every format are applied, other lookup types and lookup flags
+/// are ignored.
+pub(crate) fn shape_glyphs(
+    font: &FontRef,
+    gids: &[GlyphId],
+    features: &[Tag],
+) -> Result<Vec<GlyphId>, ReadError> {
+    let mut buffer = gids.to_vec();
+    if font.table_data(Gsub::TAG).is_none() {
+        return Ok(buffer);
+    }
+    let gsub = font.gsub()?;
+    let lookups = gsub.lookup_list()?;
+    let feature_list = gsub.feature_list()?;
+    let mut lookup_indices = Vec::new();
+    for record in feature_list.feature_records() {
+        let tag = record.feature_tag();
+        if DEFAULT_FEATURES.contains(&tag) || features.contains(&tag) {
+            let feature = record.feature(feature_list.offset_data())?;
+            lookup_indices.extend(feature.lookup_list_indices().iter().map(|idx| idx.get()));
+        }
+    }
+    lookup_indices.sort_unstable();
+    lookup_indices.dedup();
+
+    for lookup_index in lookup_indices {
+        let mut pos = 0;
+        while pos < buffer.len() {
+            pos += apply_lookup(&lookups, lookup_index, &mut buffer, pos, 0)?.unwrap_or(1);
+        }
+    }
+    Ok(buffer)
+}
+
+// Features a shaper turns on by default that substitute glyphs
+const DEFAULT_FEATURES: [Tag; 7] = [
+    Tag::new(b"ccmp"),
+    Tag::new(b"locl"),
+    Tag::new(b"rlig"),
+    Tag::new(b"rclt"),
+    Tag::new(b"calt"),
+    Tag::new(b"liga"),
+    Tag::new(b"clig"),
+];
+
+// Contextual lookups may invoke each other, this bounds how deeply
+const MAX_NESTING: usize = 8;
+
+/// Applies one lookup at `pos`, returning how many glyphs of the result it covered if it applied
+fn apply_lookup(
+    lookups: &SubstitutionLookupList,
+    lookup_index: u16,
+    buffer: &mut Vec<GlyphId>,
+    pos: usize,
+    depth: usize,
+) -> Result<Option<usize>, ReadError> {
+    if depth > MAX_NESTING || pos >= buffer.len() {
+        return Ok(None);
+    }
+    let gid = buffer[pos];
+    match lookups.lookups().get(lookup_index as usize)?.subtables()? {
+        SubstitutionSubtables::Single(tables) => {
+            for single in tables.iter() {
+                if let Some(substitute) = single_substitute(&single?, gid)? {
+                    buffer[pos] = substitute;
+                    return Ok(Some(1));
+                }
+            }
+        }
+        SubstitutionSubtables::Ligature(tables) => {
+            for table in tables.iter() {
+                let table = table?;
+                let Some(set_index) = table.coverage()?.get(gid) else {
+                    continue;
+                };
+                let set = table.ligature_sets().get(set_index as usize)?;
+                for liga in set.ligatures().iter() {
+                    let liga = liga?;
+                    let components = liga.component_glyph_ids();
+                    let end = pos + 1 + components.len();
+                    if end <= buffer.len()
+                        && components
+                            .iter()
+                            .zip(&buffer[pos + 1..end])
+                            .all(|(component, gid)| component.get() == *gid)
+                    {
+                        buffer.splice(pos..end, [liga.ligature_glyph()]);
+                        return Ok(Some(1));
+                    }
+                }
+            }
+        }
+        SubstitutionSubtables::Contextual(tables) => {
+            for table in tables.iter() {
+                if let Some(len) = apply_sequence_context(lookups, &table?, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        SubstitutionSubtables::ChainContextual(tables) => {
+            for table in tables.iter() {
+                if let Some(len) = apply_chained_context(lookups, &table?, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        // Multiple, alternate and reverse chaining substitutions don't form icon names
+        _ => (),
+    }
+    Ok(None)
+}
+
+/// The glyph `single` substitutes for `gid`, if it covers it
+pub(crate) fn single_substitute(
+    single: &SingleSubst,
+    gid: GlyphId,
+) -> Result<Option<GlyphId>, ReadError> {
+    Ok(match single {
+        SingleSubst::Format1(single) => single
+            .coverage()?
+            .get(gid)
+            .map(|_| GlyphId::new((gid.to_u16() as i32 + single.delta_glyph_id() as i32) as u16)),
+        SingleSubst::Format2(single) => single.coverage()?.get(gid).map(|coverage_idx| {
+            single
+                .substitute_glyph_ids()
+                .get(coverage_idx as usize)
+                .map(|be| be.get())
+                .unwrap_or(gid)
+        }),
+    })
+}
+
+/// Matches one glyph of a contextual rule
+enum Matcher<'a, 'b> {
+    Glyph(GlyphId),
+    Class(&'b ClassDef<'a>, u16),
+    Coverage(CoverageTable<'a>),
+}
+
+impl Matcher<'_, '_> {
+    fn matches(&self, gid: GlyphId) -> bool {
+        match self {
+            Matcher::Glyph(expected) => *expected == gid,
+            Matcher::Class(class_def, class) => class_def.get(gid) == *class,
+            Matcher::Coverage(coverage) => coverage.get(gid).is_some(),
+        }
+    }
+}
+
+fn glyphs<'a, 'b>(gids: &[BigEndian<GlyphId>]) -> Vec<Matcher<'a, 'b>> {
+    gids.iter().map(|gid| Matcher::Glyph(gid.get())).collect()
+}
+
+fn classes<'a, 'b>(
+    class_def: &'b ClassDef<'a>,
+    classes: &[BigEndian<u16>],
+) -> Vec<Matcher<'a, 'b>> {
+    classes
+        .iter()
+        .map(|class| Matcher::Class(class_def, class.get()))
+        .collect()
+}
+
+fn coverages<'a, 'b>(
+    coverages: impl Iterator<Item = Result<CoverageTable<'a>, ReadError>>,
+) -> Result<Vec<Matcher<'a, 'b>>, ReadError> {
+    coverages
+        .map(|coverage| Ok(Matcher::Coverage(coverage?)))
+        .collect()
+}
+
+/// The glyphs before `pos` nearest first, those after the glyph at `pos` and those after that
+struct Rule<'m, 'a, 'b> {
+    backtrack: &'m [Matcher<'a, 'b>],
+    input: &'m [Matcher<'a, 'b>],
+    lookahead: &'m [Matcher<'a, 'b>],
+    records: &'m [SequenceLookupRecord],
+}
+
+/// Applies the rule's lookups if it matches at `pos`, the glyph at `pos` is already matched
+fn apply_rule(
+    lookups: &SubstitutionLookupList,
+    rule: Rule,
+    buffer: &mut Vec<GlyphId>,
+    pos: usize,
+    depth: usize,
+) -> Result<Option<usize>, ReadError> {
+    let input_end = pos + 1 + rule.input.len();
+    let matched = pos >= rule.backtrack.len()
+        && input_end + rule.lookahead.len() <= buffer.len()
+        && rule
+            .backtrack
+            .iter()
+            .enumerate()
+            .all(|(i, matcher)| matcher.matches(buffer[pos - 1 - i]))
+        && rule
+            .input
+            .iter()
+            .zip(&buffer[pos + 1..input_end])
+            .all(|(matcher, gid)| matcher.matches(*gid))
+        && rule
+            .lookahead
+            .iter()
+            .zip(&buffer[input_end..])
+            .all(|(matcher, gid)| matcher.matches(*gid));
+    if !matched {
+        return Ok(None);
+    }
+    // Ligatures within the input shorten it
+    let mut len = rule.input.len() + 1;
+    for record in rule.records {
+        let before = buffer.len();
+        apply_lookup(
+            lookups,
+            record.lookup_list_index(),
+            buffer,
+            pos + record.sequence_index() as usize,
+            depth + 1,
+        )?;
+        len = (len + buffer.len()).saturating_sub(before);
+    }
+    Ok(Some(len.max(1)))
+}
+
+fn apply_sequence_context(
+    lookups: &SubstitutionLookupList,
+    context: &SequenceContext,
+    buffer: &mut Vec<GlyphId>,
+    pos: usize,
+    depth: usize,
+) -> Result<Option<usize>, ReadError> {
+    let gid = buffer[pos];
+    match context {
+        SequenceContext::Format1(table) => {
+            let Some(set_index) = table.coverage()?.get(gid) else {
+                return Ok(None);
+            };
+            let Some(set) = table.seq_rule_sets().get(set_index as usize).transpose()? else {
+                return Ok(None);
+            };
+            for rule in set.seq_rules().iter() {
+                let rule = rule?;
+                let input = glyphs(rule.input_sequence());
+                let rule = Rule {
+                    backtrack: &[],
+                    input: &input,
+                    lookahead: &[],
+                    records: rule.seq_lookup_records(),
+                };
+                if let Some(len) = apply_rule(lookups, rule, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        SequenceContext::Format2(table) => {
+            if table.coverage()?.get(gid).is_none() {
+                return Ok(None);
+            }
+            let class_def = table.class_def()?;
+            let set_index = class_def.get(gid) as usize;
+            let Some(set) = table.class_seq_rule_sets().get(set_index).transpose()? else {
+                return Ok(None);
+            };
+            for rule in set.class_seq_rules().iter() {
+                let rule = rule?;
+                let input = classes(&class_def, rule.input_sequence());
+                let rule = Rule {
+                    backtrack: &[],
+                    input: &input,
+                    lookahead: &[],
+                    records: rule.seq_lookup_records(),
+                };
+                if let Some(len) = apply_rule(lookups, rule, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        SequenceContext::Format3(table) => {
+            let input = coverages(table.coverages().iter())?;
+            let Some((first, input)) = input.split_first() else {
+                return Ok(None);
+            };
+            if !first.matches(gid) {
+                return Ok(None);
+            }
+            let rule = Rule {
+                backtrack: &[],
+                input,
+                lookahead: &[],
+                records: table.seq_lookup_records(),
+            };
+            return apply_rule(lookups, rule, buffer, pos, depth);
+        }
+    }
+    Ok(None)
+}
+
+fn apply_chained_context(
+    lookups: &SubstitutionLookupList,
+    context: &ChainedSequenceContext,
+    buffer: &mut Vec<GlyphId>,
+    pos: usize,
+    depth: usize,
+) -> Result<Option<usize>, ReadError> {
+    let gid = buffer[pos];
+    match context {
+        ChainedSequenceContext::Format1(table) => {
+            let Some(set_index) = table.coverage()?.get(gid) else {
+                return Ok(None);
+            };
+            let Some(set) = table
+                .chained_seq_rule_sets()
+                .get(set_index as usize)
+                .transpose()?
+            else {
+                return Ok(None);
+            };
+            for rule in set.chained_seq_rules().iter() {
+                let rule = rule?;
+                let backtrack = glyphs(rule.backtrack_sequence());
+                let input = glyphs(rule.input_sequence());
+                let lookahead = glyphs(rule.lookahead_sequence());
+                let rule = Rule {
+                    backtrack: &backtrack,
+                    input: &input,
+                    lookahead: &lookahead,
+                    records: rule.seq_lookup_records(),
+                };
+                if let Some(len) = apply_rule(lookups, rule, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        ChainedSequenceContext::Format2(table) => {
+            if table.coverage()?.get(gid).is_none() {
+                return Ok(None);
+            }
+            let backtrack_class_def = table.backtrack_class_def()?;
+            let input_class_def = table.input_class_def()?;
+            let lookahead_class_def = table.lookahead_class_def()?;
+            let set_index = input_class_def.get(gid) as usize;
+            let Some(set) = table
+                .chained_class_seq_rule_sets()
+                .get(set_index)
+                .transpose()?
+            else {
+                return Ok(None);
+            };
+            for rule in set.chained_class_seq_rules().iter() {
+                let rule = rule?;
+                let backtrack = classes(&backtrack_class_def, rule.backtrack_sequence());
+                let input = classes(&input_class_def, rule.input_sequence());
+                let lookahead = classes(&lookahead_class_def, rule.lookahead_sequence());
+                let rule = Rule {
+                    backtrack: &backtrack,
+                    input: &input,
+                    lookahead: &lookahead,
+                    records: rule.seq_lookup_records(),
+                };
+                if let Some(len) = apply_rule(lookups, rule, buffer, pos, depth)? {
+                    return Ok(Some(len));
+                }
+            }
+        }
+        ChainedSequenceContext::Format3(table) => {
+            let input = coverages(table.input_coverages().iter())?;
+            let Some((first, input)) = input.split_first() else {
+                return Ok(None);
+            };
+            if !first.matches(gid) {
+                return Ok(None);
+            }
+            let backtrack = coverages(table.backtrack_coverages().iter())?;
+            let lookahead = coverages(table.lookahead_coverages().iter())?;
+            let rule = Rule {
+                backtrack: &backtrack,
+                input,
+                lookahead: &lookahead,
+                records: table.seq_lookup_records(),
+            };
+            return apply_rule(lookups, rule, buffer, pos, depth);
+        }
+    }
+    Ok(None)
+}
+
+/// Maps glyphs to a non-PUA codepoint, the highest if there are several
+pub(crate) fn reverse_non_pua_cmap(font: &FontRef) -> HashMap<GlyphId, u32> {
+    font.charmap()
+        .mappings()
+        .filter(|(codepoint, _)| !is_pua(*codepoint))
+        .map(|(codepoint, gid)| (gid, codepoint))
+        .collect()
+}
+
+fn build_ligature_string(
+    first_gid: GlyphId,
+    gids: &[BigEndian<GlyphId>],
+    rev_non_pua_cmap: &HashMap<GlyphId, u32>,
+) -> Result<String, IconResolutionError> {
+    once(first_gid)
+        .chain(gids.iter().map(|g| g.get()))
+        .map(|gid| gid_to_char(&gid, rev_non_pua_cmap))
+        .collect()
+}
+
+pub(crate) fn gid_to_char(
+    gid: &GlyphId,
+    rev_non_pua_cmap: &HashMap<GlyphId, u32>,
+) -> Result<char, IconResolutionError> {
+    let codepoint = *rev_non_pua_cmap
+        .get(gid)
+        .ok_or_else(|| IconResolutionError::NoCmapEntryForGid(gid.to_u32()))?;
+    char::from_u32(codepoint).ok_or(IconResolutionError::InvalidCharacter(codepoint))
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, GlyphId, MetadataProvider};
+    use write_fonts::{
+        tables::{
+            gsub::{
+                Gsub, Ligature, LigatureSet, LigatureSubstFormat1, SingleSubst, SubstitutionLookup,
+                SubstitutionLookupList,
+            },
+            layout::{
+                ChainedSequenceContext, CoverageTable, Feature, FeatureList, FeatureRecord, Lookup,
+                LookupFlag, ScriptList, SequenceLookupRecord,
+            },
+        },
+        types::Tag,
+        FontBuilder,
+    };
+
+    use crate::{ligatures::Ligatures, testdata};
+
+    /// Replaces GSUB so "lan" only reaches its icon, gid 3, through a chaining contextual
+    /// lookup: 'l' before "an" becomes gid 5, then gid 5 "an" ligates
+    fn rebuild_font_with_contextual_ligature(fontdata: &[u8]) -> Vec<u8> {
+        let font = FontRef::new(fontdata).unwrap();
+        let [l, a, n] = ['l', 'a', 'n'].map(|c| font.charmap().map(c).unwrap());
+        let coverage = |gid| CoverageTable::format_1(vec![gid]);
+        let lookups = SubstitutionLookupList::new(vec![
+            SubstitutionLookup::ChainContextual(
+                Lookup::new(
+                    LookupFlag::empty(),
+                    vec![ChainedSequenceContext::format_3(
+                        vec![],
+                        vec![coverage(l)],
+                        vec![coverage(a), coverage(n)],
+                        vec![SequenceLookupRecord::new(0, 1)],
+                    )],
+                    0,
+                )
+                .into_concrete(),
+            ),
+            SubstitutionLookup::Single(Lookup::new(
+                LookupFlag::empty(),
+                vec![SingleSubst::format_1(coverage(l), 5 - l.to_u16() as i16)],
+                0,
+            )),
+            SubstitutionLookup::Ligature(Lookup::new(
+                LookupFlag::empty(),
+                vec![LigatureSubstFormat1::new(
+                    coverage(GlyphId::new(5)),
+                    vec![LigatureSet::new(vec![Ligature::new(
+                        GlyphId::new(3),
+                        vec![a, n],
+                    )])],
+                )],
+                0,
+            )),
+        ]);
+        let features = FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"liga"),
+            Feature::new(None, vec![0, 2]),
+        )]);
+        let gsub = Gsub::new(ScriptList::default(), features, lookups);
+        FontBuilder::new()
+            .add_table(&gsub)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build()
+    }
+
+    #[test]
+    fn ligature_strings() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let strings = font.ligature_strings().unwrap();
+
+        assert!(
+            strings.contains(&("lan".to_string(), GlyphId::new(3))),
+            "{strings:?}"
+        );
+        assert_eq!(Some(GlyphId::new(3)), font.resolve_ligature("lan").unwrap());
+        for (string, gid) in strings {
+            assert_eq!(Some(gid), font.resolve_ligature(&string).unwrap());
+        }
+    }
+
+    #[test]
+    fn ligature_through_chained_context() {
+        let font_data = rebuild_font_with_contextual_ligature(testdata::ICON_FONT);
+        let font = FontRef::new(&font_data).unwrap();
+
+        assert_eq!(Some(GlyphId::new(3)), font.resolve_ligature("lan").unwrap());
+        assert_eq!(None, font.resolve_ligature("la").unwrap());
+    }
+}
@@ -0,0 +1,369 @@
+//! Removes overlapping contours, for consumers that fill even-odd rather than nonzero
+
+use std::collections::HashMap;
+
+use kurbo::{flatten, BezPath, PathEl, Point, Shape, Vec2};
+
+// Overlapping curves are flattened to lines within this many font units
+const FLATTEN_TOLERANCE: f64 = 0.1;
+// How far to either side of an edge to test which side is filled, in font units
+const SIDE_OFFSET: f64 = 1e-4;
+// Parameters this close to the end of a segment are at the end
+const EPSILON: f64 = 1e-9;
+
+/// An edge of a flattened contour
+#[derive(Clone, Copy)]
+struct Segment {
+    contour: usize,
+    start: Point,
+    end: Point,
+}
+
+/// Rewrites `path` so no contour overlaps another or itself, filling the same area as `path`
+/// does under nonzero winding.
+///
+/// Contours that cross nothing are kept as they are, curves included, unless they sit within
+/// already filled area. Those that cross are replaced by the outline of their union, flattened
+/// to lines. Contours are wound as the bulk of `path` is, holes the other way.
+pub(crate) fn remove_overlaps(path: &BezPath) -> BezPath {
+    let contours = contours(path);
+    let polylines: Vec<Vec<Point>> = contours.iter().map(polyline).collect();
+    let segments: Vec<Segment> = polylines
+        .iter()
+        .enumerate()
+        .flat_map(|(contour, points)| {
+            let n = points.len();
+            (0..n).map(move |i| Segment {
+                contour,
+                start: points[i],
+                end: points[(i + 1) % n],
+            })
+        })
+        .filter(|segment| segment.start != segment.end)
+        .collect();
+
+    let mut splits: Vec<Vec<(f64, Point)>> = vec![Vec::new(); segments.len()];
+    let mut crossed = vec![false; contours.len()];
+    for i in 0..segments.len() {
+        for j in i + 1..segments.len() {
+            if split_at_intersections(&segments, i, j, &mut splits) {
+                crossed[segments[i].contour] = true;
+                crossed[segments[j].contour] = true;
+            }
+        }
+    }
+
+    let area: f64 = polylines.iter().map(|points| polyline_area(points)).sum();
+    let mut result = BezPath::new();
+    for (i, contour) in contours.iter().enumerate() {
+        if crossed[i] {
+            continue;
+        }
+        let Some(first) = segments.iter().find(|segment| segment.contour == i) else {
+            continue;
+        };
+        // A contour crossing nothing is all boundary or not boundary at all
+        match filled_side(&polylines, first.start, first.end) {
+            Some(true) => result.extend(contour.iter()),
+            Some(false) => result.extend(contour.reverse_subpaths().iter()),
+            None => (),
+        }
+    }
+
+    // Split crossing contours where they cross, keep the edges between filled and unfilled
+    let mut edges: Vec<(Point, Point)> = Vec::new();
+    let mut seen = HashMap::new();
+    for (segment, mut points) in segments.iter().zip(splits) {
+        if !crossed[segment.contour] {
+            continue;
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let points: Vec<Point> = std::iter::once(segment.start)
+            .chain(points.into_iter().map(|(_, p)| p))
+            .chain(std::iter::once(segment.end))
+            .collect();
+        for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            // Coincident edges of different contours bound the same area once
+            let key = if key(start) < key(end) {
+                (key(start), key(end))
+            } else {
+                (key(end), key(start))
+            };
+            if start == end || seen.insert(key, ()).is_some() {
+                continue;
+            }
+            match filled_side(&polylines, start, end) {
+                Some(true) => edges.push((start, end)),
+                Some(false) => edges.push((end, start)),
+                None => (),
+            }
+        }
+    }
+    for contour in link(&edges) {
+        let contour = drop_collinear(contour);
+        if contour.len() < 3 {
+            continue;
+        }
+        result.move_to(contour[0]);
+        for p in contour[1..].iter() {
+            result.line_to(*p);
+        }
+        result.close_path();
+    }
+
+    // Edges were oriented with the fill on their left, match the winding of the input
+    if area != 0.0 && (area > 0.0) != (result.area() > 0.0) {
+        result = result.reverse_subpaths();
+    }
+    result
+}
+
+fn contours(path: &BezPath) -> Vec<BezPath> {
+    let mut contours: Vec<BezPath> = Vec::new();
+    for el in path.elements() {
+        if matches!(el, PathEl::MoveTo(..)) || contours.is_empty() {
+            contours.push(BezPath::new());
+        }
+        if let Some(contour) = contours.last_mut() {
+            contour.push(*el);
+        }
+    }
+    contours
+}
+
+/// The contour flattened, implicitly closed
+fn polyline(contour: &BezPath) -> Vec<Point> {
+    let mut points: Vec<Point> = Vec::new();
+    flatten(contour.iter(), FLATTEN_TOLERANCE, |el| match el {
+        PathEl::MoveTo(p) | PathEl::LineTo(p) if points.last() != Some(&p) => points.push(p),
+        _ => (),
+    });
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+fn polyline_area(points: &[Point]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].to_vec2().cross(points[(i + 1) % n].to_vec2()))
+        .sum::<f64>()
+        / 2.0
+}
+
+/// The nonzero winding number of `p` across all the closed polylines
+fn winding(polylines: &[Vec<Point>], p: Point) -> i32 {
+    let mut winding = 0;
+    for points in polylines {
+        let n = points.len();
+        for i in 0..n {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            let side = (b - a).cross(p - a);
+            if a.y <= p.y && b.y > p.y && side > 0.0 {
+                winding += 1;
+            } else if b.y <= p.y && a.y > p.y && side < 0.0 {
+                winding -= 1;
+            }
+        }
+    }
+    winding
+}
+
+/// Whether the filled side of the edge is to its left, None if it isn't a boundary
+fn filled_side(polylines: &[Vec<Point>], start: Point, end: Point) -> Option<bool> {
+    let direction = (end - start).normalize();
+    let left = Vec2::new(-direction.y, direction.x) * SIDE_OFFSET;
+    let mid = start.midpoint(end);
+    let left_filled = winding(polylines, mid + left) != 0;
+    let right_filled = winding(polylines, mid - left) != 0;
+    (left_filled != right_filled).then_some(left_filled)
+}
+
+/// Records where segments `i` and `j` cross or touch, returning whether they do
+fn split_at_intersections(
+    segments: &[Segment],
+    i: usize,
+    j: usize,
+    splits: &mut [Vec<(f64, Point)>],
+) -> bool {
+    let (p, q) = (segments[i], segments[j]);
+    let (r, s) = (p.end - p.start, q.end - q.start);
+    if p.start.x.max(p.end.x) < q.start.x.min(q.end.x)
+        || q.start.x.max(q.end.x) < p.start.x.min(p.end.x)
+        || p.start.y.max(p.end.y) < q.start.y.min(q.end.y)
+        || q.start.y.max(q.end.y) < p.start.y.min(p.end.y)
+    {
+        return false;
+    }
+    let interior = |t: f64| t > EPSILON && t < 1.0 - EPSILON;
+    let at_end = |t: f64| t.abs() <= EPSILON || (t - 1.0).abs() <= EPSILON;
+    let denominator = r.cross(s);
+    let mut split = false;
+    if denominator.abs() > EPSILON * r.hypot() * s.hypot() {
+        let t = (q.start - p.start).cross(s) / denominator;
+        let u = (q.start - p.start).cross(r) / denominator;
+        if interior(t) && interior(u) {
+            // Both sides split at the very same point so the pieces join up exactly
+            let point = p.start + r * t;
+            splits[i].push((t, point));
+            splits[j].push((u, point));
+            split = true;
+        } else if interior(t) && at_end(u) {
+            splits[i].push((t, if u < 0.5 { q.start } else { q.end }));
+            split = true;
+        } else if interior(u) && at_end(t) {
+            splits[j].push((u, if t < 0.5 { p.start } else { p.end }));
+            split = true;
+        }
+    } else if (q.start - p.start).cross(r).abs() <= EPSILON * r.hypot() * r.hypot().max(1.0) {
+        // Collinear, each splits where the other ends
+        for (a, b, target) in [(p, q, i), (q, p, j)] {
+            let d = a.end - a.start;
+            for end in [b.start, b.end] {
+                let t = (end - a.start).dot(d) / d.hypot2();
+                if interior(t) {
+                    splits[target].push((t, end));
+                    split = true;
+                }
+            }
+        }
+    }
+    split
+}
+
+fn key(p: Point) -> (u64, u64) {
+    // Adding 0.0 turns -0.0 into 0.0
+    ((p.x + 0.0).to_bits(), (p.y + 0.0).to_bits())
+}
+
+/// Joins edges end to start into closed loops
+fn link(edges: &[(Point, Point)]) -> Vec<Vec<Point>> {
+    let mut by_start: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, (start, _)) in edges.iter().enumerate() {
+        by_start.entry(key(*start)).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut loops = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let origin = edges[first].0;
+        let mut points = vec![origin];
+        let mut at = edges[first].1;
+        while at != origin {
+            let next = by_start
+                .get(&key(at))
+                .and_then(|candidates| candidates.iter().find(|i| !used[**i]));
+            let Some(&next) = next else {
+                // Unclosed, the input was degenerate
+                points.clear();
+                break;
+            };
+            used[next] = true;
+            points.push(at);
+            at = edges[next].1;
+        }
+        if !points.is_empty() {
+            loops.push(points);
+        }
+    }
+    loops
+}
+
+/// Drops points in the middle of straight runs, left by splitting lines that didn't need it
+fn drop_collinear(mut points: Vec<Point>) -> Vec<Point> {
+    let mut i = 0;
+    while points.len() > 2 && i < points.len() {
+        let n = points.len();
+        let (prev, p, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        let (a, b) = (p - prev, next - p);
+        if a.cross(b).abs() <= EPSILON * a.hypot() * b.hypot() && a.dot(b) > 0.0 {
+            points.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{BezPath, Circle, Rect, Shape};
+
+    use crate::{
+        overlap::remove_overlaps,
+        quality::{check_path, Finding},
+    };
+
+    fn join(paths: &[BezPath]) -> BezPath {
+        let mut joined = BezPath::new();
+        for path in paths {
+            joined.extend(path.iter());
+        }
+        joined
+    }
+
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> BezPath {
+        Rect::new(x0, y0, x1, y1).to_path(0.1)
+    }
+
+    #[test]
+    fn overlapping_squares_merge() {
+        let path = join(&[rect(0.0, 0.0, 100.0, 100.0), rect(50.0, 50.0, 150.0, 150.0)]);
+
+        let merged = remove_overlaps(&path);
+
+        assert_eq!(
+            "M0,0 L100,0 L100,50 L150,50 L150,150 L50,150 L50,100 L0,100 Z",
+            merged.to_svg()
+        );
+        assert_eq!(17500.0, merged.area());
+        assert_eq!(Vec::<Finding>::new(), check_path(&merged, 1.0));
+    }
+
+    #[test]
+    fn contained_contour_is_dropped() {
+        let path = join(&[rect(0.0, 0.0, 100.0, 100.0), rect(25.0, 25.0, 75.0, 75.0)]);
+
+        assert_eq!(rect(0.0, 0.0, 100.0, 100.0), remove_overlaps(&path));
+    }
+
+    #[test]
+    fn holes_and_curves_are_kept() {
+        let path = join(&[
+            rect(0.0, 0.0, 100.0, 100.0),
+            rect(25.0, 25.0, 75.0, 75.0).reverse_subpaths(),
+            Circle::new((200.0, 50.0), 25.0).to_path(0.1),
+        ]);
+
+        assert_eq!(path, remove_overlaps(&path));
+    }
+
+    #[test]
+    fn winding_follows_input() {
+        let path = join(&[rect(0.0, 0.0, 100.0, 100.0), rect(50.0, 50.0, 150.0, 150.0)])
+            .reverse_subpaths();
+
+        let merged = remove_overlaps(&path);
+
+        assert_eq!(-17500.0, merged.area());
+        assert_eq!(Vec::<Finding>::new(), check_path(&merged, -1.0));
+    }
+
+    #[test]
+    fn shared_edge_is_removed() {
+        let path = join(&[rect(0.0, 0.0, 100.0, 100.0), rect(100.0, 0.0, 200.0, 100.0)]);
+        let touching = join(&[rect(0.0, 0.0, 100.0, 100.0), rect(50.0, 0.0, 200.0, 100.0)]);
+
+        assert_eq!(
+            "M0,0 L200,0 L200,100 L0,100 Z",
+            remove_overlaps(&touching).to_svg()
+        );
+        // Touching along an edge without crossing isn't an overlap
+        assert_eq!(path, remove_overlaps(&path));
+    }
+}
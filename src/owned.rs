@@ -0,0 +1,360 @@
+//! Owned counterparts of the borrowed types, for long-lived services and async code
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use kurbo::Affine;
+use skrifa::{instance::Location, raw::ReadError, FontRef, Tag};
+
+use crate::{
+    error::{DrawSvgError, IconResolutionError},
+    icon2svg::{self, Accessibility, DrawOptions, Fallback, Fill, Framing, RenderHints},
+    iconid::{Icon, IconIdentifier, Icons},
+    pathstyle::PathStyle,
+};
+
+/// Font bytes that hand out [FontRef]s on demand.
+///
+/// Cheap to clone and safe to share between threads, the bytes are only parsed enough to
+/// validate them up front.
+#[derive(Clone, Debug)]
+pub struct OwnedFont {
+    data: Arc<[u8]>,
+    index: u32,
+}
+
+impl OwnedFont {
+    pub fn new(data: impl Into<Arc<[u8]>>) -> Result<OwnedFont, ReadError> {
+        Self::from_index(data, 0)
+    }
+
+    /// The font at `index` in a collection, or a plain font if `index` is 0
+    pub fn from_index(data: impl Into<Arc<[u8]>>, index: u32) -> Result<OwnedFont, ReadError> {
+        let data = data.into();
+        FontRef::from_index(&data, index)?;
+        Ok(OwnedFont { data, index })
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn font_ref(&self) -> FontRef<'_> {
+        FontRef::from_index(&self.data, self.index).expect("Validated in constructor")
+    }
+
+    pub fn draw_icon(&self, config: &DrawConfig) -> Result<String, DrawSvgError> {
+        icon2svg::draw_icon(&self.font_ref(), &config.draw_options())
+    }
+
+    pub fn icons(&self) -> Result<Vec<Icon>, IconResolutionError> {
+        self.font_ref().icons()
+    }
+}
+
+/// An owned [DrawOptions], each field maps onto the builder method of the same name
+///
+/// With the `serde` feature the location is (de)serialized as normalized coordinates. Only
+/// the identifier and size are required, e.g.
+/// `{ "identifier": { "name": "mail" }, "width_height": 24, "user_coords": { "FILL": 1 } }`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrawConfig {
+    pub identifier: IconIdentifier,
+    pub width_height: f32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_support::location")
+    )]
+    pub location: Location,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub style: PathStyle,
+    /// See [DrawOptions::with_user_coords]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub user_coords: BTreeMap<String, f32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::serde_support::affine")
+    )]
+    pub transform: Affine,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fallback: Fallback,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub framing: Framing,
+    /// See [DrawOptions::with_canonical_output]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub canonical: bool,
+    /// See [DrawOptions::with_render_hints]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hints: RenderHints,
+    /// See [DrawOptions::with_automatic_optical_size]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub optical_size: bool,
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_support::tags"))]
+    pub features: Vec<Tag>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub color: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_width: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stroke_color: Option<[u8; 3]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auto_mirror: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub remove_overlaps: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub accessibility: Option<Accessibility>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill: Fill,
+}
+
+impl DrawConfig {
+    pub fn new(
+        identifier: IconIdentifier,
+        width_height: f32,
+        location: Location,
+        style: PathStyle,
+    ) -> DrawConfig {
+        DrawConfig {
+            identifier,
+            width_height,
+            location,
+            style,
+            user_coords: BTreeMap::new(),
+            transform: Affine::IDENTITY,
+            fallback: Fallback::Error,
+            framing: Framing::EmBox,
+            canonical: false,
+            hints: RenderHints::default(),
+            optical_size: false,
+            features: Vec::new(),
+            color: false,
+            stroke_width: None,
+            stroke_color: None,
+            auto_mirror: None,
+            remove_overlaps: false,
+            accessibility: None,
+            fill: Fill::Unset,
+        }
+    }
+
+    /// Borrows as [DrawOptions] for use with the rest of the crate
+    pub fn draw_options(&self) -> DrawOptions<'_> {
+        let mut options = DrawOptions::new(
+            self.identifier.clone(),
+            self.width_height,
+            (&self.location).into(),
+            self.style,
+        )
+        .with_user_coords(
+            self.user_coords
+                .iter()
+                .map(|(tag, value)| (tag.as_str(), *value)),
+        )
+        .with_transform(self.transform)
+        .with_fallback(self.fallback.clone())
+        .with_framing(self.framing)
+        .with_canonical_output(self.canonical)
+        .with_render_hints(self.hints.clone())
+        .with_automatic_optical_size(self.optical_size)
+        .with_color(self.color)
+        .with_remove_overlaps(self.remove_overlaps)
+        .with_fill(self.fill.clone());
+        for feature in &self.features {
+            options = options.with_feature(*feature);
+        }
+        if let Some(width) = self.stroke_width {
+            options = options.with_stroke_width(width);
+        }
+        if let Some(rgb) = self.stroke_color {
+            options = options.with_stroke_color(rgb);
+        }
+        if let Some(auto_mirror) = self.auto_mirror {
+            options = options.with_auto_mirror(auto_mirror);
+        }
+        if let Some(accessibility) = &self.accessibility {
+            options = options.with_accessibility(accessibility.clone());
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use kurbo::Affine;
+    use skrifa::{MetadataProvider, Tag};
+
+    use crate::{
+        icon2svg::{
+            draw_icon, Accessibility, DrawOptions, Fill, Framing, RenderHints, ShapeRendering,
+        },
+        iconid,
+        owned::{DrawConfig, OwnedFont},
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    #[test]
+    fn draw_from_another_thread() {
+        let font = OwnedFont::new(testdata::ICON_FONT.to_vec()).unwrap();
+        let location = font.font_ref().axes().location(&[
+            ("wght", 400.0),
+            ("opsz", 24.0),
+            ("GRAD", 0.0),
+            ("FILL", 1.0),
+        ]);
+        let config = DrawConfig::new(iconid::MAIL.clone(), 24.0, location, PathStyle::Unchanged);
+
+        let svg = thread::spawn(move || font.draw_icon(&config).unwrap())
+            .join()
+            .unwrap();
+
+        assert_eq!(testdata::MAIL_SVG.trim(), svg);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn draw_config_json_roundtrip() {
+        let font = OwnedFont::new(testdata::ICON_FONT.to_vec()).unwrap();
+        let location = font
+            .font_ref()
+            .axes()
+            .location(&[("wght", 700.0), ("FILL", 1.0)]);
+        let config = DrawConfig::new(iconid::MAN.clone(), 24.0, location, PathStyle::Compact);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: DrawConfig = serde_json::from_str(&json).unwrap();
+
+        assert!(json.contains(r#""identifier":{"glyphId":5}"#), "{json}");
+        assert!(json.contains(r#""style":"compact""#), "{json}");
+        assert_eq!(config.identifier, restored.identifier);
+        assert_eq!(config.location.coords(), restored.location.coords());
+        assert_eq!(
+            font.draw_icon(&config).unwrap(),
+            font.draw_icon(&restored).unwrap()
+        );
+    }
+
+    /// Sets every option [DrawConfig::new] leaves at its default that applies to mail
+    fn full_config() -> DrawConfig {
+        DrawConfig {
+            user_coords: [("FILL".to_string(), 1.0)].into(),
+            transform: Affine::translate((0.0, -20.0)),
+            framing: Framing::Metrics,
+            canonical: true,
+            hints: RenderHints {
+                shape_rendering: Some(ShapeRendering::CrispEdges),
+                ..Default::default()
+            },
+            features: vec![Tag::new(b"ss01")],
+            remove_overlaps: true,
+            accessibility: Some(Accessibility {
+                title: Some("Inbox".to_string()),
+                description: None,
+            }),
+            fill: Fill::CustomProperty {
+                name: "icon-color".to_string(),
+                fallback: [0, 0, 255],
+            },
+            ..DrawConfig::new(
+                iconid::MAIL.clone(),
+                48.0,
+                Default::default(),
+                PathStyle::Compact,
+            )
+        }
+    }
+
+    #[test]
+    fn draw_config_matches_options() {
+        let font = OwnedFont::new(testdata::ICON_FONT.to_vec()).unwrap();
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            48.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_user_coords([("FILL", 1.0)])
+        .with_transform(Affine::translate((0.0, -20.0)))
+        .with_framing(Framing::Metrics)
+        .with_canonical_output(true)
+        .with_render_hints(RenderHints {
+            shape_rendering: Some(ShapeRendering::CrispEdges),
+            ..Default::default()
+        })
+        .with_feature(Tag::new(b"ss01"))
+        .with_remove_overlaps(true)
+        .with_accessibility(Accessibility {
+            title: Some("Inbox".to_string()),
+            description: None,
+        })
+        .with_fill(Fill::CustomProperty {
+            name: "icon-color".to_string(),
+            fallback: [0, 0, 255],
+        });
+
+        assert_eq!(
+            draw_icon(&font.font_ref(), &options).unwrap(),
+            font.draw_icon(&full_config()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn draw_config_from_json() {
+        let font = OwnedFont::new(testdata::ICON_FONT.to_vec()).unwrap();
+        let config: DrawConfig = serde_json::from_str(
+            r#"{
+                "identifier": { "name": "mail" },
+                "width_height": 48,
+                "style": "compact",
+                "user_coords": { "FILL": 1 },
+                "transform": [1, 0, 0, 1, 0, -20],
+                "framing": "metrics",
+                "canonical": true,
+                "hints": { "shape_rendering": "crispEdges" },
+                "features": ["ss01"],
+                "remove_overlaps": true,
+                "accessibility": { "title": "Inbox" },
+                "fill": { "customProperty": { "name": "icon-color", "fallback": [0, 0, 255] } }
+            }"#,
+        )
+        .unwrap();
+        let expected = font.draw_icon(&full_config()).unwrap();
+
+        assert_eq!(expected, font.draw_icon(&config).unwrap());
+        let json = serde_json::to_string(&config).unwrap();
+        let restored: DrawConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(expected, font.draw_icon(&restored).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn draw_config_fallback_path() {
+        let font = OwnedFont::new(testdata::ICON_FONT.to_vec()).unwrap();
+        let config: DrawConfig = serde_json::from_str(
+            r#"{
+                "identifier": { "name": "no_such_icon" },
+                "width_height": 24,
+                "fallback": { "path": "M0,0 L10,0 L10,10 Z" }
+            }"#,
+        )
+        .unwrap();
+
+        let svg = font.draw_icon(&config).unwrap();
+
+        assert!(svg.contains(r#"d="M0,0L10,0L10,10L0,0Z""#), "{svg}");
+    }
+
+    #[test]
+    fn rejects_invalid_font() {
+        assert!(OwnedFont::new(vec![0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn icons_from_owned() {
+        let font = OwnedFont::new(testdata::MATERIAL_SYMBOLS_POPULAR.to_vec()).unwrap();
+        assert!(!font.icons().unwrap().is_empty());
+    }
+}
@@ -1,32 +1,41 @@
 //! Controls how a [`BezPath`] is converted to string form.
 
-use kurbo::{BezPath, PathEl, Point};
-
-#[derive(Debug, Copy, Clone)]
+use kurbo::{BezPath, Line, ParamCurveNearest, PathEl, Point};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub enum PathStyle {
     /// Emit the exact drawing commands received by the pen.
     ///
     /// This makes sense when you want to retain interpolation compatibility or to
     /// do your own post-processing later.
+    #[default]
     Unchanged,
     /// Try to produce a compact path
     ///
     /// Apply the optimizations from [svgo convertPathData.js](https://github.com/svg/svgo/blob/main/plugins/convertPathData.js)
     /// that seem to have the greatest benefit for our use cases.
     Compact,
+    /// [PathStyle::Compact] without relative commands, for consumers that only parse absolute
+    /// ones, such as some VectorDrawable parsers
+    CompactAbsolute,
 }
 
 impl PathStyle {
     pub(crate) fn write_svg_path(&self, path: &BezPath) -> String {
         match self {
             PathStyle::Unchanged => to_unchanged_svg_path(path),
-            PathStyle::Compact => to_compact_svg_path(path),
+            PathStyle::Compact | PathStyle::CompactAbsolute => to_compact_svg_path(*self, path),
         }
     }
 
     fn coord_string(self, p: Point) -> String {
         let p = p.round2();
-        if matches!(self, PathStyle::Compact) && p.y < 0.0 {
+        if matches!(self, PathStyle::Compact | PathStyle::CompactAbsolute) && p.y < 0.0 {
             format!("{}{}", p.x, p.y)
         } else {
             format!("{},{}", p.x, p.y)
@@ -114,13 +123,15 @@ fn add_command<T, const N: usize>(
         .map(|p| p.write_absolute_coord(path_style))
         .collect::<SvgCoords>()
         .0;
-    let relative = relative_to.map(|rel_to| {
-        coords
-            .iter()
-            .map(|p| p.write_relative_coord(rel_to, path_style))
-            .collect::<SvgCoords>()
-            .0
-    });
+    let relative = relative_to
+        .filter(|_| path_style != PathStyle::CompactAbsolute)
+        .map(|rel_to| {
+            coords
+                .iter()
+                .map(|p| p.write_relative_coord(rel_to, path_style))
+                .collect::<SvgCoords>()
+                .0
+        });
 
     if relative.as_ref().map(|s| s.len()).unwrap_or(usize::MAX) < absolute.len() {
         svg.push(prefix.to_ascii_lowercase());
@@ -167,13 +178,13 @@ fn to_unchanged_svg_path(path: &BezPath) -> String {
     svg
 }
 
-fn compact_line_to(svg: &mut String, p: Point, curr: Point) {
+fn compact_line_to(style: PathStyle, svg: &mut String, p: Point, curr: Point) {
     if p.x == curr.x {
-        add_command(svg, PathStyle::Compact, 'V', [p.y], Some(curr.y));
+        add_command(svg, style, 'V', [p.y], Some(curr.y));
     } else if p.y == curr.y {
-        add_command(svg, PathStyle::Compact, 'H', [p.x], Some(curr.x));
+        add_command(svg, style, 'H', [p.x], Some(curr.x));
     } else {
-        add_command(svg, PathStyle::Compact, 'L', [p], Some(curr));
+        add_command(svg, style, 'L', [p], Some(curr));
     }
 }
 
@@ -182,13 +193,19 @@ fn implied_control(prior_control: Point, prior_end: Point) -> Point {
     prior_control + 2.0 * (prior_end - prior_control)
 }
 
-fn try_add_smooth_quad(svg: &mut String, prev: Option<PathEl>, p1: Point, p2: Point) -> bool {
+fn try_add_smooth_quad(
+    style: PathStyle,
+    svg: &mut String,
+    prev: Option<PathEl>,
+    p1: Point,
+    p2: Point,
+) -> bool {
     let Some(PathEl::QuadTo(prev_p1, prev_p2)) = prev else {
         return false;
     };
 
     if implied_control(prev_p1, prev_p2).round2() == p1.round2() {
-        add_command(svg, PathStyle::Compact, 'T', [p2], Some(prev_p2));
+        add_command(svg, style, 'T', [p2], Some(prev_p2));
         true
     } else {
         false
@@ -196,6 +213,7 @@ fn try_add_smooth_quad(svg: &mut String, prev: Option<PathEl>, p1: Point, p2: Po
 }
 
 fn try_add_smooth_curve(
+    style: PathStyle,
     svg: &mut String,
     prev: Option<PathEl>,
     p1: Point,
@@ -207,55 +225,109 @@ fn try_add_smooth_curve(
     };
 
     if implied_control(prev_p2, prev_p3).round2() == p1.round2() {
-        add_command(svg, PathStyle::Compact, 'S', [p2, p3], Some(prev_p3));
+        add_command(svg, style, 'S', [p2, p3], Some(prev_p3));
         true
     } else {
         false
     }
 }
 
-fn to_compact_svg_path(path: &BezPath) -> String {
+// Rounding moves points up to half a hundredth
+const TOLERANCE: f64 = 0.005;
+
+/// Whether `p` is within what rounding to hundredths would hide of the segment `start..end`
+fn near_segment(p: Point, start: Point, end: Point) -> bool {
+    Line::new(start, end).nearest(p, 0.0).distance_sq <= TOLERANCE * TOLERANCE
+}
+
+/// A cubic as a line if its controls are on the line between its ends, or as the quad it was
+/// elevated from, within what rounding to hundredths would hide
+fn downgrade_cubic(start: Point, p1: Point, p2: Point, p3: Point) -> Option<PathEl> {
+    if near_segment(p1, start, p3) && near_segment(p2, start, p3) {
+        return Some(PathEl::LineTo(p3));
+    }
+
+    // Degree elevation puts the controls 2/3 of the way from each end to the quad's control
+    let q0 = start.lerp(p1, 1.5);
+    let q1 = p3.lerp(p2, 1.5);
+    if q0.distance(q1) <= TOLERANCE {
+        return Some(PathEl::QuadTo(q0.midpoint(q1), p3));
+    }
+    None
+}
+
+/// The path with degenerate cubics downgraded and runs of collinear lines merged into one
+fn simplify(path: &BezPath) -> Vec<PathEl> {
+    let mut simplified: Vec<PathEl> = Vec::new();
+    let mut subpath_start = Point::default();
+    let mut curr = Point::default();
+    // Where the trailing run of lines starts and the points it passes through
+    let mut run: Option<(Point, Vec<Point>)> = None;
+    for el in path.elements() {
+        let el = match *el {
+            PathEl::CurveTo(p1, p2, p3) => downgrade_cubic(curr, p1, p2, p3).unwrap_or(*el),
+            el => el,
+        };
+        match (el, &mut run, simplified.last_mut()) {
+            (PathEl::LineTo(p), Some((start, through)), Some(PathEl::LineTo(last)))
+                if near_segment(*last, *start, p)
+                    && through.iter().all(|q| near_segment(*q, *start, p)) =>
+            {
+                through.push(*last);
+                *last = p;
+                curr = p;
+                continue;
+            }
+            (PathEl::LineTo(_), ..) => run = Some((curr, Vec::new())),
+            _ => run = None,
+        }
+        if let PathEl::MoveTo(p) = el {
+            subpath_start = p;
+        }
+        curr = el.end_point().unwrap_or(subpath_start);
+        simplified.push(el);
+    }
+    simplified
+}
+
+fn to_compact_svg_path(style: PathStyle, path: &BezPath) -> String {
     let mut svg = String::new();
     let mut subpath_start = Point::default();
     let mut curr = Point::default();
     let mut prev = None;
-    for el in path.elements() {
+    for el in simplify(path).iter() {
         match el {
             PathEl::MoveTo(p) => {
-                add_command(&mut svg, PathStyle::Compact, 'M', [*p], Some(curr));
+                add_command(&mut svg, style, 'M', [*p], Some(curr));
                 subpath_start = *p;
                 curr = *p;
             }
             PathEl::LineTo(p) => {
                 if curr.round2() != p.round2() {
-                    compact_line_to(&mut svg, *p, curr);
+                    compact_line_to(style, &mut svg, *p, curr);
                 }
                 curr = *p;
             }
             PathEl::QuadTo(p1, p2) => {
-                if curr.round2() != p2.round2() && !try_add_smooth_quad(&mut svg, prev, *p1, *p2) {
-                    add_command(&mut svg, PathStyle::Compact, 'Q', [*p1, *p2], Some(curr));
+                if curr.round2() != p2.round2()
+                    && !try_add_smooth_quad(style, &mut svg, prev, *p1, *p2)
+                {
+                    add_command(&mut svg, style, 'Q', [*p1, *p2], Some(curr));
                 }
                 curr = *p2;
             }
             PathEl::CurveTo(p1, p2, p3) => {
                 if curr.round2() != p3.round2()
-                    && !try_add_smooth_curve(&mut svg, prev, *p1, *p2, *p3)
+                    && !try_add_smooth_curve(style, &mut svg, prev, *p1, *p2, *p3)
                 {
-                    add_command(
-                        &mut svg,
-                        PathStyle::Compact,
-                        'C',
-                        [*p1, *p2, *p3],
-                        Some(curr),
-                    );
+                    add_command(&mut svg, style, 'C', [*p1, *p2, *p3], Some(curr));
                 }
                 curr = *p3;
             }
             PathEl::ClosePath => {
                 // See <https://github.com/harfbuzz/harfbuzz/blob/2da79f70a1d562d883bdde5b74f6603374fb7023/src/hb-draw.hh#L148-L150>
                 if curr.round2() != subpath_start.round2() {
-                    compact_line_to(&mut svg, subpath_start, curr);
+                    compact_line_to(style, &mut svg, subpath_start, curr);
                 }
                 svg.push('Z');
                 curr = subpath_start;
@@ -429,4 +501,94 @@ mod tests {
             "M10,20l5-5H5l5,5Zm0,5l5,5H5l5-5Z"
         );
     }
+
+    #[test]
+    fn collinear_cubic_is_a_line() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.curve_to((10.0, 0.0), (20.0, 0.001), (30.0, 0.0));
+        path.curve_to((30.0, 10.0), (30.0, 20.0), (30.0, 30.0));
+        // Controls past the end overshoot, it isn't a line
+        path.curve_to((45.0, 45.0), (40.0, 40.0), (35.0, 35.0));
+        path.close_path();
+
+        assert_eq!(
+            PathStyle::Compact.write_svg_path(&path),
+            "M0,0H30V30c15,15 10,10 5,5L0,0Z"
+        );
+    }
+
+    #[test]
+    fn elevated_quad_is_a_quad() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        // Elevated from the quad with control (30, 60)
+        path.curve_to((20.0, 40.0), (40.0, 40.0), (60.0, 0.0));
+        // and then its smooth continuation, control (90, -60)
+        path.curve_to((80.0, -40.0), (100.0, -40.0), (120.0, 0.0));
+        path.curve_to((130.0, 10.0), (140.0, 30.0), (150.0, 0.0));
+
+        assert_eq!(
+            PathStyle::Compact.write_svg_path(&path),
+            "M0,0Q30,60 60,0t60,0c10,10 20,30 30,0"
+        );
+    }
+
+    #[test]
+    fn merge_collinear_lines() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((20.0, 0.0));
+        path.line_to((50.0, 0.0));
+        path.line_to((60.0, 10.0));
+        path.line_to((70.0, 20.004)); // off the line by less than rounding shows
+        path.line_to((80.0, 30.0));
+        // Doubling back isn't a continuation
+        path.line_to((80.0, 40.0));
+        path.line_to((80.0, 35.0));
+        path.close_path();
+
+        assert_eq!(
+            PathStyle::Compact.write_svg_path(&path),
+            "M0,0H50L80,30V40V35L0,0Z"
+        );
+    }
+
+    #[test]
+    fn merge_stops_at_bend() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((10.0, 0.0));
+        path.line_to((20.0, 0.02));
+        path.line_to((30.0, 0.0));
+        path.move_to((0.0, 10.0));
+        path.line_to((10.0, 10.0));
+
+        assert_eq!(
+            PathStyle::Compact.write_svg_path(&path),
+            "M0,0H10L20,0.02L30,0M0,10H10"
+        );
+    }
+
+    #[test]
+    fn compact_absolute() {
+        let mut path = BezPath::new();
+        path.move_to((100.0, 100.0));
+        path.line_to((101.0, 101.0));
+        path.line_to((101.0, 120.0));
+        path.line_to((110.0, 120.0));
+        path.line_to((120.0, 120.0));
+        path.quad_to((130.0, 130.0), (140.0, 120.0));
+        path.quad_to((150.0, 110.0), (160.0, 120.0));
+        path.close_path();
+
+        assert_eq!(
+            PathStyle::Compact.write_svg_path(&path),
+            "M100,100l1,1v19h19q10,10 20,0t20,0l-60-20Z"
+        );
+        assert_eq!(
+            PathStyle::CompactAbsolute.write_svg_path(&path),
+            "M100,100L101,101V120H120Q130,130 140,120T160,120L100,100Z"
+        );
+    }
 }
@@ -1,7 +1,22 @@
 //! Our own transformed bezier pen to avoid a dependency on write-fonts which is not in google3
 
-use kurbo::{BezPath, Point};
-use skrifa::outline::OutlinePen;
+use kurbo::{Affine, BezPath, Point, Rect, Shape};
+use skrifa::{
+    color::{Brush, ColorPainter, ColorStop, CompositeMode, Transform},
+    instance::LocationRef,
+    outline::OutlinePen,
+    raw::{types::BoundingBox, TableProvider},
+    FontRef, GlyphId,
+};
+
+use crate::{
+    document::{draw_glyph_id, Color, Group, Layer, Paint},
+    error::DrawSvgError,
+    iconid::IconIdentifier,
+};
+
+// The palette index of the foreground color
+const FOREGROUND_INDEX: u16 = 0xFFFF;
 
 /// Produces an svg representation of a font glyph corrected to be Y-down (as in svg) instead of Y-up (as in fonts)
 pub(crate) struct SvgPathPen {
@@ -51,3 +66,243 @@ impl OutlinePen for SvgPathPen {
         self.path.close_path();
     }
 }
+
+/// Collects the fills of a color glyph as layers, Y-down like [SvgPathPen]
+pub(crate) struct LayerPainter<'a> {
+    font: &'a FontRef<'a>,
+    identifier: &'a IconIdentifier,
+    location: LocationRef<'a>,
+    /// RGBA of the first palette
+    palette: Vec<[u8; 4]>,
+    /// Accumulated transforms, already converted to Y-down
+    transforms: Vec<Affine>,
+    /// Clip paths and whether they came from a glyph rather than a box
+    clips: Vec<(BezPath, bool)>,
+    /// Open layers, None for those that simply paint over what is below
+    groups: Vec<Option<Group>>,
+    next_group: usize,
+    layers: Vec<Layer>,
+    error: Option<DrawSvgError>,
+}
+
+impl<'a> LayerPainter<'a> {
+    pub(crate) fn new(
+        font: &'a FontRef<'a>,
+        identifier: &'a IconIdentifier,
+        location: LocationRef<'a>,
+    ) -> Result<Self, DrawSvgError> {
+        let mut palette = Vec::new();
+        if font.table_data(skrifa::Tag::new(b"CPAL")).is_some() {
+            let cpal = font
+                .cpal()
+                .map_err(|e| DrawSvgError::ReadError("CPAL", e))?;
+            let first = cpal
+                .color_record_indices()
+                .first()
+                .map(|idx| idx.get() as usize)
+                .unwrap_or_default();
+            if let Some(records) = cpal.color_records_array() {
+                let records = records.map_err(|e| DrawSvgError::ReadError("CPAL", e))?;
+                palette = records
+                    .iter()
+                    .skip(first)
+                    .take(cpal.num_palette_entries() as usize)
+                    .map(|c| [c.red(), c.green(), c.blue(), c.alpha()])
+                    .collect();
+            }
+        }
+        Ok(LayerPainter {
+            font,
+            identifier,
+            location,
+            palette,
+            transforms: Vec::new(),
+            clips: Vec::new(),
+            groups: Vec::new(),
+            next_group: 0,
+            layers: Vec::new(),
+            error: None,
+        })
+    }
+
+    pub(crate) fn into_layers(self) -> Result<Vec<Layer>, DrawSvgError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.layers),
+        }
+    }
+
+    fn transform(&self) -> Affine {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+
+    /// A Y-up point in font units to Y-down with the current transform applied
+    fn point(&self, p: skrifa::raw::types::Point<f32>) -> Point {
+        self.transform() * Point::new(p.x as f64, -p.y as f64)
+    }
+
+    fn radius(&self, r: f32) -> f64 {
+        // Exact for uniform scales, the best a circle can do otherwise
+        (r.max(0.0) as f64) * self.transform().determinant().abs().sqrt()
+    }
+
+    fn color(&self, palette_index: u16, alpha: f32) -> Color {
+        match self.palette.get(palette_index as usize) {
+            Some([r, g, b, a]) if palette_index != FOREGROUND_INDEX => Color {
+                rgb: Some([*r, *g, *b]),
+                alpha: *a as f32 / 255.0 * alpha,
+            },
+            _ => Color { rgb: None, alpha },
+        }
+    }
+
+    fn stops(&self, color_stops: &[ColorStop]) -> Vec<(f32, Color)> {
+        color_stops
+            .iter()
+            .map(|stop| (stop.offset, self.color(stop.palette_index, stop.alpha)))
+            .collect()
+    }
+}
+
+impl ColorPainter for LayerPainter<'_> {
+    fn push_transform(&mut self, transform: Transform) {
+        let Transform {
+            xx,
+            yx,
+            xy,
+            yy,
+            dx,
+            dy,
+        } = transform;
+        let transform = Affine::new([xx, yx, xy, yy, dx, dy].map(|c| c as f64));
+        // The same transform acting on Y-down coordinates
+        let transform = Affine::FLIP_Y * transform * Affine::FLIP_Y;
+        self.transforms.push(self.transform() * transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        let mut path = match draw_glyph_id(self.font, self.identifier, glyph_id, self.location) {
+            Ok(path) => path,
+            Err(e) => {
+                self.error.get_or_insert(e);
+                BezPath::new()
+            }
+        };
+        path.apply_affine(self.transform());
+        self.clips.push((path, true));
+    }
+
+    fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+        let rect = Rect::new(
+            clip_box.x_min as f64,
+            -clip_box.y_max as f64,
+            clip_box.x_max as f64,
+            -clip_box.y_min as f64,
+        );
+        let mut path = rect.to_path(0.1);
+        path.apply_affine(self.transform());
+        self.clips.push((path, false));
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn fill(&mut self, brush: Brush<'_>) {
+        // We don't intersect clips, a glyph is usually inside any box around it
+        let Some((path, _)) = self
+            .clips
+            .iter()
+            .rev()
+            .find(|(_, glyph)| *glyph)
+            .or(self.clips.last())
+        else {
+            return;
+        };
+        let paint = match brush {
+            Brush::Solid {
+                palette_index,
+                alpha,
+            } => Paint::Solid(self.color(palette_index, alpha)),
+            Brush::LinearGradient {
+                p0,
+                p1,
+                color_stops,
+                extend,
+            } if color_stops.len() > 1 => Paint::LinearGradient {
+                p0: self.point(p0),
+                p1: self.point(p1),
+                stops: self.stops(color_stops),
+                extend,
+            },
+            Brush::RadialGradient {
+                c0,
+                r0,
+                c1,
+                r1,
+                color_stops,
+                extend,
+            } if color_stops.len() > 1 => Paint::RadialGradient {
+                c0: self.point(c0),
+                r0: self.radius(r0),
+                c1: self.point(c1),
+                r1: self.radius(r1),
+                stops: self.stops(color_stops),
+                extend,
+            },
+            // Sweeps, and gradients of a single stop, are drawn in their first color
+            Brush::LinearGradient { color_stops, .. }
+            | Brush::RadialGradient { color_stops, .. }
+            | Brush::SweepGradient { color_stops, .. } => Paint::Solid(
+                color_stops
+                    .first()
+                    .map(|stop| self.color(stop.palette_index, stop.alpha))
+                    .unwrap_or(Color::FOREGROUND),
+            ),
+        };
+        let mut layer = Layer::with_paint(path.clone(), paint);
+        layer.groups = self.groups.iter().flatten().copied().collect();
+        self.layers.push(layer);
+    }
+
+    fn push_layer(&mut self, composite_mode: CompositeMode) {
+        let group = blend_mode(composite_mode).map(|blend| Group {
+            id: self.next_group,
+            blend,
+        });
+        self.next_group += 1;
+        self.groups.push(group);
+    }
+
+    fn pop_layer(&mut self) {
+        self.groups.pop();
+    }
+}
+
+/// The css mix-blend-mode for a composite mode, None for source over, which is how layers
+/// paint anyway, and for the Porter-Duff modes css can't express
+fn blend_mode(composite_mode: CompositeMode) -> Option<&'static str> {
+    Some(match composite_mode {
+        CompositeMode::Plus => "plus-lighter",
+        CompositeMode::Screen => "screen",
+        CompositeMode::Overlay => "overlay",
+        CompositeMode::Darken => "darken",
+        CompositeMode::Lighten => "lighten",
+        CompositeMode::ColorDodge => "color-dodge",
+        CompositeMode::ColorBurn => "color-burn",
+        CompositeMode::HardLight => "hard-light",
+        CompositeMode::SoftLight => "soft-light",
+        CompositeMode::Difference => "difference",
+        CompositeMode::Exclusion => "exclusion",
+        CompositeMode::Multiply => "multiply",
+        CompositeMode::HslHue => "hue",
+        CompositeMode::HslSaturation => "saturation",
+        CompositeMode::HslColor => "color",
+        CompositeMode::HslLuminosity => "luminosity",
+        _ => return None,
+    })
+}
@@ -0,0 +1,364 @@
+//! Inspects icon outlines for construction problems, e.g. as a gate before release
+
+use kurbo::{flatten, BezPath, PathEl, Point, Shape, Vec2};
+use skrifa::{instance::LocationRef, raw::TableProvider, FontRef, GlyphId};
+
+use crate::{
+    document::draw_glyph_id,
+    error::DrawSvgError,
+    iconid::{IconIdentifier, Icons},
+};
+
+// Curves are checked as polylines within this many font units
+const FLATTEN_TOLERANCE: f64 = 0.25;
+// How far inside a contour to test which contours contain it, in font units
+const INTERIOR_OFFSET: f64 = 0.01;
+
+/// A problem with an outline, contours are numbered in drawing order from 0
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum Finding {
+    /// The contour crosses itself
+    SelfIntersection { contour: usize },
+    /// The contours cross each other
+    OverlappingContours { contours: (usize, usize) },
+    /// The contour winds against the font's convention, or the same way as the contour it
+    /// sits in so it won't punch a hole under nonzero filling
+    WrongWinding { contour: usize },
+    /// The contour encloses no area, e.g. a lone point, so draws nothing
+    StrayPoints { contour: usize },
+    /// The contour isn't closed
+    OpenContour { contour: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityReport {
+    pub identifier: IconIdentifier,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::glyph_id"))]
+    pub gid: GlyphId,
+    /// Empty for a clean outline
+    pub findings: Vec<Finding>,
+}
+
+/// Resolves and inspects one icon at `location`
+pub fn check_icon(
+    font: &FontRef,
+    identifier: &IconIdentifier,
+    location: LocationRef,
+) -> Result<QualityReport, DrawSvgError> {
+    let gid = identifier
+        .resolve(font, &location)
+        .map_err(|e| DrawSvgError::ResolutionError(identifier.clone(), e))?;
+    check_glyph(font, identifier.clone(), gid, location)
+}
+
+/// Inspects every icon in the font at `location`, identified by name where it has one
+pub fn check_icons(
+    font: &FontRef,
+    location: LocationRef,
+) -> Result<Vec<QualityReport>, DrawSvgError> {
+    font.icons()
+        .map_err(DrawSvgError::IconsError)?
+        .into_iter()
+        .map(|icon| {
+            let identifier = match (icon.names.first(), icon.codepoints.first()) {
+                (Some(name), _) => IconIdentifier::Name(name.into()),
+                (None, Some(cp)) => IconIdentifier::Codepoint(*cp),
+                (None, None) => IconIdentifier::GlyphId(icon.gid),
+            };
+            // The listed glyph is the one at the default location
+            let gid = IconIdentifier::GlyphId(icon.gid)
+                .resolve(font, &location)
+                .map_err(|e| DrawSvgError::ResolutionError(identifier.clone(), e))?;
+            check_glyph(font, identifier, gid, location)
+        })
+        .collect()
+}
+
+fn check_glyph(
+    font: &FontRef,
+    identifier: IconIdentifier,
+    gid: GlyphId,
+    location: LocationRef,
+) -> Result<QualityReport, DrawSvgError> {
+    let path = draw_glyph_id(font, &identifier, gid, location)?;
+    // TrueType outer contours are clockwise, which has positive area once drawn Y-down. CFF is
+    // the reverse.
+    let outer_area_sign = if font.glyf().is_ok() { 1.0 } else { -1.0 };
+    Ok(QualityReport {
+        identifier,
+        gid,
+        findings: check_path(&path, outer_area_sign),
+    })
+}
+
+struct Contour {
+    path: BezPath,
+    closed: bool,
+    polyline: Vec<Point>,
+}
+
+impl Contour {
+    /// A point just inside the middle of the first segment, points on the outline itself are
+    /// ambiguous where contours touch
+    fn interior_point(&self) -> Option<Point> {
+        let (start, end) = self.segments().next()?;
+        let direction = (end - start).normalize();
+        // Interior is to the right of travel for positive area, Y-down
+        let inward = Vec2::new(-direction.y, direction.x) * self.path.area().signum();
+        Some(start.midpoint(end) + inward * INTERIOR_OFFSET)
+    }
+
+    /// The polyline's segments including the closing one
+    fn segments(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let n = self.polyline.len();
+        (0..n).map(move |i| (self.polyline[i], self.polyline[(i + 1) % n]))
+    }
+}
+
+pub(crate) fn check_path(path: &BezPath, outer_area_sign: f64) -> Vec<Finding> {
+    let contours = contours(path);
+    let mut findings = Vec::new();
+
+    let mut overlaps = Vec::new();
+    for (i, a) in contours.iter().enumerate() {
+        for (j, b) in contours.iter().enumerate().skip(i + 1) {
+            let crosses = a
+                .segments()
+                .any(|s| b.segments().any(|t| properly_intersect(s, t)));
+            if crosses {
+                overlaps.push((i, j));
+            }
+        }
+    }
+
+    for (i, contour) in contours.iter().enumerate() {
+        if !contour.closed {
+            findings.push(Finding::OpenContour { contour: i });
+        }
+        let bbox = contour.path.bounding_box();
+        if bbox.width() == 0.0 || bbox.height() == 0.0 {
+            findings.push(Finding::StrayPoints { contour: i });
+            continue;
+        }
+        if self_intersects(contour) {
+            findings.push(Finding::SelfIntersection { contour: i });
+        }
+        // Which contour sits in which is unclear when they cross, that's reported as an overlap
+        if overlaps.iter().any(|(a, b)| *a == i || *b == i) {
+            continue;
+        }
+        // A contour inside an odd number of others is a hole and should wind the other way
+        let Some(inside) = contour.interior_point() else {
+            continue;
+        };
+        let area = contour.path.area();
+        let depth = contours
+            .iter()
+            .enumerate()
+            .filter(|(j, other)| {
+                // Of identical contours the earlier one is taken to contain the later
+                let other_area = other.path.area().abs();
+                *j != i
+                    && (other_area > area.abs() || (other_area == area.abs() && *j < i))
+                    && other.path.winding(inside) != 0
+            })
+            .count();
+        let expected = if depth % 2 == 0 {
+            outer_area_sign
+        } else {
+            -outer_area_sign
+        };
+        if area * expected < 0.0 {
+            findings.push(Finding::WrongWinding { contour: i });
+        }
+    }
+
+    findings.extend(
+        overlaps
+            .into_iter()
+            .map(|contours| Finding::OverlappingContours { contours }),
+    );
+    findings
+}
+
+fn contours(path: &BezPath) -> Vec<Contour> {
+    let mut contours: Vec<Contour> = Vec::new();
+    for el in path.elements() {
+        match el {
+            PathEl::MoveTo(..) => contours.push(Contour {
+                path: BezPath::new(),
+                closed: false,
+                polyline: Vec::new(),
+            }),
+            PathEl::ClosePath => {
+                if let Some(contour) = contours.last_mut() {
+                    contour.closed = true;
+                }
+            }
+            _ => (),
+        }
+        if let Some(contour) = contours.last_mut() {
+            contour.path.push(*el);
+        }
+    }
+    for contour in contours.iter_mut() {
+        let mut polyline = Vec::new();
+        flatten(contour.path.iter(), FLATTEN_TOLERANCE, |el| match el {
+            PathEl::MoveTo(p) | PathEl::LineTo(p) if polyline.last() != Some(&p) => {
+                polyline.push(p)
+            }
+            _ => (),
+        });
+        // The closing segment is implied
+        if polyline.len() > 1 && polyline.first() == polyline.last() {
+            polyline.pop();
+        }
+        contour.polyline = polyline;
+    }
+    contours
+}
+
+fn self_intersects(contour: &Contour) -> bool {
+    let segments: Vec<_> = contour.segments().collect();
+    let n = segments.len();
+    (0..n).any(|i| {
+        // Neighbors share an endpoint, including the last with the first
+        (i + 2..n)
+            .filter(|j| !(i == 0 && *j == n - 1))
+            .any(|j| properly_intersect(segments[i], segments[j]))
+    })
+}
+
+/// Whether the segments cross at a point interior to both, touching doesn't count
+fn properly_intersect((p1, p2): (Point, Point), (q1, q2): (Point, Point)) -> bool {
+    let side = |a: Point, b: Point, c: Point| (b - a).cross(c - a);
+    let (d1, d2) = (side(q1, q2, p1), side(q1, q2, p2));
+    let (d3, d4) = (side(p1, p2, q1), side(p1, p2, q2));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{BezPath, Rect, Shape};
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        iconid,
+        quality::{check_icon, check_icons, check_path, Finding},
+        testdata,
+    };
+
+    /// A Y-down rect wound as a TrueType outer contour, or reversed
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64, outer: bool) -> BezPath {
+        let path = Rect::new(x0, y0, x1, y1).to_path(0.1);
+        if outer {
+            path
+        } else {
+            path.reverse_subpaths()
+        }
+    }
+
+    fn join(paths: &[BezPath]) -> BezPath {
+        let mut joined = BezPath::new();
+        for path in paths {
+            joined.extend(path.iter());
+        }
+        joined
+    }
+
+    #[test]
+    fn clean_square_with_hole() {
+        let path = join(&[
+            rect(0.0, 0.0, 100.0, 100.0, true),
+            rect(25.0, 25.0, 75.0, 75.0, false),
+        ]);
+        assert_eq!(Vec::<Finding>::new(), check_path(&path, 1.0));
+    }
+
+    #[test]
+    fn hole_wound_like_its_container() {
+        let path = join(&[
+            rect(0.0, 0.0, 100.0, 100.0, true),
+            rect(25.0, 25.0, 75.0, 75.0, true),
+        ]);
+        assert_eq!(
+            vec![Finding::WrongWinding { contour: 1 }],
+            check_path(&path, 1.0)
+        );
+    }
+
+    #[test]
+    fn outer_against_convention() {
+        let path = rect(0.0, 0.0, 100.0, 100.0, true);
+        assert_eq!(
+            vec![Finding::WrongWinding { contour: 0 }],
+            check_path(&path, -1.0)
+        );
+    }
+
+    #[test]
+    fn overlapping_contours() {
+        let path = join(&[
+            rect(0.0, 0.0, 100.0, 100.0, true),
+            rect(50.0, 50.0, 150.0, 150.0, true),
+        ]);
+        assert_eq!(
+            vec![Finding::OverlappingContours { contours: (0, 1) }],
+            check_path(&path, 1.0)
+        );
+    }
+
+    #[test]
+    fn bow_tie_intersects_itself() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((100.0, 100.0));
+        path.line_to((100.0, 0.0));
+        path.line_to((0.0, 100.0));
+        path.close_path();
+        // Equal and opposite lobes, the area nets to 0 so winding isn't reported
+        assert_eq!(
+            vec![Finding::SelfIntersection { contour: 0 }],
+            check_path(&path, 1.0)
+        );
+    }
+
+    #[test]
+    fn stray_and_open() {
+        let mut path = rect(0.0, 0.0, 100.0, 100.0, true);
+        path.move_to((200.0, 200.0));
+        path.close_path();
+        path.move_to((300.0, 0.0));
+        path.line_to((400.0, 0.0));
+        path.line_to((400.0, 100.0));
+        assert_eq!(
+            vec![
+                Finding::StrayPoints { contour: 1 },
+                Finding::OpenContour { contour: 2 },
+            ],
+            check_path(&path, 1.0)
+        );
+    }
+
+    #[test]
+    fn icon_font_is_clean() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("FILL", 1.0)]);
+
+        let mail = check_icon(&font, &iconid::MAIL, (&loc).into()).unwrap();
+        let all = check_icons(&font, (&loc).into()).unwrap();
+
+        assert_eq!(Vec::<Finding>::new(), mail.findings);
+        assert_eq!(3, all.len());
+        assert!(
+            all.iter().all(|report| report.findings.is_empty()),
+            "{all:?}"
+        );
+    }
+}
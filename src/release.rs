@@ -0,0 +1,493 @@
+//! Describes a font release from the differences to the prior release
+
+use crate::cmp::{AxisChange, AxisRange, CodepointChange, CodepointViolation, CompareResult};
+
+/// Ordered by severity
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum ReleaseLevel {
+    NoChange,
+    /// Only the drawing of existing icons changed
+    Patch,
+    /// Icons, codepoints or axes were added, or an axis range widened
+    Minor,
+    /// Icons, codepoints or axes were removed, codepoints moved, an axis range narrowed or
+    /// units per em changed, users may break
+    Major,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReleaseSuggestion {
+    pub level: ReleaseLevel,
+    /// Why, one line per kind of change, most severe first
+    pub rationale: Vec<String>,
+}
+
+/// Classifies a release from [crate::cmp::compare_fonts] and
+/// [crate::cmp::check_codepoint_stability] results.
+///
+/// Violations for icons that were removed outright are already covered by `removed` so only
+/// names that still exist but lost a codepoint, and codepoints reassigned to other names,
+/// are reported as moves. Codepoint changes in `result` for names already reported as moves
+/// aren't repeated.
+pub fn suggest_release(
+    result: &CompareResult,
+    violations: &[CodepointViolation],
+) -> ReleaseSuggestion {
+    let mut level = ReleaseLevel::NoChange;
+    let mut rationale = Vec::new();
+
+    if !result.removed.is_empty() {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!(
+            "major: {} icon(s) removed: {}",
+            result.removed.len(),
+            sorted(&result.removed).join(", ")
+        ));
+    }
+    let moves: Vec<_> = violations
+        .iter()
+        .filter(|v| !v.name_now_has.is_empty() || !v.codepoint_now_has.is_empty())
+        .map(|v| {
+            let now = if v.name_now_has.is_empty() {
+                "removed".to_string()
+            } else {
+                v.name_now_has
+                    .iter()
+                    .map(|cp| format!("U+{cp:04X}"))
+                    .collect::<Vec<_>>()
+                    .join("/")
+            };
+            format!("{} U+{:04X} -> {now}", v.name, v.codepoint)
+        })
+        .collect();
+    if !moves.is_empty() {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!(
+            "major: {} codepoint(s) moved: {}",
+            moves.len(),
+            moves.join(", ")
+        ));
+    }
+    let moved = |name: &str| violations.iter().any(|v| v.name == name);
+    let (lost, gained): (Vec<_>, Vec<_>) = result
+        .codepoints
+        .iter()
+        .filter(|c| !moved(&c.name))
+        .partition(|c| c.old.iter().any(|cp| !c.new.contains(cp)));
+    if !lost.is_empty() {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!(
+            "major: {} icon(s) lost codepoints: {}",
+            lost.len(),
+            lost.iter()
+                .map(|c| codepoint_change(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    let axes = |pick: fn(&AxisChange) -> bool| -> Vec<String> {
+        result
+            .axes
+            .iter()
+            .filter(|a| pick(a))
+            .map(axis_change)
+            .collect()
+    };
+    let removed_axes = axes(|a| a.new.is_none());
+    if !removed_axes.is_empty() {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!(
+            "major: {} axis/axes removed: {}",
+            removed_axes.len(),
+            removed_axes.join(", ")
+        ));
+    }
+    let narrowed_axes =
+        axes(|a| matches!((a.old, a.new), (Some(old), Some(new)) if narrowed(old, new)));
+    if !narrowed_axes.is_empty() {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!(
+            "major: {} axis range(s) narrowed: {}",
+            narrowed_axes.len(),
+            narrowed_axes.join(", ")
+        ));
+    }
+    if let Some((old, new)) = result.upem {
+        level = level.max(ReleaseLevel::Major);
+        rationale.push(format!("major: units per em changed: {old} -> {new}"));
+    }
+    if !result.added.is_empty() {
+        level = level.max(ReleaseLevel::Minor);
+        rationale.push(format!(
+            "minor: {} icon(s) added: {}",
+            result.added.len(),
+            sorted(&result.added).join(", ")
+        ));
+    }
+    if !gained.is_empty() {
+        level = level.max(ReleaseLevel::Minor);
+        rationale.push(format!(
+            "minor: {} icon(s) gained codepoints: {}",
+            gained.len(),
+            gained
+                .iter()
+                .map(|c| codepoint_change(c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    let added_axes = axes(|a| a.old.is_none());
+    if !added_axes.is_empty() {
+        level = level.max(ReleaseLevel::Minor);
+        rationale.push(format!(
+            "minor: {} axis/axes added: {}",
+            added_axes.len(),
+            added_axes.join(", ")
+        ));
+    }
+    let widened_axes =
+        axes(|a| matches!((a.old, a.new), (Some(old), Some(new)) if !narrowed(old, new)));
+    if !widened_axes.is_empty() {
+        level = level.max(ReleaseLevel::Minor);
+        rationale.push(format!(
+            "minor: {} axis range(s) widened: {}",
+            widened_axes.len(),
+            widened_axes.join(", ")
+        ));
+    }
+    if !result.modified.is_empty() {
+        level = level.max(ReleaseLevel::Patch);
+        rationale.push(format!(
+            "patch: {} icon(s) modified: {}",
+            result.modified.len(),
+            sorted(&result.modified).join(", ")
+        ));
+    }
+
+    ReleaseSuggestion { level, rationale }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChangelogOptions {
+    heading: Option<String>,
+    image_url: Option<String>,
+}
+
+impl ChangelogOptions {
+    pub fn new() -> ChangelogOptions {
+        Default::default()
+    }
+
+    /// Starts the fragment with `## <heading>`, typically the version
+    pub fn with_heading(mut self, heading: &str) -> Self {
+        self.heading = Some(heading.to_string());
+        self
+    }
+
+    /// Links an image after each icon, `{name}` in `url` is replaced by the icon name
+    ///
+    /// For example `diffs/{name}.svg` to show renders produced alongside the changelog.
+    pub fn with_image_url(mut self, url: &str) -> Self {
+        self.image_url = Some(url.to_string());
+        self
+    }
+}
+
+/// Writes a markdown CHANGELOG fragment with Added, Modified and Removed sections.
+///
+/// Icons are sorted by name and empty sections are left out.
+pub fn changelog_markdown(result: &CompareResult, options: &ChangelogOptions) -> String {
+    let mut md = String::new();
+    if let Some(heading) = &options.heading {
+        md.push_str(&format!("## {heading}\n\n"));
+    }
+    let sections = [
+        ("Added", &result.added),
+        ("Modified", &result.modified),
+        ("Removed", &result.removed),
+    ];
+    for (title, names) in sections {
+        if names.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("### {title}\n\n"));
+        for name in sorted(names) {
+            md.push_str(&format!("- `{name}`"));
+            if let Some(url) = &options.image_url {
+                md.push_str(&format!(" ![{name}]({})", url.replace("{name}", name)));
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+    if sections.iter().all(|(_, names)| names.is_empty()) {
+        md.push_str("No changes.\n");
+    }
+    md
+}
+
+/// True if `new` drops part of `old` or moves its default, so some settings no longer apply
+fn narrowed(old: AxisRange, new: AxisRange) -> bool {
+    new.min > old.min || new.max < old.max || new.default != old.default
+}
+
+fn axis_range(range: &AxisRange) -> String {
+    format!("{}..{}..{}", range.min, range.default, range.max)
+}
+
+fn axis_change(change: &AxisChange) -> String {
+    match (&change.old, &change.new) {
+        (Some(old), Some(new)) => {
+            format!("{} {} -> {}", change.tag, axis_range(old), axis_range(new))
+        }
+        _ => change.tag.clone(),
+    }
+}
+
+fn codepoint_change(change: &CodepointChange) -> String {
+    let codepoints = |cps: &[u32]| {
+        cps.iter()
+            .map(|cp| format!("U+{cp:04X}"))
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    format!(
+        "{} {} -> {}",
+        change.name,
+        codepoints(&change.old),
+        codepoints(&change.new)
+    )
+}
+
+fn sorted(names: &[String]) -> Vec<&str> {
+    let mut names: Vec<_> = names.iter().map(String::as_str).collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::{
+        cmp::{
+            check_codepoint_stability, compare_fonts, AxisChange, AxisRange, CodepointChange,
+            CodepointViolation, CompareResult,
+        },
+        release::{changelog_markdown, suggest_release, ChangelogOptions, ReleaseLevel},
+        testdata,
+    };
+
+    fn result(added: &[&str], modified: &[&str], removed: &[&str]) -> CompareResult {
+        let to_vec = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        CompareResult {
+            added: to_vec(added),
+            modified: to_vec(modified),
+            removed: to_vec(removed),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_change() {
+        let suggestion = suggest_release(&result(&[], &[], &[]), &[]);
+        assert_eq!(ReleaseLevel::NoChange, suggestion.level);
+        assert!(suggestion.rationale.is_empty());
+    }
+
+    #[test]
+    fn geometry_only_is_patch() {
+        let suggestion = suggest_release(&result(&[], &["b", "a"], &[]), &[]);
+        assert_eq!(ReleaseLevel::Patch, suggestion.level);
+        assert_eq!(
+            vec!["patch: 2 icon(s) modified: a, b".to_string()],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn codepoint_move_is_major() {
+        let violations = [CodepointViolation {
+            name: "mail".to_string(),
+            codepoint: 0xe158,
+            name_now_has: vec![0xe200],
+            codepoint_now_has: vec![],
+        }];
+        let suggestion = suggest_release(&result(&["new"], &[], &[]), &violations);
+        assert_eq!(ReleaseLevel::Major, suggestion.level);
+        assert_eq!(
+            vec![
+                "major: 1 codepoint(s) moved: mail U+E158 -> U+E200".to_string(),
+                "minor: 1 icon(s) added: new".to_string(),
+            ],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn axis_removal_is_major() {
+        let fill = AxisRange {
+            min: 0.0,
+            default: 0.0,
+            max: 1.0,
+        };
+        let result = CompareResult {
+            axes: vec![AxisChange {
+                tag: "FILL".to_string(),
+                old: Some(fill),
+                new: None,
+            }],
+            ..Default::default()
+        };
+        let suggestion = suggest_release(&result, &[]);
+        assert_eq!(ReleaseLevel::Major, suggestion.level);
+        assert_eq!(
+            vec!["major: 1 axis/axes removed: FILL".to_string()],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn widened_axis_is_minor() {
+        let wght = |min| AxisRange {
+            min,
+            default: 400.0,
+            max: 700.0,
+        };
+        let result = CompareResult {
+            axes: vec![AxisChange {
+                tag: "wght".to_string(),
+                old: Some(wght(200.0)),
+                new: Some(wght(100.0)),
+            }],
+            ..Default::default()
+        };
+        let suggestion = suggest_release(&result, &[]);
+        assert_eq!(ReleaseLevel::Minor, suggestion.level);
+        assert_eq!(
+            vec!["minor: 1 axis range(s) widened: wght 200..400..700 -> 100..400..700".to_string()],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn upem_change_is_major() {
+        let result = CompareResult {
+            upem: Some((960, 1000)),
+            ..Default::default()
+        };
+        let suggestion = suggest_release(&result, &[]);
+        assert_eq!(ReleaseLevel::Major, suggestion.level);
+        assert_eq!(
+            vec!["major: units per em changed: 960 -> 1000".to_string()],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn codepoint_changes() {
+        let result = CompareResult {
+            codepoints: vec![
+                CodepointChange {
+                    name: "lan".to_string(),
+                    old: vec![0xeb2f],
+                    new: vec![0xeb2f, 0xeb30],
+                },
+                CodepointChange {
+                    name: "mail".to_string(),
+                    old: vec![0xe158],
+                    new: vec![0xe200],
+                },
+            ],
+            ..Default::default()
+        };
+
+        let suggestion = suggest_release(&result, &[]);
+        assert_eq!(ReleaseLevel::Major, suggestion.level);
+        assert_eq!(
+            vec![
+                "major: 1 icon(s) lost codepoints: mail U+E158 -> U+E200".to_string(),
+                "minor: 1 icon(s) gained codepoints: lan U+EB2F -> U+EB2F/U+EB30".to_string(),
+            ],
+            suggestion.rationale
+        );
+
+        // Already reported as a move
+        let violations = [CodepointViolation {
+            name: "mail".to_string(),
+            codepoint: 0xe158,
+            name_now_has: vec![0xe200],
+            codepoint_now_has: vec![],
+        }];
+        let suggestion = suggest_release(&result, &violations);
+        assert_eq!(
+            vec![
+                "major: 1 codepoint(s) moved: mail U+E158 -> U+E200".to_string(),
+                "minor: 1 icon(s) gained codepoints: lan U+EB2F -> U+EB2F/U+EB30".to_string(),
+            ],
+            suggestion.rationale
+        );
+    }
+
+    #[test]
+    fn changelog_sections() {
+        let md = changelog_markdown(
+            &result(&["settings"], &["label", "backspace"], &["menu"]),
+            &ChangelogOptions::new().with_heading("4.0.0"),
+        );
+        assert_eq!(
+            "## 4.0.0\n\n\
+             ### Added\n\n- `settings`\n\n\
+             ### Modified\n\n- `backspace`\n- `label`\n\n\
+             ### Removed\n\n- `menu`\n\n",
+            md
+        );
+    }
+
+    #[test]
+    fn changelog_images() {
+        let md = changelog_markdown(
+            &result(&[], &["label"], &[]),
+            &ChangelogOptions::new().with_image_url("diffs/{name}.svg"),
+        );
+        assert_eq!(
+            "### Modified\n\n- `label` ![label](diffs/label.svg)\n\n",
+            md
+        );
+    }
+
+    #[test]
+    fn changelog_no_changes() {
+        assert_eq!(
+            "No changes.\n",
+            changelog_markdown(&result(&[], &[], &[]), &ChangelogOptions::new())
+        );
+    }
+
+    #[test]
+    fn real_release() {
+        let old = FontRef::new(testdata::FULL_VF_OLD).unwrap();
+        let new = FontRef::new(testdata::FULL_VF_NEW).unwrap();
+
+        let suggestion = suggest_release(
+            &compare_fonts(&old, &new).unwrap(),
+            &check_codepoint_stability(&old, &new).unwrap(),
+        );
+
+        assert_eq!(ReleaseLevel::Major, suggestion.level);
+        assert_eq!(
+            vec![
+                "major: 1 icon(s) removed: menu".to_string(),
+                "minor: 1 icon(s) added: settings".to_string(),
+                "patch: 3 icon(s) modified: all_match, backspace, label".to_string(),
+            ],
+            suggestion.rationale
+        );
+    }
+}
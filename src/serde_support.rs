@@ -0,0 +1,112 @@
+//! (De)serialization for foreign types that don't support serde themselves
+
+pub(crate) mod glyph_id {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use skrifa::GlyphId;
+
+    pub(crate) fn serialize<S: Serializer>(
+        gid: &GlyphId,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        gid.to_u16().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<GlyphId, D::Error> {
+        Ok(GlyphId::new(u16::deserialize(deserializer)?))
+    }
+}
+
+/// A [skrifa::instance::Location] as a list of normalized coordinates in axis order
+pub(crate) mod location {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use skrifa::{instance::Location, raw::types::F2Dot14};
+
+    pub(crate) fn serialize<S: Serializer>(
+        location: &Location,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        location
+            .coords()
+            .iter()
+            .map(|c| c.to_f32())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Location, D::Error> {
+        let coords = Vec::<f32>::deserialize(deserializer)?;
+        let mut location = Location::new(coords.len());
+        for (coord, value) in location.coords_mut().iter_mut().zip(coords) {
+            *coord = F2Dot14::from_f32(value);
+        }
+        Ok(location)
+    }
+}
+
+/// A [kurbo::Affine] as its six coefficients
+pub(crate) mod affine {
+    use kurbo::Affine;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        affine: &Affine,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        affine.as_coeffs().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Affine, D::Error> {
+        Ok(Affine::new(<[f64; 6]>::deserialize(deserializer)?))
+    }
+}
+
+/// A [kurbo::BezPath] as svg path data
+pub(crate) mod bez_path {
+    use kurbo::BezPath;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        path: &BezPath,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.to_svg().serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BezPath, D::Error> {
+        let svg = String::deserialize(deserializer)?;
+        BezPath::from_svg(&svg).map_err(D::Error::custom)
+    }
+}
+
+/// A list of [skrifa::Tag]s as strings, e.g. `["ss01"]`
+pub(crate) mod tags {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use skrifa::Tag;
+
+    pub(crate) fn serialize<S: Serializer>(tags: &[Tag], serializer: S) -> Result<S::Ok, S::Error> {
+        tags.iter()
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Tag>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|tag| {
+                Tag::new_checked(tag.as_bytes())
+                    .map_err(|_| D::Error::custom(format!("Invalid tag '{tag}'")))
+            })
+            .collect()
+    }
+}
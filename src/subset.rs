@@ -0,0 +1,335 @@
+//! Cuts an icon font down to a few icons, so apps ship only the icons they use
+
+use std::collections::HashSet;
+
+use skrifa::{
+    instance::LocationRef,
+    raw::{
+        tables::{
+            glyf::{Glyf, Glyph},
+            gvar::Gvar,
+        },
+        ReadError, TableProvider,
+    },
+    FontRef, GlyphId, MetadataProvider, Tag,
+};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        cmap::Cmap,
+        gsub::{ExtensionSubtable, Gsub, LigatureSubstFormat1, SubstitutionLookup},
+        head::Head,
+        layout::CoverageTableBuilder,
+    },
+    FontBuilder, OffsetMarker,
+};
+
+use crate::{
+    error::SubsetError,
+    iconid::{IconIdentifier, Icons},
+};
+
+/// Produces a font with only `icons`, reachable by their codepoints and ligatures, at every
+/// location the font varies over.
+///
+/// Glyph ids are retained, other glyphs are left empty. That drops their outlines and
+/// variations, the bulk of an icon font, while tables indexed by glyph can be copied as they are.
+/// Icons are resolved at the default location, glyphs they substitute to elsewhere, e.g. when
+/// filled, are kept too.
+pub fn subset(font: &FontRef, icons: &[IconIdentifier]) -> Result<Vec<u8>, SubsetError> {
+    let glyphs = retained_glyphs(font, icons)?;
+    let read_error = |table| move |e| SubsetError::ReadError(table, e);
+
+    let mut builder = FontBuilder::new();
+    let (glyf, loca) = subset_glyf(font, &glyphs)?;
+    let mut head: Head = font.head().map_err(read_error("head"))?.to_owned_table();
+    head.index_to_loc_format = 1;
+    builder.add_raw(Tag::new(b"glyf"), glyf);
+    builder.add_raw(Tag::new(b"loca"), loca);
+    if let Ok(gvar) = font.gvar() {
+        builder.add_raw(
+            Tag::new(b"gvar"),
+            subset_gvar(gvar, &glyphs).map_err(read_error("gvar"))?,
+        );
+    }
+    let cmap = Cmap::from_mappings(
+        font.charmap()
+            .mappings()
+            .filter(|(_, gid)| glyphs.contains(gid))
+            .filter_map(|(cp, gid)| char::from_u32(cp).map(|c| (c, gid))),
+    )
+    .map_err(|e| SubsetError::CompileError(e.to_string()))?;
+    builder
+        .add_table(&head)
+        .and_then(|builder| builder.add_table(&cmap))
+        .map_err(|e| SubsetError::CompileError(e.to_string()))?;
+    if let Ok(gsub) = font.gsub() {
+        let mut gsub: Gsub = gsub.to_owned_table();
+        prune_ligatures(&mut gsub, &glyphs);
+        builder
+            .add_table(&gsub)
+            .map_err(|e| SubsetError::CompileError(e.to_string()))?;
+    }
+    Ok(builder.copy_missing_tables(font.clone()).build())
+}
+
+/// The icon glyphs, what they substitute to, the glyphs spelling their names and the
+/// components of any composites among them
+fn retained_glyphs(
+    font: &FontRef,
+    icons: &[IconIdentifier],
+) -> Result<HashSet<GlyphId>, SubsetError> {
+    let location = LocationRef::default();
+    let mut glyphs = HashSet::from([GlyphId::NOTDEF]);
+    for identifier in icons {
+        let gid = identifier
+            .resolve(font, &location)
+            .map_err(|e| SubsetError::ResolutionError(identifier.clone(), e))?;
+        glyphs.insert(gid);
+    }
+    if let Ok(gsub) = font.gsub() {
+        glyphs = gsub
+            .closure_glyphs(glyphs)
+            .map_err(|e| SubsetError::ReadError("GSUB", e))?;
+    }
+
+    let charmap = font.charmap();
+    let spellings: Vec<GlyphId> = font
+        .icons()
+        .map_err(SubsetError::IconsError)?
+        .iter()
+        .filter(|icon| glyphs.contains(&icon.gid))
+        .flat_map(|icon| icon.names.iter().flat_map(|name| name.chars()))
+        .filter_map(|c| charmap.map(c))
+        .collect();
+    glyphs.extend(spellings);
+
+    if let (Ok(glyf), Ok(loca)) = (font.glyf(), font.loca(None)) {
+        let mut pending: Vec<GlyphId> = glyphs.iter().copied().collect();
+        while let Some(gid) = pending.pop() {
+            if let Ok(Some(Glyph::Composite(composite))) = loca.get_glyf(gid, &glyf) {
+                for component in composite.components() {
+                    if glyphs.insert(component.glyph) {
+                        pending.push(component.glyph);
+                    }
+                }
+            }
+        }
+    }
+    Ok(glyphs)
+}
+
+/// glyf and long loca with only `glyphs` drawn
+fn subset_glyf(
+    font: &FontRef,
+    glyphs: &HashSet<GlyphId>,
+) -> Result<(Vec<u8>, Vec<u8>), SubsetError> {
+    let glyf: Glyf = font.glyf().map_err(|e| SubsetError::ReadError("glyf", e))?;
+    let loca = font
+        .loca(None)
+        .map_err(|e| SubsetError::ReadError("loca", e))?;
+    let data = glyf.offset_data().as_bytes();
+    let mut new_glyf = Vec::with_capacity(data.len());
+    let mut new_loca = Vec::with_capacity(loca.len() * 4);
+    for gid in 0..loca.len().saturating_sub(1) {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if !glyphs.contains(&GlyphId::new(gid as u16)) {
+            continue;
+        }
+        let (Some(start), Some(end)) = (loca.get_raw(gid), loca.get_raw(gid + 1)) else {
+            return Err(SubsetError::ReadError("loca", ReadError::OutOfBounds));
+        };
+        let Some(bytes) = data.get(start as usize..end as usize) else {
+            return Err(SubsetError::ReadError("glyf", ReadError::OutOfBounds));
+        };
+        new_glyf.extend_from_slice(bytes);
+        new_glyf.resize(new_glyf.len().next_multiple_of(4), 0);
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+    Ok((new_glyf, new_loca))
+}
+
+/// gvar with variations for only `glyphs`, written with long offsets
+fn subset_gvar(gvar: Gvar, glyphs: &HashSet<GlyphId>) -> Result<Vec<u8>, ReadError> {
+    let data = gvar.offset_data();
+    let glyph_count = gvar.glyph_count() as usize;
+    let shared_start = gvar.shared_tuples_offset().to_u32() as usize;
+    let shared_len = gvar.shared_tuple_count() as usize * gvar.axis_count() as usize * 2;
+    let shared = data
+        .slice(shared_start..shared_start + shared_len)
+        .ok_or(ReadError::OutOfBounds)?;
+    let array_start = gvar.glyph_variation_data_array_offset() as usize;
+    let offsets = gvar.glyph_variation_data_offsets();
+
+    let header_len = 20;
+    let new_shared_start = header_len + (glyph_count + 1) * 4;
+    let new_array_start = new_shared_start + shared_len;
+    let mut new_offsets = Vec::with_capacity((glyph_count + 1) * 4);
+    let mut variations = Vec::new();
+    for gid in 0..glyph_count {
+        new_offsets.extend_from_slice(&(variations.len() as u32).to_be_bytes());
+        if !glyphs.contains(&GlyphId::new(gid as u16)) {
+            continue;
+        }
+        let start = array_start + offsets.get(gid)?.get() as usize;
+        let end = array_start + offsets.get(gid + 1)?.get() as usize;
+        variations.extend_from_slice(
+            data.slice(start..end)
+                .ok_or(ReadError::OutOfBounds)?
+                .as_bytes(),
+        );
+    }
+    new_offsets.extend_from_slice(&(variations.len() as u32).to_be_bytes());
+
+    let mut gvar_bytes = Vec::with_capacity(new_array_start + variations.len());
+    // version, axisCount and sharedTupleCount are unchanged
+    gvar_bytes.extend_from_slice(&data.as_bytes()[..8]);
+    gvar_bytes.extend_from_slice(&(new_shared_start as u32).to_be_bytes());
+    gvar_bytes.extend_from_slice(&(glyph_count as u16).to_be_bytes());
+    // Long offsets
+    gvar_bytes.extend_from_slice(&(gvar.flags().bits() | 1).to_be_bytes());
+    gvar_bytes.extend_from_slice(&(new_array_start as u32).to_be_bytes());
+    gvar_bytes.extend_from_slice(&new_offsets);
+    gvar_bytes.extend_from_slice(shared.as_bytes());
+    gvar_bytes.extend_from_slice(&variations);
+    Ok(gvar_bytes)
+}
+
+/// Drops ligatures that produce or consume glyphs that are no longer drawn
+fn prune_ligatures(gsub: &mut Gsub, glyphs: &HashSet<GlyphId>) {
+    for lookup in gsub.lookup_list.lookups.iter_mut() {
+        match &mut **lookup {
+            SubstitutionLookup::Ligature(lookup) => {
+                for subtable in lookup.subtables.iter_mut() {
+                    prune_subtable(subtable, glyphs);
+                }
+            }
+            SubstitutionLookup::Extension(lookup) => {
+                for subtable in lookup.subtables.iter_mut() {
+                    if let ExtensionSubtable::Ligature(extension) = &mut **subtable {
+                        prune_subtable(&mut extension.extension, glyphs);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn prune_subtable(subtable: &mut LigatureSubstFormat1, glyphs: &HashSet<GlyphId>) {
+    let first_glyphs: Vec<GlyphId> = subtable.coverage.iter().collect();
+    let mut coverage = Vec::new();
+    let mut sets = Vec::new();
+    for (first, mut set) in first_glyphs
+        .into_iter()
+        .zip(std::mem::take(&mut subtable.ligature_sets))
+    {
+        if !glyphs.contains(&first) {
+            continue;
+        }
+        set.ligatures.retain(|ligature| {
+            glyphs.contains(&ligature.ligature_glyph)
+                && ligature
+                    .component_glyph_ids
+                    .iter()
+                    .all(|gid| glyphs.contains(gid))
+        });
+        if !set.ligatures.is_empty() {
+            coverage.push(first);
+            sets.push(set);
+        }
+    }
+    subtable.coverage = OffsetMarker::new(CoverageTableBuilder::from_glyphs(coverage).build());
+    subtable.ligature_sets = sets;
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{raw::TableProvider, FontRef, MetadataProvider};
+
+    use crate::{
+        error::SubsetError,
+        icon2svg::{draw_icon, DrawOptions},
+        iconid::{self, IconIdentifier, Icons},
+        pathstyle::PathStyle,
+        subset::subset,
+        testdata,
+    };
+
+    fn draw(font: &FontRef, identifier: &IconIdentifier, axes: &[(&str, f32)]) -> String {
+        let loc = font.axes().location(axes);
+        draw_icon(
+            font,
+            &DrawOptions::new(identifier.clone(), 24.0, (&loc).into(), PathStyle::Compact),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_only_requested_icons() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let subset_data = subset(&font, std::slice::from_ref(&iconid::LAN)).unwrap();
+        let subset = FontRef::new(&subset_data).unwrap();
+
+        assert!(subset_data.len() < testdata::ICON_FONT.len());
+        assert_eq!(
+            font.maxp().unwrap().num_glyphs(),
+            subset.maxp().unwrap().num_glyphs()
+        );
+        for axes in [
+            &[][..],
+            &[("FILL", 1.0)][..],
+            &[("wght", 700.0), ("GRAD", 200.0), ("opsz", 48.0)][..],
+        ] {
+            assert_eq!(
+                draw(&font, &iconid::LAN, axes),
+                draw(&subset, &iconid::LAN, axes),
+                "{axes:?}"
+            );
+        }
+        let mail = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        );
+        assert!(draw_icon(&subset, &mail).is_err());
+    }
+
+    #[test]
+    fn keeps_ligatures_of_icons_given_by_codepoint() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let names: Vec<String> = font
+            .icons()
+            .unwrap()
+            .into_iter()
+            .find(|icon| icon.codepoints.contains(&0xe158))
+            .unwrap()
+            .names;
+
+        let subset_data = subset(&font, std::slice::from_ref(&iconid::MAIL)).unwrap();
+        let subset = FontRef::new(&subset_data).unwrap();
+
+        assert!(!names.is_empty());
+        for name in names {
+            let by_name = IconIdentifier::Name(name.into());
+            assert_eq!(draw(&font, &by_name, &[]), draw(&subset, &by_name, &[]));
+        }
+        assert!(subset
+            .icons()
+            .unwrap()
+            .iter()
+            .all(|icon| icon.codepoints.contains(&0xe158)));
+    }
+
+    #[test]
+    fn unresolved_icon() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        assert!(matches!(
+            subset(&font, &[IconIdentifier::Name("nope".into())]),
+            Err(SubsetError::ResolutionError(..))
+        ));
+    }
+}
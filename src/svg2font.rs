@@ -0,0 +1,654 @@
+//! Builds Google-style icon fonts from svgs, the reverse of [crate::icon2svg].
+//!
+//! Each icon is a glyph reachable by a PUA codepoint and by a ligature of its name.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+use kurbo::{Affine, BezPath, CubicBez, PathEl, Point, Rect, Shape};
+use skrifa::raw::types::{FWord, Fixed, NameId, Tag, UfWord};
+use write_fonts::{
+    tables::{
+        cmap::Cmap,
+        glyf::{Bbox, GlyfLocaBuilder, Glyph, SimpleGlyph},
+        gsub::{Gsub, Ligature, LigatureSet, LigatureSubstFormat1, SubstitutionLookupList},
+        head::Head,
+        hhea::Hhea,
+        hmtx::{Hmtx, LongMetric},
+        layout::{
+            CoverageTableBuilder, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+        maxp::Maxp,
+        name::{Name, NameRecord},
+        os2::Os2,
+        post::Post,
+    },
+    types::GlyphId,
+    FontBuilder, OffsetMarker,
+};
+
+use crate::error::BuildFontError;
+
+// The first codepoint in the Basic Multilingual Plane Private Use Area
+const FIRST_PUA_CODEPOINT: u32 = 0xE000;
+const LAST_BMP_PUA_CODEPOINT: u32 = 0xF8FF;
+
+/// Maximum distance, in font units, between a cubic and the quadratics that replace it
+const CUBIC_TO_QUAD_ACCURACY: f64 = 1.0;
+
+struct IconSource {
+    name: String,
+    codepoint: Option<u32>,
+    /// In font units, Y-up
+    path: BezPath,
+}
+
+/// Accumulates icons and compiles them into a TrueType font.
+///
+/// Icon names may contain only ascii alphanumerics and `_`, as in Google icon fonts. Icons without
+/// an explicit codepoint are assigned the lowest free codepoint in the BMP Private Use Area.
+pub struct IconFontBuilder {
+    family_name: String,
+    upem: u16,
+    icons: Vec<IconSource>,
+}
+
+impl IconFontBuilder {
+    pub fn new(family_name: &str) -> IconFontBuilder {
+        IconFontBuilder {
+            family_name: family_name.to_string(),
+            upem: 960,
+            icons: Vec::new(),
+        }
+    }
+
+    /// Units per em, also the width and height of every icon. Defaults to 960, as in Material Symbols.
+    pub fn with_upem(mut self, upem: u16) -> IconFontBuilder {
+        self.upem = upem;
+        self
+    }
+
+    /// Add an icon whose outline is already in font units, Y-up, in the em square
+    pub fn add_path(
+        &mut self,
+        name: &str,
+        codepoint: Option<u32>,
+        path: BezPath,
+    ) -> Result<&mut Self, BuildFontError> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(BuildFontError::InvalidName(name.to_string()));
+        }
+        if self.icons.iter().any(|icon| icon.name == name) {
+            return Err(BuildFontError::DuplicateName(name.to_string()));
+        }
+        if let Some(cp) = codepoint {
+            if let Some(icon) = self.icons.iter().find(|icon| icon.codepoint == Some(cp)) {
+                return Err(BuildFontError::DuplicateCodepoint(cp, icon.name.clone()));
+            }
+        }
+        self.icons.push(IconSource {
+            name: name.to_string(),
+            codepoint,
+            path,
+        });
+        Ok(self)
+    }
+
+    /// Add an icon from a simple svg; one or more untransformed paths with a viewBox on the root.
+    ///
+    /// The viewBox is scaled to fill the em square.
+    pub fn add_svg(
+        &mut self,
+        name: &str,
+        codepoint: Option<u32>,
+        svg: &str,
+    ) -> Result<&mut Self, BuildFontError> {
+        let path = parse_svg(name, svg, self.upem)?;
+        self.add_path(name, codepoint, path)
+    }
+
+    /// Add every `*.svg` in `dir`, named by file stem, in name order.
+    ///
+    /// Codepoints are looked up in `codepoints`, e.g. as read by [parse_codepoints]; icons
+    /// absent from it are auto-assigned.
+    pub fn add_svg_dir(
+        &mut self,
+        dir: &Path,
+        codepoints: &HashMap<String, u32>,
+    ) -> Result<&mut Self, BuildFontError> {
+        let mut files = fs::read_dir(dir)
+            .map_err(|e| BuildFontError::IoError(dir.to_path_buf(), e))?
+            .map(|entry| {
+                entry
+                    .map(|e| e.path())
+                    .map_err(|e| BuildFontError::IoError(dir.to_path_buf(), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        files.retain(|f| f.extension().is_some_and(|ext| ext == "svg"));
+        files.sort();
+
+        for file in files {
+            let Some(name) = file.file_stem().and_then(|s| s.to_str()) else {
+                return Err(BuildFontError::InvalidName(file.display().to_string()));
+            };
+            let svg =
+                fs::read_to_string(&file).map_err(|e| BuildFontError::IoError(file.clone(), e))?;
+            self.add_svg(name, codepoints.get(name).copied(), &svg)?;
+        }
+        Ok(self)
+    }
+
+    /// Compile the font
+    pub fn build(&self) -> Result<Vec<u8>, BuildFontError> {
+        let icons = self.assign_codepoints()?;
+
+        // Glyph order: .notdef, ligature components, icons
+        let components: BTreeSet<char> = icons
+            .iter()
+            .filter(|(icon, _)| icon.name.chars().count() > 1)
+            .flat_map(|(icon, _)| icon.name.chars())
+            .collect();
+        let single_chars: BTreeSet<char> = icons
+            .iter()
+            .filter_map(|(icon, _)| single_char(&icon.name))
+            .collect();
+
+        let mut glyph_names = vec![".notdef".to_string()];
+        let mut glyphs = vec![Glyph::Simple(notdef(self.upem)?)];
+        let mut advances = vec![self.upem];
+        let mut cmap = Vec::new();
+        let mut gid_for_char = HashMap::new();
+
+        // A single char icon doubles as the component glyph for that char
+        for c in components.iter().filter(|c| !single_chars.contains(c)) {
+            let gid = GlyphId::new(glyphs.len() as u16);
+            gid_for_char.insert(*c, gid);
+            cmap.push((*c, gid));
+            glyph_names.push(component_glyph_name(*c));
+            glyphs.push(Glyph::Empty);
+            advances.push(0);
+        }
+        let first_icon_gid = glyphs.len();
+        for (i, (icon, codepoint)) in icons.iter().enumerate() {
+            let gid = GlyphId::new((first_icon_gid + i) as u16);
+            let pua =
+                char::from_u32(*codepoint).ok_or(BuildFontError::InvalidCodepoint(*codepoint))?;
+            cmap.push((pua, gid));
+            if let Some(c) = single_char(&icon.name) {
+                gid_for_char.insert(c, gid);
+                cmap.push((c, gid));
+            }
+            glyph_names.push(icon.name.clone());
+            glyphs.push(glyph(&icon.name, &icon.path)?);
+            advances.push(self.upem);
+        }
+        if glyphs.len() > u16::MAX as usize {
+            return Err(BuildFontError::TooManyGlyphs(glyphs.len()));
+        }
+
+        let ligatures: Vec<_> = icons
+            .iter()
+            .enumerate()
+            .filter(|(_, (icon, _))| icon.name.chars().count() > 1)
+            .map(|(i, (icon, _))| {
+                let gids: Vec<_> = icon.name.chars().map(|c| gid_for_char[&c]).collect();
+                (gids, GlyphId::new((first_icon_gid + i) as u16))
+            })
+            .collect();
+
+        self.compile(&glyph_names, &glyphs, &advances, cmap, ligatures)
+    }
+
+    /// Pair each icon with an explicit or auto-assigned codepoint
+    fn assign_codepoints(&self) -> Result<Vec<(&IconSource, u32)>, BuildFontError> {
+        let used: BTreeSet<u32> = self.icons.iter().filter_map(|i| i.codepoint).collect();
+        let mut free =
+            (FIRST_PUA_CODEPOINT..=LAST_BMP_PUA_CODEPOINT).filter(|cp| !used.contains(cp));
+        self.icons
+            .iter()
+            .map(|icon| {
+                let cp = match icon.codepoint {
+                    Some(cp) => cp,
+                    None => free
+                        .next()
+                        .ok_or_else(|| BuildFontError::OutOfCodepoints(icon.name.clone()))?,
+                };
+                Ok((icon, cp))
+            })
+            .collect()
+    }
+
+    fn compile(
+        &self,
+        glyph_names: &[String],
+        glyphs: &[Glyph],
+        advances: &[u16],
+        cmap: Vec<(char, GlyphId)>,
+        ligatures: Vec<(Vec<GlyphId>, GlyphId)>,
+    ) -> Result<Vec<u8>, BuildFontError> {
+        let mut glyf_builder = GlyfLocaBuilder::new();
+        let mut bbox: Option<Bbox> = None;
+        let mut maxp = Maxp::new(glyphs.len() as u16);
+        let (mut max_points, mut max_contours) = (0u16, 0u16);
+        let mut h_metrics = Vec::with_capacity(glyphs.len());
+        for ((glyph, advance), name) in glyphs.iter().zip(advances).zip(glyph_names) {
+            glyf_builder
+                .add_glyph(glyph)
+                .map_err(|e| BuildFontError::CompileError(format!("{name}: {e}")))?;
+            let glyph_bbox = glyph.bbox();
+            if let Some(glyph_bbox) = glyph_bbox {
+                bbox = Some(bbox.map(|b| b.union(glyph_bbox)).unwrap_or(glyph_bbox));
+            }
+            if let Glyph::Simple(simple) = glyph {
+                max_contours = max_contours.max(simple.contours().len() as u16);
+                max_points =
+                    max_points.max(simple.contours().iter().map(|c| c.len()).sum::<usize>() as u16);
+            }
+            h_metrics.push(LongMetric::new(
+                *advance,
+                glyph_bbox.map(|b| b.x_min).unwrap_or_default(),
+            ));
+        }
+        let (glyf, loca, loca_format) = glyf_builder.build();
+        let bbox = bbox.unwrap_or_default();
+
+        maxp.max_points = Some(max_points);
+        maxp.max_contours = Some(max_contours);
+        maxp.max_composite_points = Some(0);
+        maxp.max_composite_contours = Some(0);
+        maxp.max_zones = Some(1);
+        maxp.max_twilight_points = Some(0);
+        maxp.max_storage = Some(0);
+        maxp.max_function_defs = Some(0);
+        maxp.max_instruction_defs = Some(0);
+        maxp.max_stack_elements = Some(0);
+        maxp.max_size_of_instructions = Some(0);
+        maxp.max_component_elements = Some(0);
+        maxp.max_component_depth = Some(0);
+
+        let head = Head {
+            font_revision: Fixed::from_f64(1.0),
+            units_per_em: self.upem,
+            x_min: bbox.x_min,
+            y_min: bbox.y_min,
+            x_max: bbox.x_max,
+            y_max: bbox.y_max,
+            lowest_rec_ppem: 6,
+            index_to_loc_format: loca_format as i16,
+            ..Default::default()
+        };
+        let hhea = Hhea::new(
+            FWord::new(self.upem as i16),
+            FWord::new(0),
+            FWord::new(0),
+            UfWord::new(advances.iter().copied().max().unwrap_or_default()),
+            FWord::new(
+                h_metrics
+                    .iter()
+                    .map(|m| m.side_bearing)
+                    .min()
+                    .unwrap_or_default(),
+            ),
+            FWord::new(0),
+            FWord::new(bbox.x_max),
+            1,
+            0,
+            0,
+            h_metrics.len() as u16,
+        );
+        let os2 = Os2 {
+            s_typo_ascender: self.upem as i16,
+            us_win_ascent: self.upem,
+            ach_vend_id: Tag::new(b"NONE"),
+            us_first_char_index: cmap
+                .iter()
+                .map(|(c, _)| *c as u32)
+                .min()
+                .unwrap_or_default()
+                .min(0xFFFF) as u16,
+            us_last_char_index: cmap
+                .iter()
+                .map(|(c, _)| *c as u32)
+                .max()
+                .unwrap_or_default()
+                .min(0xFFFF) as u16,
+            ..Default::default()
+        };
+        let cmap = Cmap::from_mappings(cmap)
+            .map_err(|e| BuildFontError::CompileError(format!("cmap: {e}")))?;
+        let post = Post::new_v2(glyph_names.iter().map(String::as_str));
+
+        let mut builder = FontBuilder::new();
+        builder
+            .add_table(&head)
+            .and_then(|b| b.add_table(&hhea))
+            .and_then(|b| b.add_table(&maxp))
+            .and_then(|b| b.add_table(&os2))
+            .and_then(|b| b.add_table(&Hmtx::new(h_metrics, Vec::new())))
+            .and_then(|b| b.add_table(&cmap))
+            .and_then(|b| b.add_table(&loca))
+            .and_then(|b| b.add_table(&glyf))
+            .and_then(|b| b.add_table(&self.name_table()))
+            .and_then(|b| b.add_table(&post))
+            .and_then(|b| b.add_table(&gsub(ligatures)))
+            .map_err(|e| BuildFontError::CompileError(e.to_string()))?;
+        Ok(builder.build())
+    }
+
+    fn name_table(&self) -> Name {
+        let postscript_name: String = self
+            .family_name
+            .chars()
+            .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%".contains(*c))
+            .collect();
+        let names = [
+            (NameId::FAMILY_NAME, self.family_name.clone()),
+            (NameId::SUBFAMILY_NAME, "Regular".to_string()),
+            (
+                NameId::UNIQUE_ID,
+                format!("{};{postscript_name}-Regular", env!("CARGO_PKG_NAME")),
+            ),
+            (NameId::FULL_NAME, format!("{} Regular", self.family_name)),
+            (NameId::VERSION_STRING, "Version 1.000".to_string()),
+            (
+                NameId::POSTSCRIPT_NAME,
+                format!("{postscript_name}-Regular"),
+            ),
+        ];
+        Name::new(
+            names
+                .into_iter()
+                .map(|(name_id, value)| {
+                    // Windows, Unicode BMP, English (United States)
+                    NameRecord::new(3, 1, 0x409, name_id, OffsetMarker::new(value))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Parse a codepoints file, lines of `name hex_codepoint` as shipped alongside Google icon fonts.
+pub fn parse_codepoints(content: &str) -> Result<HashMap<String, u32>, BuildFontError> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let invalid = || BuildFontError::InvalidCodepointsLine(line.to_string());
+            let (name, cp) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let cp = u32::from_str_radix(cp.trim(), 16).map_err(|_| invalid())?;
+            Ok((name.to_string(), cp))
+        })
+        .collect()
+}
+
+fn parse_svg(name: &str, svg: &str, upem: u16) -> Result<BezPath, BuildFontError> {
+    let svg_error = |reason: String| BuildFontError::SvgError(name.to_string(), reason);
+    let doc = roxmltree::Document::parse(svg).map_err(|e| svg_error(e.to_string()))?;
+    let root = doc.root_element();
+
+    let view_box: Vec<f64> = root
+        .attribute("viewBox")
+        .ok_or_else(|| svg_error("No viewBox".to_string()))?
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|v| v.parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| svg_error(format!("Bad viewBox: {e}")))?;
+    let [min_x, min_y, width, height] = view_box[..] else {
+        return Err(svg_error(format!(
+            "viewBox must have 4 values, got {view_box:?}"
+        )));
+    };
+    if width <= 0.0 || height <= 0.0 {
+        return Err(svg_error(format!(
+            "viewBox must be non-empty, got {view_box:?}"
+        )));
+    }
+
+    // svg is Y-down, fonts are Y-up
+    let (sx, sy) = (upem as f64 / width, upem as f64 / height);
+    let to_font_units = Affine::new([sx, 0.0, 0.0, -sy, -min_x * sx, upem as f64 + min_y * sy]);
+
+    let mut path = BezPath::new();
+    for node in root.descendants().filter(|n| n.is_element()) {
+        if node.has_attribute("transform") {
+            return Err(svg_error("Transforms are not supported".to_string()));
+        }
+        if !node.has_tag_name("path") {
+            continue;
+        }
+        let d = node.attribute("d").unwrap_or_default();
+        let subpath = BezPath::from_svg(d).map_err(|e| svg_error(format!("Bad path: {e}")))?;
+        path.extend(subpath);
+    }
+    if path.elements().is_empty() {
+        return Err(svg_error("No path data".to_string()));
+    }
+    Ok(to_font_units * path)
+}
+
+/// glyf is quadratic, replace cubics with approximating quadratics
+fn cubics_to_quads(path: &BezPath) -> BezPath {
+    let mut quads = BezPath::new();
+    let mut subpath_start = Point::ZERO;
+    let mut curr = Point::ZERO;
+    for el in path.elements() {
+        match *el {
+            PathEl::MoveTo(p) => {
+                quads.move_to(p);
+                subpath_start = p;
+                curr = p;
+            }
+            PathEl::LineTo(p) => {
+                quads.line_to(p);
+                curr = p;
+            }
+            PathEl::QuadTo(p1, p2) => {
+                quads.quad_to(p1, p2);
+                curr = p2;
+            }
+            PathEl::CurveTo(p1, p2, p3) => {
+                for (_, _, quad) in CubicBez::new(curr, p1, p2, p3).to_quads(CUBIC_TO_QUAD_ACCURACY)
+                {
+                    quads.quad_to(quad.p1, quad.p2);
+                }
+                curr = p3;
+            }
+            PathEl::ClosePath => {
+                quads.close_path();
+                curr = subpath_start;
+            }
+        }
+    }
+    quads
+}
+
+fn glyph(name: &str, path: &BezPath) -> Result<Glyph, BuildFontError> {
+    SimpleGlyph::from_bezpath(&cubics_to_quads(path))
+        .map(Glyph::Simple)
+        .map_err(|e| BuildFontError::CompileError(format!("{name}: {e:?}")))
+}
+
+/// The conventional hollow box
+fn notdef(upem: u16) -> Result<SimpleGlyph, BuildFontError> {
+    let upem = upem as f64;
+    let outer = Rect::new(upem * 0.1, 0.0, upem * 0.9, upem);
+    let inner = outer.inset(-upem * 0.05);
+    let mut path = outer.to_path(0.1);
+    // Counter-wise so the inside isn't filled
+    path.extend(inner.to_path(0.1).reverse_subpaths());
+    SimpleGlyph::from_bezpath(&path)
+        .map_err(|e| BuildFontError::CompileError(format!(".notdef: {e:?}")))
+}
+
+fn single_char(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn component_glyph_name(c: char) -> String {
+    match c {
+        '_' => "underscore".to_string(),
+        c if c.is_ascii_alphabetic() => c.to_string(),
+        c => format!("uni{:04X}", c as u32),
+    }
+}
+
+/// A liga feature with one ligature per icon, for all scripts
+fn gsub(ligatures: Vec<(Vec<GlyphId>, GlyphId)>) -> Gsub {
+    let mut by_first: HashMap<_, Vec<_>> = HashMap::new();
+    for (components, ligature) in ligatures {
+        by_first
+            .entry(components[0])
+            .or_default()
+            .push(Ligature::new(ligature, components[1..].to_vec()));
+    }
+    let mut first_glyphs: Vec<_> = by_first.keys().copied().collect();
+    first_glyphs.sort();
+    let ligature_sets = first_glyphs
+        .iter()
+        .map(|first| {
+            let mut ligatures = by_first.remove(first).unwrap_or_default();
+            // Longest first so that the longest name wins, e.g. mail_outline over mail
+            ligatures.sort_by_key(|l| std::cmp::Reverse(l.component_glyph_ids.len()));
+            LigatureSet::new(ligatures)
+        })
+        .collect();
+    let coverage = CoverageTableBuilder::from_glyphs(first_glyphs).build();
+    let lookup = Lookup::new(
+        LookupFlag::empty(),
+        vec![LigatureSubstFormat1::new(coverage, ligature_sets)],
+        0,
+    );
+
+    let lang_sys = LangSys::new(vec![0]);
+    let scripts = [Tag::new(b"DFLT"), Tag::new(b"latn")]
+        .into_iter()
+        .map(|tag| ScriptRecord::new(tag, Script::new(Some(lang_sys.clone()), Vec::new())))
+        .collect();
+    Gsub::new(
+        ScriptList::new(scripts),
+        FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"liga"),
+            Feature::new(None, vec![0]),
+        )]),
+        SubstitutionLookupList::new(vec![
+            write_fonts::tables::gsub::SubstitutionLookup::Ligature(lookup),
+        ]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use skrifa::{instance::Location, FontRef, GlyphId, MetadataProvider};
+
+    use crate::{
+        icon2svg::{draw_icon, DrawOptions},
+        iconid::{Icon, IconIdentifier, Icons},
+        ligatures::Ligatures,
+        pathstyle::PathStyle,
+        testdata,
+    };
+
+    use super::{parse_codepoints, IconFontBuilder};
+
+    fn build_test_font() -> Vec<u8> {
+        let mut builder = IconFontBuilder::new("Test Icons");
+        builder
+            .add_svg("lan", Some(0xEB2F), testdata::LAN_SVG)
+            .unwrap()
+            .add_svg("mail", None, testdata::MAIL_SVG)
+            .unwrap()
+            .add_svg("x", None, testdata::MAN_SVG)
+            .unwrap()
+            .add_svg(
+                "mail_outline",
+                None,
+                r#"<svg viewBox="0 0 24 24"><g><path d="M2,2 C2,12 22,12 22,22 L2,22 Z"/></g></svg>"#,
+            )
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    fn draw(font: &FontRef, identifier: IconIdentifier) -> String {
+        let loc = Location::default();
+        let options = DrawOptions::new(identifier, 24.0, (&loc).into(), PathStyle::Unchanged);
+        draw_icon(font, &options).unwrap()
+    }
+
+    #[test]
+    fn roundtrip_svg() {
+        let font_data = build_test_font();
+        let font = FontRef::new(&font_data).unwrap();
+        assert_eq!(
+            testdata::LAN_SVG.trim(),
+            draw(&font, IconIdentifier::Name("lan".into()))
+        );
+        assert_eq!(
+            draw(&font, IconIdentifier::Name("lan".into())),
+            draw(&font, IconIdentifier::Codepoint(0xEB2F))
+        );
+    }
+
+    #[test]
+    fn assigns_codepoints_and_ligatures() {
+        let font_data = build_test_font();
+        let font = FontRef::new(&font_data).unwrap();
+        let mut icons = font.icons().unwrap();
+        icons.sort_by_key(|i| i.gid);
+        let first_icon = font.charmap().map('\u{EB2F}').unwrap().to_u16();
+        assert_eq!(
+            vec![
+                Icon::new("lan", [0xEB2F], first_icon),
+                Icon::new("mail", [0xE000], first_icon + 1),
+                Icon::new("x", [0xE001], first_icon + 2),
+                Icon::new("mail_outline", [0xE002], first_icon + 3),
+            ],
+            icons
+        );
+        // The longer name must not be shadowed by the shorter
+        assert_eq!(
+            Some(GlyphId::new(first_icon + 3)),
+            font.resolve_ligature("mail_outline").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let mut builder = IconFontBuilder::new("Test Icons");
+        builder.add_svg("lan", None, testdata::LAN_SVG).unwrap();
+        assert!(builder.add_svg("lan", None, testdata::LAN_SVG).is_err());
+        assert!(builder
+            .add_svg("bad-name", None, testdata::LAN_SVG)
+            .is_err());
+        assert!(builder
+            .add_svg("no_viewbox", None, r#"<svg><path d="M0,0L1,1Z"/></svg>"#)
+            .is_err());
+        assert!(builder
+            .add_svg(
+                "transformed",
+                None,
+                r#"<svg viewBox="0 0 24 24"><path transform="scale(2)" d="M0,0L1,1Z"/></svg>"#
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn codepoints_file() {
+        assert_eq!(
+            HashMap::from([("mail".to_string(), 0xE158), ("lan".to_string(), 0xEB2F)]),
+            parse_codepoints("mail e158\nlan eb2f\n").unwrap()
+        );
+        assert!(parse_codepoints("mail").is_err());
+    }
+}
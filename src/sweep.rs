@@ -0,0 +1,164 @@
+//! Renders an icon at a series of positions along an axis, for animation frames and strips
+
+use skrifa::{
+    instance::{Location, LocationRef},
+    FontRef, MetadataProvider, Tag,
+};
+
+use crate::{
+    document::{user_location, IconDocument},
+    error::DrawSvgError,
+    icon2svg::{write_svg, DrawOptions},
+    iconid::IconIdentifier,
+};
+
+/// Draws the icon in `options` at `steps` evenly spaced user coordinates of `axis`, from
+/// `from` to `to` inclusive, returning an svg per step.
+///
+/// The rest of the location comes from `options`. The icon is looked up once and only location
+/// based substitution, e.g. for FILL, is redone per step.
+pub fn draw_axis_sweep(
+    font: &FontRef,
+    options: &DrawOptions<'_>,
+    axis: &str,
+    (from, to): (f32, f32),
+    steps: usize,
+) -> Result<Vec<String>, DrawSvgError> {
+    let axes = font.axes();
+    let index = Tag::new_checked(axis.as_bytes())
+        .ok()
+        .and_then(|tag| axes.get_by_tag(tag))
+        .ok_or_else(|| DrawSvgError::NoAxis(axis.to_string()))?
+        .index();
+    // Drawing by glyph id skips the ligature lookup but still substitutes by location
+    let identifier = match options.identifier.resolve_unsubstituted(font) {
+        Ok(gid) => IconIdentifier::GlyphId(gid),
+        // Let each step fail, or fall back, as a plain draw would
+        Err(_) => options.identifier.clone(),
+    };
+
+    // User coordinates are folded into the base so they can't override the swept axis
+    let user = user_location(font, options);
+    let base = user
+        .as_ref()
+        .map(LocationRef::from)
+        .unwrap_or(options.location)
+        .coords();
+    let mut location = Location::new(axes.len());
+    let len = base.len().min(axes.len());
+    location.coords_mut()[..len].copy_from_slice(&base[..len]);
+
+    (0..steps)
+        .map(|step| {
+            let t = if steps > 1 {
+                step as f32 / (steps - 1) as f32
+            } else {
+                0.0
+            };
+            let value = from + (to - from) * t;
+            location.coords_mut()[index] = axes.location([(axis, value)]).coords()[index];
+            let mut frame = options.clone();
+            frame.identifier = identifier.clone();
+            frame.location = (&location).into();
+            frame.user_coords.clear();
+            Ok(write_svg(&IconDocument::draw(font, &frame)?, &frame))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::{FontRef, MetadataProvider};
+
+    use crate::{
+        error::DrawSvgError,
+        icon2svg::{draw_icon, DrawOptions},
+        iconid,
+        pathstyle::PathStyle,
+        sweep::draw_axis_sweep,
+        testdata,
+    };
+
+    #[test]
+    fn sweep_matches_individual_draws() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let base = font.axes().location(&[("wght", 700.0)]);
+        let options = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&base).into(),
+            PathStyle::Compact,
+        );
+
+        let frames = draw_axis_sweep(&font, &options, "FILL", (0.0, 1.0), 3).unwrap();
+
+        assert_eq!(3, frames.len());
+        for (frame, fill) in frames.iter().zip([0.0, 0.5, 1.0]) {
+            let loc = font.axes().location(&[("wght", 700.0), ("FILL", fill)]);
+            let expected = draw_icon(
+                &font,
+                &DrawOptions::new(
+                    iconid::MAIL.clone(),
+                    24.0,
+                    (&loc).into(),
+                    PathStyle::Compact,
+                ),
+            )
+            .unwrap();
+            assert_eq!(&expected, frame, "FILL {fill}");
+        }
+        assert_ne!(frames[0], frames[2]);
+    }
+
+    #[test]
+    fn user_coords_fold_into_base() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let bold = font.axes().location(&[("wght", 700.0)]);
+        let user = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        )
+        .with_user_coords([("wght", 700.0), ("FILL", 0.5)]);
+        let located = DrawOptions::new(
+            iconid::MAIL.clone(),
+            24.0,
+            (&bold).into(),
+            PathStyle::Compact,
+        );
+
+        assert_eq!(
+            draw_axis_sweep(&font, &located, "FILL", (0.0, 1.0), 3).unwrap(),
+            draw_axis_sweep(&font, &user, "FILL", (0.0, 1.0), 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn single_step_is_start() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let loc = font.axes().location(&[("wght", 100.0)]);
+        let options =
+            DrawOptions::new(iconid::LAN.clone(), 24.0, (&loc).into(), PathStyle::Compact);
+
+        let frames = draw_axis_sweep(&font, &options, "wght", (100.0, 700.0), 1).unwrap();
+
+        assert_eq!(vec![draw_icon(&font, &options).unwrap()], frames);
+    }
+
+    #[test]
+    fn missing_axis() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let options = DrawOptions::new(
+            iconid::LAN.clone(),
+            24.0,
+            Default::default(),
+            PathStyle::Compact,
+        );
+
+        assert!(matches!(
+            draw_axis_sweep(&font, &options, "wdth", (50.0, 100.0), 4),
+            Err(DrawSvgError::NoAxis(axis)) if axis == "wdth"
+        ));
+    }
+}
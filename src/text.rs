@@ -0,0 +1,255 @@
+//! Lays out a run of text as a single outline, for wordmarks and labels
+
+use kurbo::{Affine, BezPath};
+use skrifa::{
+    instance::{LocationRef, Size},
+    FontRef, MetadataProvider, Tag,
+};
+
+use crate::{
+    document::draw_glyph_id,
+    error::{DrawSvgError, IconResolutionError},
+    iconid::IconIdentifier,
+    ligatures::shape_glyphs,
+};
+
+/// A run of text drawn in font units, Y-down with the baseline at y=0 and the pen starting at x=0
+#[derive(Clone, Debug, Default)]
+pub struct TextPath {
+    pub path: BezPath,
+    /// Where the pen ends up, where the next run would start
+    pub advance: f64,
+}
+
+/// Extra space between characters, in font units, as CSS letter-spacing and word-spacing
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spacing {
+    /// Added after every character, including the last, may be negative
+    pub letter: f64,
+    /// Added after every space (U+0020 or U+00A0) on top of `letter`, may be negative
+    pub word: f64,
+}
+
+/// Draws `text` left to right, each glyph placed at the advance of those before it.
+///
+/// Characters are mapped through the cmap then substituted as a shaper would with its default
+/// features and `features`, e.g. `ss01`, on, see [crate::ligatures], then as
+/// [IconIdentifier::resolve] substitutes for `location`. Glyphs are not positioned
+/// so there is no kerning and no complex script support. Glyphs without an outline, such as
+/// spaces, just advance the pen.
+pub fn draw_text(
+    font: &FontRef,
+    text: &str,
+    location: LocationRef,
+    features: &[Tag],
+) -> Result<TextPath, DrawSvgError> {
+    draw_text_with_spacing(font, text, location, features, Spacing::default())
+}
+
+/// As [draw_text], with `spacing` added to the advances, e.g. to match CSS styled text.
+///
+/// Letter spacing follows each glyph, so a ligature is spaced as one character.
+pub fn draw_text_with_spacing(
+    font: &FontRef,
+    text: &str,
+    location: LocationRef,
+    features: &[Tag],
+    spacing: Spacing,
+) -> Result<TextPath, DrawSvgError> {
+    let charmap = font.charmap();
+    let gids = text
+        .chars()
+        .map(|c| {
+            charmap.map(c).ok_or(DrawSvgError::ResolutionError(
+                IconIdentifier::Codepoint(c as u32),
+                IconResolutionError::UnmappedCharError(c),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let gids =
+        shape_glyphs(font, &gids, features).map_err(|e| DrawSvgError::ReadError("GSUB", e))?;
+    let spaces = [' ', '\u{00A0}'].map(|c| charmap.map(c));
+
+    let glyph_metrics = font.glyph_metrics(Size::unscaled(), location);
+    let mut drawn = TextPath::default();
+    for gid in gids {
+        // Swap in the glyphs the location asks for, e.g. filled at FILL 1, as icons do
+        let identifier = IconIdentifier::GlyphId(gid);
+        let gid = identifier
+            .resolve(font, &location)
+            .map_err(|e| DrawSvgError::ResolutionError(identifier.clone(), e))?;
+        match draw_glyph_id(font, &identifier, gid, location) {
+            Ok(mut path) => {
+                path.apply_affine(Affine::translate((drawn.advance, 0.0)));
+                drawn.path.extend(path);
+            }
+            Err(DrawSvgError::NoOutline(..)) => (),
+            Err(e) => return Err(e),
+        }
+        drawn.advance += glyph_metrics.advance_width(gid).unwrap_or_default() as f64;
+        drawn.advance += spacing.letter;
+        if spaces.contains(&Some(gid)) {
+            drawn.advance += spacing.word;
+        }
+    }
+    Ok(drawn)
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{Affine, Shape};
+    use skrifa::{
+        instance::{LocationRef, Size},
+        FontRef, GlyphId, MetadataProvider, Tag,
+    };
+    use write_fonts::{
+        tables::{
+            gsub::{Gsub, SingleSubst, SubstitutionLookup, SubstitutionLookupList},
+            layout::{CoverageTable, Feature, FeatureList, FeatureRecord, Lookup, LookupFlag},
+        },
+        FontBuilder,
+    };
+
+    use crate::{
+        document::draw_glyph_id,
+        error::DrawSvgError,
+        iconid, testdata,
+        text::{draw_text, draw_text_with_spacing, Spacing},
+    };
+
+    #[test]
+    fn characters_follow_advances() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let gid = font.charmap().map('.').unwrap();
+        let advance = font
+            .glyph_metrics(Size::unscaled(), LocationRef::default())
+            .advance_width(gid)
+            .unwrap() as f64;
+
+        let one = draw_text(&font, ".", Default::default(), &[]).unwrap();
+        let two = draw_text(&font, "..", Default::default(), &[]).unwrap();
+
+        assert_eq!(advance, one.advance);
+        assert_eq!(2.0 * advance, two.advance);
+        let mut second = one.path.clone();
+        second.apply_affine(Affine::translate((advance, 0.0)));
+        let mut expected = one.path.clone();
+        expected.extend(second);
+        assert_eq!(expected, two.path);
+        assert!(two.path.bounding_box().x1 > advance);
+    }
+
+    #[test]
+    fn letter_and_word_spacing() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+        let plain = draw_text(&font, ". .", Default::default(), &[]).unwrap();
+        let dot = draw_text(&font, ".", Default::default(), &[]).unwrap();
+        let spacing = Spacing {
+            letter: 10.0,
+            word: 100.0,
+        };
+
+        let spaced =
+            draw_text_with_spacing(&font, ". .", Default::default(), &[], spacing).unwrap();
+
+        assert_eq!(plain.advance + 3.0 * 10.0 + 100.0, spaced.advance);
+        // The second dot moves by two letter spacings and a word spacing
+        let mut expected = dot.path.clone();
+        let mut second = dot.path.clone();
+        second.apply_affine(Affine::translate((
+            plain.advance - dot.advance + 2.0 * 10.0 + 100.0,
+            0.0,
+        )));
+        expected.extend(second);
+        assert_eq!(expected, spaced.path);
+    }
+
+    #[test]
+    fn empty_text() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+
+        let drawn = draw_text(&font, "", Default::default(), &[]).unwrap();
+
+        assert_eq!(0.0, drawn.advance);
+        assert!(drawn.path.elements().is_empty());
+    }
+
+    #[test]
+    fn unmapped_character() {
+        let font = FontRef::new(testdata::MOSTLY_OFF_CURVE_FONT).unwrap();
+
+        assert!(matches!(
+            draw_text(&font, ".\u{10FFFD}", Default::default(), &[]),
+            Err(DrawSvgError::ResolutionError(..))
+        ));
+    }
+
+    #[test]
+    fn ligatures_are_substituted() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let lan = GlyphId::new(3);
+
+        let drawn = draw_text(&font, "lan", Default::default(), &[]).unwrap();
+
+        assert_eq!(
+            draw_glyph_id(&font, &iconid::LAN, lan, LocationRef::default()).unwrap(),
+            drawn.path
+        );
+        assert_eq!(
+            font.glyph_metrics(Size::unscaled(), LocationRef::default())
+                .advance_width(lan)
+                .unwrap() as f64,
+            drawn.advance
+        );
+    }
+
+    #[test]
+    fn features_are_applied() {
+        // ss01 swaps 'a' for the lan icon
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let a = font.charmap().map('a').unwrap();
+        let lookups = SubstitutionLookupList::new(vec![SubstitutionLookup::Single(Lookup::new(
+            LookupFlag::empty(),
+            vec![SingleSubst::format_1(
+                CoverageTable::format_1(vec![a]),
+                3 - a.to_u16() as i16,
+            )],
+            0,
+        ))]);
+        let features = FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"ss01"),
+            Feature::new(None, vec![0]),
+        )]);
+        let gsub = Gsub::new(Default::default(), features, lookups);
+        let font_data = FontBuilder::new()
+            .add_table(&gsub)
+            .unwrap()
+            .copy_missing_tables(font)
+            .build();
+        let font = FontRef::new(&font_data).unwrap();
+        let lan =
+            draw_glyph_id(&font, &iconid::LAN, GlyphId::new(3), LocationRef::default()).unwrap();
+
+        let plain = draw_text(&font, "a", Default::default(), &[]).unwrap();
+        let ss01 = draw_text(&font, "a", Default::default(), &[Tag::new(b"ss01")]).unwrap();
+
+        assert_ne!(lan, plain.path);
+        assert_eq!(lan, ss01.path);
+    }
+
+    #[test]
+    fn substituted_for_location() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let filled = font.axes().location([("FILL", 1.0)]);
+        let filled = LocationRef::from(&filled);
+        let filled_lan = iconid::LAN.resolve(&font, &filled).unwrap();
+        assert_ne!(GlyphId::new(3), filled_lan);
+
+        let drawn = draw_text(&font, "lan", filled, &[]).unwrap();
+
+        assert_eq!(
+            draw_glyph_id(&font, &iconid::LAN, filled_lan, filled).unwrap(),
+            drawn.path
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Bindings for use from JavaScript, e.g. to customize icons client-side in a browser.
+//!
+//! Fonts are passed in as a `Uint8Array` and options and results are plain objects. Errors
+//! from drawing start with their [crate::Error::code], e.g. `no_cmap_entry: ...`.
+
+use serde::Serialize;
+use skrifa::FontRef;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    cmp,
+    error::{DrawSvgError, IconResolutionError},
+    icon2svg,
+    iconid::Icons,
+    owned::DrawConfig,
+    Error,
+};
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsIcon {
+    gid: u32,
+    names: Vec<String>,
+    codepoints: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsCompareResult {
+    added: Vec<String>,
+    modified: Vec<String>,
+    removed: Vec<String>,
+}
+
+fn read_font(font: &[u8]) -> Result<FontRef<'_>, JsError> {
+    FontRef::new(font).map_err(|e| JsError::new(&format!("Unable to load font: {e}")))
+}
+
+fn js_error(error: impl Into<Error>) -> JsError {
+    let error = error.into();
+    JsError::new(&format!("{}: {error}", error.code()))
+}
+
+fn to_js_value(value: &impl Serialize) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Draws an icon as an svg string, options are a [DrawConfig], e.g.
+/// `{ identifier: { name: "mail" }, width_height: 24, user_coords: { wght: 700, FILL: 1 },
+/// style: "compact" }`
+///
+/// Identifiers are `{ name: "mail" }`, `{ codepoint: 0xe158 }`, `{ glyphId: 5 }` or
+/// `{ sequence: [0x1f1fa, 0x1f1f8] }`. Axes that are not specified are at default.
+#[wasm_bindgen(js_name = drawIcon)]
+pub fn draw_icon(font: &[u8], options: JsValue) -> Result<String, JsError> {
+    let config: DrawConfig =
+        serde_wasm_bindgen::from_value(options).map_err(|e| JsError::new(&e.to_string()))?;
+    draw_icon_impl(&read_font(font)?, &config).map_err(js_error)
+}
+
+fn draw_icon_impl(font: &FontRef, config: &DrawConfig) -> Result<String, DrawSvgError> {
+    icon2svg::draw_icon(font, &config.draw_options())
+}
+
+/// Lists the icons in a font as `[{ gid, names, codepoints }]`
+#[wasm_bindgen(js_name = getIcons)]
+pub fn get_icons(font: &[u8]) -> Result<JsValue, JsError> {
+    let icons = get_icons_impl(&read_font(font)?).map_err(js_error)?;
+    to_js_value(&icons)
+}
+
+fn get_icons_impl(font: &FontRef) -> Result<Vec<JsIcon>, IconResolutionError> {
+    Ok(font
+        .icons()?
+        .into_iter()
+        .map(|icon| JsIcon {
+            gid: icon.gid.to_u32(),
+            names: icon.names,
+            codepoints: icon.codepoints,
+        })
+        .collect())
+}
+
+/// Compares two icon fonts, returning `{ added, modified, removed }` lists of icon names
+#[wasm_bindgen(js_name = compareFonts)]
+pub fn compare_fonts(old: &[u8], new: &[u8]) -> Result<JsValue, JsError> {
+    let result = cmp::compare_fonts(&read_font(old)?, &read_font(new)?).map_err(js_error)?;
+    to_js_value(&JsCompareResult {
+        added: result.added,
+        modified: result.modified,
+        removed: result.removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::testdata;
+
+    use crate::owned::DrawConfig;
+
+    use super::{draw_icon_impl, get_icons_impl};
+
+    fn draw_config(json: &str) -> DrawConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn draw_with_user_location() {
+        let font = FontRef::new(testdata::ICON_FONT).unwrap();
+        let config = draw_config(
+            r#"{ "identifier": { "codepoint": 57688 }, "width_height": 24,
+                 "user_coords": { "wght": 400, "opsz": 24, "GRAD": 0, "FILL": 1 } }"#,
+        );
+        assert_eq!(
+            testdata::MAIL_SVG.trim(),
+            draw_icon_impl(&font, &config).unwrap()
+        );
+    }
+
+    #[test]
+    fn draw_by_name_compact() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let config = draw_config(
+            r#"{ "identifier": { "name": "info" }, "width_height": 24, "style": "compact" }"#,
+        );
+        assert_eq!(
+            testdata::INFO_COMPACT_SVG.trim(),
+            draw_icon_impl(&font, &config).unwrap()
+        );
+    }
+
+    #[test]
+    fn icons_are_plain_data() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let icons = get_icons_impl(&font).unwrap();
+        assert!(icons
+            .iter()
+            .any(|icon| icon.gid == 31 && icon.names.contains(&"mic".to_string())));
+    }
+}
@@ -0,0 +1,187 @@
+//! Produces a stylesheet to use an icon font as a webfont, with one class per icon
+
+use skrifa::FontRef;
+
+use crate::{error::IconResolutionError, iconid::Icons};
+
+pub struct WebfontCssOptions {
+    family: String,
+    sources: Vec<String>,
+    class_prefix: String,
+}
+
+impl WebfontCssOptions {
+    /// Options for `family` with classes named `icon-<name>`
+    pub fn new(family: &str) -> WebfontCssOptions {
+        WebfontCssOptions {
+            family: family.to_string(),
+            sources: Vec::new(),
+            class_prefix: "icon".to_string(),
+        }
+    }
+
+    /// Adds a url to load the font from, format is inferred from the extension
+    pub fn with_source(mut self, url: &str) -> Self {
+        self.sources.push(url.to_string());
+        self
+    }
+
+    /// The base class, icon classes are `<prefix>-<name>`
+    pub fn with_class_prefix(mut self, class_prefix: &str) -> Self {
+        self.class_prefix = class_prefix.to_string();
+        self
+    }
+}
+
+/// Emits an `@font-face` rule, a base class and a `::before` rule per icon name.
+///
+/// Use as `<span class="icon icon-mail"></span>`. Icons are referenced by their first
+/// codepoint so the stylesheet works without ligature support.
+pub fn webfont_css(
+    font: &FontRef,
+    options: &WebfontCssOptions,
+) -> Result<String, IconResolutionError> {
+    let mut icons = font.icons()?;
+    icons.sort_by(|a, b| a.names.cmp(&b.names));
+
+    let family = escape_css_string(&options.family);
+    let prefix = escape_css_identifier(&options.class_prefix);
+    let mut css = String::new();
+
+    css.push_str("@font-face {\n");
+    css.push_str(&format!("  font-family: \"{family}\";\n"));
+    css.push_str("  font-style: normal;\n");
+    css.push_str("  font-display: block;\n");
+    if !options.sources.is_empty() {
+        let sources: Vec<_> = options
+            .sources
+            .iter()
+            .map(|url| {
+                let url = escape_css_string(url);
+                match font_format(&url) {
+                    Some(format) => format!("url(\"{url}\") format(\"{format}\")"),
+                    None => format!("url(\"{url}\")"),
+                }
+            })
+            .collect();
+        css.push_str(&format!("  src: {};\n", sources.join(",\n       ")));
+    }
+    css.push_str("}\n\n");
+
+    css.push_str(&format!(".{prefix} {{\n"));
+    css.push_str(&format!("  font-family: \"{family}\";\n"));
+    css.push_str("  font-weight: normal;\n");
+    css.push_str("  font-style: normal;\n");
+    css.push_str("  line-height: 1;\n");
+    css.push_str("  display: inline-block;\n");
+    css.push_str("  white-space: nowrap;\n");
+    css.push_str("  -webkit-font-smoothing: antialiased;\n");
+    css.push_str("}\n");
+
+    for icon in icons.iter() {
+        let Some(codepoint) = icon.codepoints.iter().min() else {
+            continue;
+        };
+        for name in icon.names.iter() {
+            css.push_str(&format!(
+                "\n.{prefix}-{}::before {{\n  content: \"\\{codepoint:x}\";\n}}\n",
+                escape_css_identifier(name)
+            ));
+        }
+    }
+    Ok(css)
+}
+
+fn font_format(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let (_, extension) = path.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "woff2" => Some("woff2"),
+        "woff" => Some("woff"),
+        "ttf" => Some("truetype"),
+        "otf" => Some("opentype"),
+        _ => None,
+    }
+}
+
+fn escape_css_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => escaped.push_str(&format!("\\{:x} ", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes anything that can't appear in the middle of a css identifier.
+///
+/// Names are appended to a prefix so a leading digit, as in `3d_rotation`, is fine.
+fn escape_css_identifier(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' || !c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use skrifa::FontRef;
+
+    use crate::{
+        testdata,
+        webfont::{escape_css_identifier, font_format, webfont_css, WebfontCssOptions},
+    };
+
+    #[test]
+    fn css_has_class_per_name() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let options = WebfontCssOptions::new("Material Symbols")
+            .with_source("icons.woff2")
+            .with_source("icons.ttf?v=2");
+
+        let css = webfont_css(&font, &options).unwrap();
+
+        assert!(css.contains(
+            "  src: url(\"icons.woff2\") format(\"woff2\"),\n       url(\"icons.ttf?v=2\") format(\"truetype\");\n"
+        ));
+        assert!(css.contains(".icon {\n  font-family: \"Material Symbols\";\n"));
+        assert!(css.contains("\n.icon-mic::before {\n  content: \"\\e029\";\n}\n"));
+        assert!(css.contains("\n.icon-mic_none::before {\n  content: \"\\e029\";\n}\n"));
+    }
+
+    #[test]
+    fn css_class_prefix() {
+        let font = FontRef::new(testdata::MATERIAL_SYMBOLS_POPULAR).unwrap();
+        let options = WebfontCssOptions::new("Material Symbols").with_class_prefix("ms");
+
+        let css = webfont_css(&font, &options).unwrap();
+
+        assert!(!css.contains("src:"));
+        assert!(css.contains(".ms-info::before"));
+    }
+
+    #[test]
+    fn formats_from_extension() {
+        assert_eq!(Some("woff"), font_format("https://example.com/a.WOFF#x"));
+        assert_eq!(Some("opentype"), font_format("a.otf"));
+        assert_eq!(None, font_format("font"));
+    }
+
+    #[test]
+    fn escapes_identifiers() {
+        assert_eq!("a\\.b\\ c", escape_css_identifier("a.b c"));
+        assert_eq!("3d_rotation", escape_css_identifier("3d_rotation"));
+    }
+}
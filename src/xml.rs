@@ -0,0 +1,956 @@
+//! A minimal XML document model used to build well-formed output.
+//!
+//! Attribute values and text are held unescaped and escaped as they are written.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io,
+};
+
+use crate::error::XmlError;
+
+/// An XML namespace, identified by its uri and bound to a prefix.
+///
+/// The empty prefix denotes the default namespace, e.g. `xmlns="http://www.w3.org/2000/svg"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Namespace<'a> {
+    pub prefix: &'a str,
+    pub uri: &'a str,
+}
+
+impl<'a> Namespace<'a> {
+    pub const SVG: Namespace<'static> = Namespace::new("", "http://www.w3.org/2000/svg");
+    pub const XLINK: Namespace<'static> = Namespace::new("xlink", "http://www.w3.org/1999/xlink");
+    pub const ANDROID: Namespace<'static> =
+        Namespace::new("android", "http://schemas.android.com/apk/res/android");
+    pub const AAPT: Namespace<'static> = Namespace::new("aapt", "http://schemas.android.com/aapt");
+
+    pub const fn new(prefix: &'a str, uri: &'a str) -> Namespace<'a> {
+        Namespace { prefix, uri }
+    }
+
+    /// The name of the attribute that declares this namespace
+    fn declaration_name(&self) -> String {
+        if self.prefix.is_empty() {
+            String::from("xmlns")
+        } else {
+            format!("xmlns:{}", self.prefix)
+        }
+    }
+
+    /// Qualify a local name with our prefix, e.g. `fillColor` => `android:fillColor`
+    pub fn qualify(&self, local_name: &str) -> String {
+        if self.prefix.is_empty() {
+            local_name.to_string()
+        } else {
+            format!("{}:{}", self.prefix, local_name)
+        }
+    }
+}
+
+/// Anything that can appear as the child of an element or in the prolog of a document
+#[derive(Debug, Clone, PartialEq)]
+pub enum XmlNode {
+    Element(XmlElement),
+    /// Character data, `&`, `<` and `>` are escaped
+    Text(String),
+    /// Written as `<![CDATA[text]]>`. A `]]>` sequence, which would end the section, is split.
+    CData(String),
+    /// Written as `<!--text-->`. A `-` followed by another, which may not appear in a comment,
+    /// or ending the text is followed by a space.
+    Comment(String),
+    /// Written as `<?target data?>`. A `?>` sequence, which would end the instruction, is split.
+    ProcessingInstruction {
+        target: String,
+        data: String,
+    },
+}
+
+impl XmlNode {
+    pub fn comment(text: impl Into<String>) -> XmlNode {
+        XmlNode::Comment(text.into())
+    }
+
+    pub fn text(text: impl Into<String>) -> XmlNode {
+        XmlNode::Text(text.into())
+    }
+
+    pub fn cdata(text: impl Into<String>) -> XmlNode {
+        XmlNode::CData(text.into())
+    }
+
+    pub fn processing_instruction(target: impl Into<String>, data: impl Into<String>) -> XmlNode {
+        XmlNode::ProcessingInstruction {
+            target: target.into(),
+            data: data.into(),
+        }
+    }
+
+    /// The `<?xml version="1.0" encoding="utf-8"?>` declaration
+    pub fn xml_declaration() -> XmlNode {
+        XmlNode::processing_instruction("xml", "version=\"1.0\" encoding=\"utf-8\"")
+    }
+
+    pub fn as_element(&self) -> Option<&XmlElement> {
+        match self {
+            XmlNode::Element(el) => Some(el),
+            _ => None,
+        }
+    }
+
+    pub fn as_element_mut(&mut self) -> Option<&mut XmlElement> {
+        match self {
+            XmlNode::Element(el) => Some(el),
+            _ => None,
+        }
+    }
+
+    /// Whitespace only text is dropped, yielding None. CDATA sections are reported as text.
+    fn from_roxmltree(node: roxmltree::Node) -> Result<Option<XmlNode>, XmlError> {
+        Ok(match node.node_type() {
+            roxmltree::NodeType::Element => Some(XmlElement::from_roxmltree(node)?.into()),
+            roxmltree::NodeType::Comment => node.text().map(XmlNode::comment),
+            roxmltree::NodeType::PI => node
+                .pi()
+                .map(|pi| XmlNode::processing_instruction(pi.target, pi.value.unwrap_or_default())),
+            roxmltree::NodeType::Text => node
+                .text()
+                .filter(|text| !text.trim().is_empty())
+                .map(XmlNode::text),
+            roxmltree::NodeType::Root => None,
+        })
+    }
+
+    fn write_indented(
+        &self,
+        f: &mut impl fmt::Write,
+        indent: Option<(usize, usize)>,
+    ) -> fmt::Result {
+        if let XmlNode::Element(el) = self {
+            return el.write_indented(f, indent);
+        }
+        if let Some((width, depth)) = indent {
+            write!(f, "{:1$}", "", width * depth)?;
+        }
+        match self {
+            XmlNode::Element(..) => unreachable!("Elements are written above"),
+            XmlNode::Text(text) => write_escaped(f, text, false),
+            XmlNode::CData(text) => {
+                write!(f, "<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+            }
+            XmlNode::Comment(text) => {
+                // A space after any '-' that is followed by another, or by the closing "-->"
+                let mut escaped = String::with_capacity(text.len());
+                let mut chars = text.chars().peekable();
+                while let Some(c) = chars.next() {
+                    escaped.push(c);
+                    if c == '-' && chars.peek().is_none_or(|next| *next == '-') {
+                        escaped.push(' ');
+                    }
+                }
+                write!(f, "<!--{escaped}-->")
+            }
+            XmlNode::ProcessingInstruction { target, data } if data.is_empty() => {
+                write!(f, "<?{target}?>")
+            }
+            XmlNode::ProcessingInstruction { target, data } => {
+                write!(f, "<?{target} {}?>", data.replace("?>", "? >"))
+            }
+        }
+    }
+}
+
+impl From<XmlElement> for XmlNode {
+    fn from(value: XmlElement) -> Self {
+        XmlNode::Element(value)
+    }
+}
+
+impl Display for XmlNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, XmlFormat::from_formatter(f).indent())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmlElement {
+    name: String,
+    /// (prefix, uri) pairs declared on this element, written before attributes
+    namespaces: Vec<(String, String)>,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+impl XmlElement {
+    pub fn new(name: impl Into<String>) -> XmlElement {
+        XmlElement {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Create an element whose name is in `namespace`, e.g. `aapt:attr`.
+    ///
+    /// The namespace is not declared, typically that happens once on the root.
+    pub fn new_ns(namespace: &Namespace, local_name: &str) -> XmlElement {
+        XmlElement::new(namespace.qualify(local_name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Splits the name into (prefix, local name). The prefix is empty if there isn't one.
+    pub fn split_name(&self) -> (&str, &str) {
+        split_qualified_name(&self.name)
+    }
+
+    /// Declare a namespace on this element, making its prefix available to it and its descendants.
+    ///
+    /// Redeclaring a prefix replaces the uri bound to it.
+    pub fn declare_namespace(&mut self, namespace: &Namespace) {
+        match self
+            .namespaces
+            .iter_mut()
+            .find(|(prefix, _)| prefix == namespace.prefix)
+        {
+            Some((_, uri)) => *uri = namespace.uri.to_string(),
+            None => self
+                .namespaces
+                .push((namespace.prefix.to_string(), namespace.uri.to_string())),
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: &Namespace) -> XmlElement {
+        self.declare_namespace(namespace);
+        self
+    }
+
+    /// The uri bound to `prefix` by a declaration on this element, if any
+    pub fn namespace_uri(&self, prefix: &str) -> Option<&str> {
+        self.namespaces
+            .iter()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| uri.as_str())
+    }
+
+    /// Set an attribute, replacing the value if it is already present.
+    pub fn set_attribute(&mut self, name: impl Into<String>, value: impl Display) {
+        let name = name.into();
+        let value = value.to_string();
+        match self.attributes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => *v = value,
+            None => self.attributes.push((name, value)),
+        }
+    }
+
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Display) -> XmlElement {
+        self.set_attribute(name, value);
+        self
+    }
+
+    /// Set an attribute whose name is in `namespace`, e.g. `android:pathData`.
+    pub fn set_ns_attribute(
+        &mut self,
+        namespace: &Namespace,
+        local_name: &str,
+        value: impl Display,
+    ) {
+        self.set_attribute(namespace.qualify(local_name), value);
+    }
+
+    pub fn with_ns_attribute(
+        mut self,
+        namespace: &Namespace,
+        local_name: &str,
+        value: impl Display,
+    ) -> XmlElement {
+        self.set_ns_attribute(namespace, local_name, value);
+        self
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn ns_attribute(&self, namespace: &Namespace, local_name: &str) -> Option<&str> {
+        self.attribute(&namespace.qualify(local_name))
+    }
+
+    /// Append a child, typically an [XmlElement], [XmlNode::Text] or [XmlNode::Comment]
+    pub fn add_child(&mut self, child: impl Into<XmlNode>) {
+        self.children.push(child.into());
+    }
+
+    pub fn with_child(mut self, child: impl Into<XmlNode>) -> XmlElement {
+        self.add_child(child);
+        self
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let idx = self.attributes.iter().position(|(n, _)| n == name)?;
+        Some(self.attributes.remove(idx).1)
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<XmlNode> {
+        &mut self.children
+    }
+
+    /// Replace all children, returning the previous ones
+    pub fn replace_children(
+        &mut self,
+        children: impl IntoIterator<Item = impl Into<XmlNode>>,
+    ) -> Vec<XmlNode> {
+        std::mem::replace(
+            &mut self.children,
+            children.into_iter().map(Into::into).collect(),
+        )
+    }
+
+    /// Keep only the children for which `predicate` returns true
+    pub fn retain_children(&mut self, predicate: impl FnMut(&XmlNode) -> bool) {
+        self.children.retain(predicate);
+    }
+
+    /// This element and all elements beneath it, depth first in document order
+    pub fn descendants(&self) -> impl Iterator<Item = &XmlElement> {
+        let mut pending = vec![self];
+        std::iter::from_fn(move || {
+            let next = pending.pop()?;
+            pending.extend(next.child_elements().rev());
+            Some(next)
+        })
+    }
+
+    /// The first element, depth first, for which `predicate` returns true. Includes self.
+    pub fn find(&self, predicate: impl Fn(&XmlElement) -> bool) -> Option<&XmlElement> {
+        self.descendants().find(|el| predicate(el))
+    }
+
+    /// Mutable equivalent of [XmlElement::find]
+    pub fn find_mut(&mut self, predicate: impl Fn(&XmlElement) -> bool) -> Option<&mut XmlElement> {
+        self.find_mut_impl(&predicate)
+    }
+
+    fn find_mut_impl(
+        &mut self,
+        predicate: &impl Fn(&XmlElement) -> bool,
+    ) -> Option<&mut XmlElement> {
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| match child {
+            XmlNode::Element(el) => el.find_mut_impl(predicate),
+            _ => None,
+        })
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Option<&XmlElement> {
+        self.find(|el| el.attribute("id") == Some(id))
+    }
+
+    pub fn find_by_id_mut(&mut self, id: &str) -> Option<&mut XmlElement> {
+        self.find_mut(|el| el.attribute("id") == Some(id))
+    }
+
+    /// All elements, including self, with the given (qualified) name
+    pub fn find_by_tag<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.descendants().filter(move |el| el.name == name)
+    }
+
+    /// Stream the element to `writer` rather than building a String.
+    ///
+    /// Many small writes are made; prefer a buffered writer.
+    pub fn write_to(&self, writer: &mut impl io::Write, format: XmlFormat) -> io::Result<()> {
+        write_io(writer, |w| self.write_indented(w, format.indent()))
+    }
+
+    /// Parse a document and return the root element, discarding any prolog
+    pub fn parse(xml: &str) -> Result<XmlElement, XmlError> {
+        Ok(XmlDocument::parse(xml)?.root)
+    }
+
+    fn from_roxmltree(node: roxmltree::Node) -> Result<XmlElement, XmlError> {
+        let mut element = XmlElement::new(qualified_name(
+            &node,
+            node.tag_name().namespace(),
+            node.tag_name().name(),
+        ));
+
+        // Only the namespaces declared here, not those inherited from our parent
+        let inherited: Vec<_> = node
+            .parent_element()
+            .map(|p| p.namespaces().collect())
+            .unwrap_or_default();
+        for namespace in node.namespaces().filter(|ns| !inherited.contains(ns)) {
+            element.declare_namespace(&Namespace::new(
+                namespace.name().unwrap_or_default(),
+                namespace.uri(),
+            ));
+        }
+
+        for attr in node.attributes() {
+            element.set_attribute(
+                qualified_name(&node, attr.namespace(), attr.name()),
+                attr.value(),
+            );
+        }
+
+        for child in node.children() {
+            if let Some(child) = XmlNode::from_roxmltree(child)? {
+                element.add_child(child);
+            }
+        }
+        Ok(element)
+    }
+
+    pub fn with_comment(self, text: impl Into<String>) -> XmlElement {
+        self.with_child(XmlNode::comment(text))
+    }
+
+    pub fn with_text(self, text: impl Into<String>) -> XmlElement {
+        self.with_child(XmlNode::text(text))
+    }
+
+    /// The text and CDATA children joined, e.g. the content of a `<title>`
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                XmlNode::Text(text) | XmlNode::CData(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn children(&self) -> &[XmlNode] {
+        &self.children
+    }
+
+    /// The children that are elements, skipping comments and processing instructions
+    pub fn child_elements(&self) -> impl DoubleEndedIterator<Item = &XmlElement> {
+        self.children.iter().filter_map(XmlNode::as_element)
+    }
+
+    /// Prefixes used by element or attribute names in this tree that have no declaration in scope.
+    ///
+    /// The reserved `xml` and `xmlns` prefixes are always in scope. Empty means every prefixed name
+    /// will resolve when the document is parsed by a namespace aware reader.
+    pub fn undeclared_prefixes(&self) -> Vec<String> {
+        let mut undeclared = Vec::new();
+        let mut in_scope = vec!["xml".to_string(), "xmlns".to_string()];
+        self.collect_undeclared_prefixes(&mut in_scope, &mut undeclared);
+        undeclared
+    }
+
+    fn collect_undeclared_prefixes(
+        &self,
+        in_scope: &mut Vec<String>,
+        undeclared: &mut Vec<String>,
+    ) {
+        let scope_len = in_scope.len();
+        in_scope.extend(self.namespaces.iter().map(|(prefix, _)| prefix.clone()));
+
+        let used = once_prefix(&self.name).chain(self.attributes.iter().filter_map(|(n, _)| {
+            let (prefix, _) = split_qualified_name(n);
+            (!prefix.is_empty()).then_some(prefix)
+        }));
+        for prefix in used {
+            if !in_scope.iter().any(|p| p == prefix) && !undeclared.iter().any(|p| p == prefix) {
+                undeclared.push(prefix.to_string());
+            }
+        }
+
+        for child in self.child_elements() {
+            child.collect_undeclared_prefixes(in_scope, undeclared);
+        }
+        in_scope.truncate(scope_len);
+    }
+
+    fn write_indented(
+        &self,
+        f: &mut impl fmt::Write,
+        indent: Option<(usize, usize)>,
+    ) -> fmt::Result {
+        if let Some((width, depth)) = indent {
+            write!(f, "{:1$}", "", width * depth)?;
+        }
+        write!(f, "<{}", self.name)?;
+        for (prefix, uri) in self.namespaces.iter() {
+            let namespace = Namespace::new(prefix, uri);
+            write!(f, " {}=\"", namespace.declaration_name())?;
+            write_escaped(f, uri, true)?;
+            f.write_str("\"")?;
+        }
+        for (name, value) in self.attributes.iter() {
+            write!(f, " {name}=\"")?;
+            write_escaped(f, value, true)?;
+            f.write_str("\"")?;
+        }
+        if self.children.is_empty() {
+            return f.write_str("/>");
+        }
+        f.write_str(">")?;
+        // Indenting would add whitespace to text, so mixed content is written as is
+        let indent = indent.filter(|_| {
+            !self
+                .children
+                .iter()
+                .any(|child| matches!(child, XmlNode::Text(..) | XmlNode::CData(..)))
+        });
+        for child in self.children.iter() {
+            if indent.is_some() {
+                f.write_str("\n")?;
+            }
+            child.write_indented(f, indent.map(|(width, depth)| (width, depth + 1)))?;
+        }
+        if let Some((width, depth)) = indent {
+            write!(f, "\n{:1$}", "", width * depth)?;
+        }
+        write!(f, "</{}>", self.name)
+    }
+}
+
+fn split_qualified_name(name: &str) -> (&str, &str) {
+    name.split_once(':').unwrap_or(("", name))
+}
+
+fn once_prefix(name: &str) -> impl Iterator<Item = &str> {
+    let (prefix, _) = split_qualified_name(name);
+    (!prefix.is_empty()).then_some(prefix).into_iter()
+}
+
+/// Writes compact xml by default. The alternate flag, `{:#}`, writes one node per line indented
+/// by the requested width, 2 if none is given; `{:#4}` indents by 4.
+impl Display for XmlElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, XmlFormat::from_formatter(f).indent())
+    }
+}
+
+/// A root element preceded by a prolog of comments and processing instructions.
+///
+/// Formats the same way as [XmlElement], except that each prolog node always ends
+/// with a newline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XmlDocument {
+    pub prolog: Vec<XmlNode>,
+    pub root: XmlElement,
+}
+
+impl XmlDocument {
+    pub fn new(root: XmlElement) -> XmlDocument {
+        XmlDocument {
+            prolog: Vec::new(),
+            root,
+        }
+    }
+
+    /// Start the document with `<?xml version="1.0" encoding="utf-8"?>`
+    pub fn with_xml_declaration(mut self) -> XmlDocument {
+        self.prolog.insert(0, XmlNode::xml_declaration());
+        self
+    }
+
+    pub fn with_prolog(mut self, node: impl Into<XmlNode>) -> XmlDocument {
+        self.prolog.push(node.into());
+        self
+    }
+
+    pub fn with_comment(self, text: impl Into<String>) -> XmlDocument {
+        self.with_prolog(XmlNode::comment(text))
+    }
+
+    /// Stream the document to `writer` rather than building a String.
+    ///
+    /// Many small writes are made; prefer a buffered writer.
+    pub fn write_to(&self, writer: &mut impl io::Write, format: XmlFormat) -> io::Result<()> {
+        write_io(writer, |w| self.write_indented(w, format.indent()))
+    }
+
+    fn write_indented(
+        &self,
+        f: &mut impl fmt::Write,
+        indent: Option<(usize, usize)>,
+    ) -> fmt::Result {
+        for node in self.prolog.iter() {
+            node.write_indented(f, indent)?;
+            f.write_str("\n")?;
+        }
+        self.root.write_indented(f, indent)
+    }
+
+    /// Parse a document, keeping comments and processing instructions.
+    ///
+    /// Whitespace only text, such as that between elements, is discarded. The xml
+    /// declaration is not reported by the underlying parser so it is not part of the prolog.
+    pub fn parse(xml: &str) -> Result<XmlDocument, XmlError> {
+        let doc = roxmltree::Document::parse(xml)?;
+        let mut prolog = Vec::new();
+        for node in doc.root().children() {
+            if node.is_element() {
+                break;
+            }
+            if let Some(node) = XmlNode::from_roxmltree(node)? {
+                prolog.push(node);
+            }
+        }
+        Ok(XmlDocument {
+            prolog,
+            root: XmlElement::from_roxmltree(doc.root_element())?,
+        })
+    }
+}
+
+/// The name as `prefix:local` using whatever prefix is bound to the namespace uri at `node`
+fn qualified_name(node: &roxmltree::Node, namespace: Option<&str>, local_name: &str) -> String {
+    match namespace.and_then(|uri| node.lookup_prefix(uri)) {
+        Some(prefix) => Namespace::new(prefix, "").qualify(local_name),
+        None => local_name.to_string(),
+    }
+}
+
+/// Escapes markup in text, and quotes too in attribute values
+fn write_escaped(f: &mut impl fmt::Write, value: &str, attribute: bool) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '&' => f.write_str("&amp;")?,
+            '<' => f.write_str("&lt;")?,
+            '>' => f.write_str("&gt;")?,
+            '"' if attribute => f.write_str("&quot;")?,
+            '\'' if attribute => f.write_str("&apos;")?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+impl Display for XmlDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, XmlFormat::from_formatter(f).indent())
+    }
+}
+
+/// Layout of written xml, equivalent to the options supported by [Display]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum XmlFormat {
+    /// Everything on one line, as written by `{}`
+    #[default]
+    Compact,
+    /// One node per line indented by the given number of spaces per level, as written by `{:#n}`
+    Indented(usize),
+}
+
+impl XmlFormat {
+    fn from_formatter(f: &Formatter<'_>) -> XmlFormat {
+        if f.alternate() {
+            XmlFormat::Indented(f.width().unwrap_or(2))
+        } else {
+            XmlFormat::Compact
+        }
+    }
+
+    /// (width, depth) of indentation at the root, if any
+    fn indent(self) -> Option<(usize, usize)> {
+        match self {
+            XmlFormat::Compact => None,
+            XmlFormat::Indented(width) => Some((width, 0)),
+        }
+    }
+}
+
+/// Lets the [fmt::Write] based writers stream to an [io::Write], retaining the underlying error
+struct IoWriter<'a, W: io::Write> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+fn write_io<W: io::Write>(
+    inner: &mut W,
+    write: impl FnOnce(&mut IoWriter<W>) -> fmt::Result,
+) -> io::Result<()> {
+    let mut writer = IoWriter { inner, error: None };
+    write(&mut writer).map_err(|_| {
+        writer
+            .error
+            .take()
+            .unwrap_or_else(|| io::Error::other("Unable to format xml"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{Namespace, XmlDocument, XmlElement, XmlFormat, XmlNode};
+
+    fn vector_drawable() -> XmlElement {
+        XmlElement::new("vector")
+            .with_namespace(&Namespace::ANDROID)
+            .with_ns_attribute(&Namespace::ANDROID, "width", "24dp")
+            .with_child(XmlElement::new("path").with_ns_attribute(
+                &Namespace::ANDROID,
+                "pathData",
+                "M0,0L1,1Z",
+            ))
+    }
+
+    #[test]
+    fn declares_namespace_before_attributes() {
+        assert_eq!(
+            "<vector xmlns:android=\"http://schemas.android.com/apk/res/android\" android:width=\"24dp\"><path android:pathData=\"M0,0L1,1Z\"/></vector>",
+            vector_drawable().to_string()
+        );
+    }
+
+    #[test]
+    fn default_namespace() {
+        let svg = XmlElement::new("svg").with_namespace(&Namespace::SVG);
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+            svg.to_string()
+        );
+        assert_eq!(Some(Namespace::SVG.uri), svg.namespace_uri(""));
+    }
+
+    #[test]
+    fn redeclare_replaces_uri() {
+        let mut el = XmlElement::new("a").with_namespace(&Namespace::new("x", "urn:one"));
+        el.declare_namespace(&Namespace::new("x", "urn:two"));
+        assert_eq!("<a xmlns:x=\"urn:two\"/>", el.to_string());
+    }
+
+    #[test]
+    fn prefixed_element_names() {
+        let attr = XmlElement::new_ns(&Namespace::AAPT, "attr").with_ns_attribute(
+            &Namespace::ANDROID,
+            "name",
+            "android:fillColor",
+        );
+        assert_eq!(("aapt", "attr"), attr.split_name());
+        assert_eq!(
+            Some("android:fillColor"),
+            attr.ns_attribute(&Namespace::ANDROID, "name")
+        );
+    }
+
+    #[test]
+    fn finds_undeclared_prefixes() {
+        let doc = vector_drawable().with_child(XmlElement::new_ns(&Namespace::AAPT, "attr"));
+        assert_eq!(vec!["aapt".to_string()], doc.undeclared_prefixes());
+
+        let doc = doc.with_namespace(&Namespace::AAPT);
+        assert!(doc.undeclared_prefixes().is_empty());
+    }
+
+    #[test]
+    fn declarations_are_scoped_to_subtree() {
+        let doc = XmlElement::new("root")
+            .with_child(XmlElement::new("a").with_namespace(&Namespace::XLINK))
+            .with_child(XmlElement::new("b").with_ns_attribute(&Namespace::XLINK, "href", "#x"));
+        assert_eq!(vec!["xlink".to_string()], doc.undeclared_prefixes());
+    }
+
+    #[test]
+    fn pretty_print() {
+        assert_eq!(
+            "<vector xmlns:android=\"http://schemas.android.com/apk/res/android\" android:width=\"24dp\">\n    <path android:pathData=\"M0,0L1,1Z\"/>\n</vector>",
+            format!("{:#4}", vector_drawable())
+        );
+    }
+
+    #[test]
+    fn comments_and_children() {
+        let el = XmlElement::new("g")
+            .with_comment(" mail ")
+            .with_child(XmlElement::new("path"));
+        assert_eq!("<g><!-- mail --><path/></g>", el.to_string());
+        assert_eq!(1, el.child_elements().count());
+        assert_eq!("<g>\n  <!-- mail -->\n  <path/>\n</g>", format!("{el:#}"));
+    }
+
+    #[test]
+    fn comment_cannot_terminate_early() {
+        assert_eq!("<!--a- -b- -->", XmlNode::comment("a--b-").to_string());
+        assert_eq!("<!--- - - -->", XmlNode::comment("---").to_string());
+        assert_eq!("<!--a- -->", XmlNode::comment("a-").to_string());
+        for text in ["---", "a-", "a--b-", "-"] {
+            let xml = format!("<g>{}</g>", XmlNode::comment(text));
+            assert!(roxmltree::Document::parse(&xml).is_ok(), "{xml}");
+        }
+    }
+
+    #[test]
+    fn processing_instruction_cannot_terminate_early() {
+        let pi = XmlNode::processing_instruction("php", "echo '?>';");
+        assert_eq!("<?php echo '? >';?>", pi.to_string());
+        assert!(roxmltree::Document::parse(&format!("{pi}<g/>")).is_ok());
+    }
+
+    #[test]
+    fn document_with_prolog() {
+        let doc = XmlDocument::new(XmlElement::new("svg").with_namespace(&Namespace::SVG))
+            .with_comment(" Generated by sleipnir ")
+            .with_prolog(XmlNode::processing_instruction(
+                "xml-stylesheet",
+                "href=\"a.css\"",
+            ))
+            .with_xml_declaration();
+        assert_eq!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!-- Generated by sleipnir -->\n<?xml-stylesheet href=\"a.css\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+            doc.to_string()
+        );
+    }
+
+    const TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- A template -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <g id="Guides">
+    <line id="Baseline-S" x1="0" y1="1" x2="2" y2="1"/>
+  </g>
+  <g id="Symbols">
+    <g id="Regular-S"/>
+    <g id="Black-S"><!-- todo --></g>
+  </g>
+  <use xlink:href="&amp;x"/>
+</svg>"#;
+
+    #[test]
+    fn parse_roundtrip() {
+        let doc = XmlDocument::parse(TEMPLATE).unwrap();
+        assert_eq!(
+            "<!-- A template -->\n<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\"><g id=\"Guides\"><line id=\"Baseline-S\" x1=\"0\" y1=\"1\" x2=\"2\" y2=\"1\"/></g><g id=\"Symbols\"><g id=\"Regular-S\"/><g id=\"Black-S\"><!-- todo --></g></g><use xlink:href=\"&amp;x\"/></svg>",
+            doc.to_string()
+        );
+        assert!(doc.root.undeclared_prefixes().is_empty());
+    }
+
+    #[test]
+    fn parse_only_declares_namespaces_once() {
+        let el = XmlElement::parse(
+            "<a xmlns:x=\"urn:x\"><x:b x:c=\"1\"><d xmlns:y=\"urn:y\"/></x:b></a>",
+        )
+        .unwrap();
+        assert_eq!(
+            "<a xmlns:x=\"urn:x\"><x:b x:c=\"1\"><d xmlns:y=\"urn:y\"/></x:b></a>",
+            el.to_string()
+        );
+    }
+
+    #[test]
+    fn parse_keeps_text() {
+        let el = XmlElement::parse("<a>\n  <b>x &amp; y<![CDATA[ <z> ]]></b>\n</a>").unwrap();
+        assert_eq!("<a><b>x &amp; y &lt;z&gt; </b></a>", el.to_string());
+        assert_eq!("x & y <z> ", el.find_by_tag("b").next().unwrap().text());
+    }
+
+    #[test]
+    fn escapes_attributes() {
+        let el = XmlElement::new("a").with_attribute("title", "<\"Tom\" & 'Jerry'>");
+        assert_eq!(
+            "<a title=\"&lt;&quot;Tom&quot; &amp; &apos;Jerry&apos;&gt;\"/>",
+            el.to_string()
+        );
+        assert_eq!(Some("<\"Tom\" & 'Jerry'>"), el.attribute("title"));
+        assert_eq!(el, XmlElement::parse(&el.to_string()).unwrap());
+    }
+
+    #[test]
+    fn text_and_cdata() {
+        let el = XmlElement::new("title")
+            .with_text("a < b & c > d")
+            .with_child(XmlNode::cdata("x]]>y"));
+        assert_eq!(
+            "<title>a &lt; b &amp; c &gt; d<![CDATA[x]]]]><![CDATA[>y]]></title>",
+            el.to_string()
+        );
+        // Indenting would change the text
+        assert_eq!(
+            "<svg>\n  <title>a</title>\n</svg>",
+            format!(
+                "{:#}",
+                XmlElement::new("svg").with_child(XmlElement::new("title").with_text("a"))
+            )
+        );
+    }
+
+    #[test]
+    fn find_and_mutate() {
+        let mut svg = XmlElement::parse(TEMPLATE).unwrap();
+        assert_eq!(
+            vec!["Guides", "Symbols", "Regular-S", "Black-S"],
+            svg.find_by_tag("g")
+                .filter_map(|g| g.attribute("id"))
+                .collect::<Vec<_>>()
+        );
+
+        let regular = svg.find_by_id_mut("Regular-S").unwrap();
+        regular.replace_children([XmlElement::new("path").with_attribute("d", "M0,0Z")]);
+
+        let symbols = svg.find_by_id_mut("Symbols").unwrap();
+        symbols.retain_children(|child| {
+            child
+                .as_element()
+                .map(|el| !el.children().is_empty())
+                .unwrap_or_default()
+        });
+        svg.find_by_id_mut("Baseline-S")
+            .unwrap()
+            .remove_attribute("x2");
+
+        assert_eq!(
+            "<g id=\"Symbols\"><g id=\"Regular-S\"><path d=\"M0,0Z\"/></g><g id=\"Black-S\"><!-- todo --></g></g>",
+            svg.find_by_id("Symbols").unwrap().to_string()
+        );
+        assert_eq!(
+            "<line id=\"Baseline-S\" x1=\"0\" y1=\"1\" y2=\"1\"/>",
+            svg.find_by_id("Baseline-S").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn write_to_matches_display() {
+        let doc = XmlDocument::new(vector_drawable()).with_comment("hi");
+        for (format, expected) in [
+            (XmlFormat::Compact, doc.to_string()),
+            (XmlFormat::Indented(2), format!("{doc:#}")),
+            (XmlFormat::Indented(3), format!("{doc:#3}")),
+        ] {
+            let mut buf = Vec::new();
+            doc.write_to(&mut buf, format).unwrap();
+            assert_eq!(expected, String::from_utf8(buf).unwrap());
+        }
+    }
+
+    struct FullWriter;
+
+    impl io::Write for FullWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::StorageFull.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to_reports_io_error() {
+        let err = vector_drawable()
+            .write_to(&mut FullWriter, XmlFormat::Compact)
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::StorageFull, err.kind());
+    }
+}